@@ -2,6 +2,10 @@ use bevy::prelude::*;
 use bevy::gltf::Gltf;
 use std::collections::HashMap;
 use crate::schema::player::AnimationMap;
+use crate::schema::behavior::ANIMATION_FINISHED_EVENT;
+use crate::runtime::behavior::Behavior;
+use crate::runtime::messages::BehaviorMessage;
+use crate::runtime::events::EventId;
 
 #[derive(Component)]
 pub struct AnimationController {
@@ -12,17 +16,39 @@ pub struct AnimationController {
     pub gltf_handle: Handle<Gltf>,
     pub node_indices: HashMap<String, AnimationNodeIndex>,
     pub graph_initialized: bool,
+    /// Completed cycles of the current clip seen so far.
+    pub completions: u32,
+}
+
+impl AnimationController {
+    /// Controller for clips stored in the glTF file behind `model_path`
+    /// (any `#Scene0` style label is stripped).
+    pub fn new(model_path: &str, animations: AnimationMap, asset_server: &AssetServer) -> Self {
+        let gltf_path = model_path.split('#').next().unwrap_or("").to_string();
+        let gltf_handle = asset_server.load(gltf_path.clone());
+        Self {
+            current: animations.idle.clone(),
+            animations,
+            last_played: String::new(),
+            gltf_path,
+            gltf_handle,
+            node_indices: HashMap::new(),
+            graph_initialized: false,
+            completions: 0,
+        }
+    }
 }
 
 pub fn animation_playback_system(
     mut commands: Commands,
     gltfs: Res<Assets<Gltf>>,
     mut graphs: ResMut<Assets<AnimationGraph>>,
-    mut controller_query: Query<(Entity, &mut AnimationController)>,
+    mut controller_query: Query<(Entity, &mut AnimationController, Has<Behavior>)>,
     mut player_query: Query<&mut AnimationPlayer>,
     children_query: Query<&Children>,
+    mut behavior_messages: MessageWriter<BehaviorMessage>,
 ) {
-    for (entity, mut controller) in &mut controller_query {
+    for (entity, mut controller, has_behavior) in &mut controller_query {
         // 1. Initialize Graph if not done and GLTF is ready
         if !controller.graph_initialized {
             if let Some(gltf) = gltfs.get(&controller.gltf_handle) {
@@ -50,6 +76,18 @@ pub fn animation_playback_system(
                         indices.insert(name, index);
                     }
                 }
+
+                // Other clips in the file can still be played by `PlayAnimation`
+                let mut extra: Vec<_> = gltf
+                    .named_animations
+                    .iter()
+                    .filter(|(name, _)| !indices.contains_key(&***name))
+                    .collect();
+                extra.sort_by(|a, b| a.0.cmp(b.0));
+                for (name, clip) in extra {
+                    let index = graph.add_clip(clip.clone(), 1.0, graph.root);
+                    indices.insert(name.to_string(), index);
+                }
                 
                 let graph_handle = graphs.add(graph);
                 
@@ -68,12 +106,36 @@ pub fn animation_playback_system(
             if let Some(player_ent) = find_player_entity_recursive(entity, &player_query, &children_query) {
                 if let Ok(mut player) = player_query.get_mut(player_ent) {
                     if let Some(&index) = controller.node_indices.get(&controller.current) {
-                        player.play(index).repeat();
+                        // Deaths play once and hold their last pose.
+                        let animation = player.play(index);
+                        if controller.current != controller.animations.death {
+                            animation.repeat();
+                        }
                         controller.last_played = controller.current.clone();
+                        controller.completions = 0;
                     }
                 }
             }
         }
+
+        // 3. Tell the entity's behavior when the current clip completes
+        if has_behavior && controller.graph_initialized {
+            let completions = find_player_entity_recursive(entity, &player_query, &children_query)
+                .and_then(|player_ent| player_query.get(player_ent).ok())
+                .zip(controller.node_indices.get(&controller.last_played))
+                .and_then(|(player, &index)| player.animation(index))
+                .map(|active| active.completions());
+            if let Some(completions) = completions {
+                if completions > controller.completions {
+                    controller.completions = completions;
+                    behavior_messages.write(BehaviorMessage {
+                        entity,
+                        event: EventId::of(ANIMATION_FINISHED_EVENT),
+                        other: None,
+                    });
+                }
+            }
+        }
     }
 }
 
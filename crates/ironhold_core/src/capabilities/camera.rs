@@ -1,5 +1,8 @@
 use bevy::prelude::*;
 use crate::capabilities::player::CharacterController;
+use crate::schema::player::{CameraConfig, Steering};
+use crate::schema::input::*;
+use crate::runtime::input::InputActions;
 
 #[derive(Component)]
 pub struct OrbitCamera {
@@ -13,23 +16,39 @@ pub struct OrbitCamera {
     pub pitch: f32,
     pub yaw: f32,
     pub look_at_offset: Vec3,
+    /// Turn of the target, in radians, still to be applied by the next
+    /// gameplay tick while steering.
+    pub steer: f32,
 }
 
+impl OrbitCamera {
+    pub fn new(target: Entity, config: &CameraConfig) -> Self {
+        Self {
+            target,
+            radius: Vec3::from(config.offset).length(),
+            offset: Vec3::from(config.offset),
+            zoom_speed: config.zoom_speed,
+            orbit_speed: config.orbit_speed,
+            min_radius: config.min_radius,
+            max_radius: config.max_radius,
+            pitch: 0.5, // Approx starting pitch
+            yaw: 0.0,
+            look_at_offset: Vec3::from(config.look_at_offset),
+            steer: 0.0,
+        }
+    }
+}
+
+/// Orbits and zooms the camera every frame and follows the target's
+/// interpolated transform.
 pub fn camera_orbit_system(
     time: Res<Time>,
-    mut mouse_motion_events: MessageReader<bevy::input::mouse::MouseMotion>,
-    mut mouse_wheel_events: MessageReader<bevy::input::mouse::MouseWheel>,
-    mouse_button_input: Res<ButtonInput<MouseButton>>,
+    actions: Res<InputActions>,
     mut camera_query: Query<(&mut Transform, &mut OrbitCamera), Without<CharacterController>>,
-    mut character_query: Query<&mut Transform, (With<CharacterController>, Without<OrbitCamera>)>,
+    character_query: Query<&Transform, (With<CharacterController>, Without<OrbitCamera>)>,
 ) {
-    // Collect mouse motion
-    let mut mouse_delta = Vec2::ZERO;
-    for event in mouse_motion_events.read() {
-        mouse_delta += event.delta;
-    }
-
-    let zoom_delta: f32 = mouse_wheel_events.read().map(|e| e.y).sum();
+    let orbit_delta = actions.axis_2d(CAMERA_ORBIT_ACTION);
+    let zoom_delta = actions.axis(CAMERA_ZOOM_ACTION);
 
     for (mut cam_transform, mut orbit) in &mut camera_query {
         // Zoom
@@ -39,24 +58,19 @@ pub fn camera_orbit_system(
         }
 
         // Orbit Logic
-        let lmb_pressed = mouse_button_input.pressed(MouseButton::Left);
-        let rmb_pressed = mouse_button_input.pressed(MouseButton::Right);
-
-        if lmb_pressed || rmb_pressed {
+        if orbit_delta != Vec2::ZERO {
             // Yaw (Left/Right)
-            orbit.yaw -= mouse_delta.x * orbit.orbit_speed * time.delta_secs();
+            orbit.yaw -= orbit_delta.x * orbit.orbit_speed * time.delta_secs();
             
             // Pitch (Up/Down)
-            orbit.pitch -= mouse_delta.y * orbit.orbit_speed * time.delta_secs();
+            orbit.pitch -= orbit_delta.y * orbit.orbit_speed * time.delta_secs();
             // Clamp pitch to avoid flipping
             orbit.pitch = orbit.pitch.clamp(0.1, 1.5); 
         }
         
-        // If RMB pressed, also rotate character if possible
-        if rmb_pressed {
-             if let Ok(mut char_transform) = character_query.get_mut(orbit.target) {
-                 char_transform.rotate_y(-mouse_delta.x * orbit.orbit_speed * time.delta_secs());
-             }
+        // While steering, also rotate character on the next tick
+        if actions.pressed(CAMERA_STEER_ACTION) && character_query.contains(orbit.target) {
+            orbit.steer -= orbit_delta.x * orbit.orbit_speed * time.delta_secs();
         }
 
         // Update Camera Position
@@ -72,3 +86,22 @@ pub fn camera_orbit_system(
         }
     }
 }
+
+/// Turns each camera's target by the steering gathered since the last tick.
+/// Camera-relative characters face where they move instead.
+pub fn camera_steer_system(
+    mut camera_query: Query<&mut OrbitCamera>,
+    mut character_query: Query<(&mut Transform, &CharacterController)>,
+) {
+    for mut orbit in &mut camera_query {
+        if orbit.steer == 0.0 {
+            continue;
+        }
+        if let Ok((mut char_transform, controller)) = character_query.get_mut(orbit.target) {
+            if controller.movement.steering == Steering::Tank {
+                char_transform.rotate_y(orbit.steer);
+            }
+        }
+        orbit.steer = 0.0;
+    }
+}
@@ -0,0 +1,39 @@
+use bevy::prelude::*;
+use crate::schema::entity::ColliderShape;
+
+/// Collision volume centred on the entity's transform.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Collider {
+    pub shape: ColliderShape,
+    pub is_trigger: bool,
+}
+
+impl Collider {
+    /// Whether `point`, in the collider's local space, lies inside the shape.
+    pub fn contains(&self, point: Vec3) -> bool {
+        match self.shape {
+            ColliderShape::Box(x, y, z) => {
+                point.x.abs() <= x && point.y.abs() <= y && point.z.abs() <= z
+            }
+            ColliderShape::Sphere(radius) => point.length() <= radius,
+            ColliderShape::Capsule(radius, half_height) => {
+                let axis = Vec3::new(0.0, point.y.clamp(-half_height, half_height), 0.0);
+                point.distance(axis) <= radius
+            }
+        }
+    }
+
+    /// Height of the top of the shape above `(x, z)`, in the collider's local
+    /// space, or `None` if the shape does not reach over that point.
+    pub fn top(&self, x: f32, z: f32) -> Option<f32> {
+        let cap = |radius: f32| {
+            let squared = radius * radius - x * x - z * z;
+            (squared >= 0.0).then(|| squared.sqrt())
+        };
+        match self.shape {
+            ColliderShape::Box(hx, hy, hz) => (x.abs() <= hx && z.abs() <= hz).then_some(hy),
+            ColliderShape::Sphere(radius) => cap(radius),
+            ColliderShape::Capsule(radius, half_height) => cap(radius).map(|top| top + half_height),
+        }
+    }
+}
@@ -1,7 +1,13 @@
 pub mod player;
 pub mod camera;
 pub mod animation;
+pub mod collider;
+pub mod velocity;
+pub mod rebind;
 
 pub use player::*;
 pub use camera::*;
 pub use animation::*;
+pub use collider::*;
+pub use velocity::*;
+pub use rebind::*;
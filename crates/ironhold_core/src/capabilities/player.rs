@@ -1,81 +1,424 @@
 use bevy::prelude::*;
-use crate::schema::player::InputMap;
+use bevy::transform::helper::TransformHelper;
+use serde::{Deserialize, Serialize};
+use crate::schema::player::{AnimationMap, ButtonMode, InputMap, MovementConfig, Steering};
+use crate::schema::input::*;
+use crate::runtime::input::InputActions;
+use crate::runtime::tick::TransformInterpolation;
 use crate::capabilities::animation::AnimationController;
+use crate::capabilities::camera::OrbitCamera;
+use crate::capabilities::collider::Collider;
+
+/// Seconds of `jump_enter` before a jump shows `jump_loop`.
+pub const JUMP_ENTER_SECONDS: f32 = 0.25;
+/// Seconds of `jump_exit` after landing, unless the character moves on.
+pub const LANDING_SECONDS: f32 = 0.3;
+/// Characters on the ground follow it down steps this high, and step onto
+/// surfaces this far above their feet.
+pub const STEP_HEIGHT: f32 = 0.3;
 
 #[derive(Component)]
+#[require(TransformInterpolation)]
 pub struct CharacterController {
-    pub walk_speed: f32,
-    pub run_speed: f32,
-    pub rot_speed: f32,
-    pub inputs: InputMap,
+    /// Speeds, ramps and modes, as configured in content.
+    pub movement: MovementConfig,
+    /// Marks the controller as driven by the player's `InputActions`. The keys
+    /// are used when the project has no `InputProfile`.
+    pub inputs: Option<InputMap>,
     pub is_running: bool,
+    /// Whether the character moved on the last tick.
+    pub is_moving: bool,
+    pub locomotion: Locomotion,
+    /// World velocity of the last tick.
+    pub velocity: Vec3,
+    /// Horizontal velocity the character left the ground with.
+    pub takeoff_velocity: Vec3,
+    pub jump: JumpPhase,
+}
+
+impl Default for CharacterController {
+    fn default() -> Self {
+        Self {
+            movement: MovementConfig::default(),
+            inputs: None,
+            is_running: false,
+            is_moving: false,
+            locomotion: Locomotion::Standing,
+            velocity: Vec3::ZERO,
+            takeoff_velocity: Vec3::ZERO,
+            jump: JumpPhase::Grounded,
+        }
+    }
+}
+
+impl CharacterController {
+    /// The horizontal velocity after ramping last tick's toward `target`.
+    /// Speeds are ramped as shares of `run_speed`, so a ramp's `seconds` is
+    /// the time from standing still to running.
+    fn ramp(&self, target: Vec3, dt: f32) -> Vec3 {
+        let current = self.velocity.with_y(0.0);
+        let direction = target.normalize_or_zero();
+        // Last tick's speed along the new direction ramps toward the target;
+        // the rest of its velocity slows down along its own direction, so
+        // turning around brakes before it speeds up again.
+        let along = current.dot(direction).max(0.0);
+        let rest = current - direction * along;
+        let to = target.length();
+        let range = self.movement.run_speed.max(along).max(rest.length()).max(to);
+        if range <= 0.0 {
+            return target;
+        }
+        let along = if to > along {
+            (self.movement.acceleration.advance(along / range, dt) * range).min(to)
+        } else {
+            ((1.0 - self.movement.deceleration.advance(1.0 - along / range, dt)) * range).max(to)
+        };
+        let rest_speed = (1.0 - self.movement.deceleration.advance(1.0 - rest.length() / range, dt)) * range;
+        direction * along + rest.normalize_or_zero() * rest_speed.max(0.0)
+    }
+}
+
+/// What a character is doing besides walking, running and jumping. Player
+/// characters change state with the `crouch`, `roll` and `emote` actions;
+/// `Kill` and `Revive` actions change any character's.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Locomotion {
+    #[default]
+    Standing,
+    /// Moving at `crouch_speed`. Jumping stands the character up.
+    Crouching,
+    /// Moving along `direction` at `roll_speed`, without turning, for
+    /// `time` seconds so far.
+    Rolling { time: f32, direction: Vec3 },
+    /// Playing the `dance` clip, unable to move until `emote` is pressed again.
+    Emoting,
+    /// Playing the `death` clip and ignoring input until revived.
+    Dead,
+}
+
+impl Locomotion {
+    /// Name of the state in snapshots, e.g. `"crouching"`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Locomotion::Standing => "standing",
+            Locomotion::Crouching => "crouching",
+            Locomotion::Rolling { .. } => "rolling",
+            Locomotion::Emoting => "emoting",
+            Locomotion::Dead => "dead",
+        }
+    }
+
+    /// The state called `name`; rolls continue from `roll`, the
+    /// `(time, direction)` of a `Rolling` state.
+    pub fn from_name(name: &str, roll: Option<(f32, Vec3)>) -> Option<Self> {
+        Some(match name {
+            "standing" => Locomotion::Standing,
+            "crouching" => Locomotion::Crouching,
+            "rolling" => {
+                let (time, direction) = roll?;
+                Locomotion::Rolling { time, direction }
+            }
+            "emoting" => Locomotion::Emoting,
+            "dead" => Locomotion::Dead,
+            _ => return None,
+        })
+    }
+}
+
+/// Where a character is between take-off and landing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum JumpPhase {
+    #[default]
+    Grounded,
+    /// In the air, for this many seconds.
+    Airborne(f32),
+    /// Back on the ground, for this many seconds.
+    Landed(f32),
+}
+
+impl JumpPhase {
+    pub fn is_grounded(self) -> bool {
+        !matches!(self, JumpPhase::Airborne(_))
+    }
+}
+
+/// The surfaces characters stand on: the level's ground plane and the tops
+/// of solid colliders, placed where the current tick has them.
+pub struct Ground {
+    pub plane: Option<f32>,
+    pub colliders: Vec<(Collider, GlobalTransform)>,
+}
+
+impl Ground {
+    /// Height of the highest surface under `(x, z)` that is no higher than `max_y`.
+    pub fn height_below(&self, x: f32, z: f32, max_y: f32) -> Option<f32> {
+        let tops = self.colliders.iter().filter_map(|(collider, transform)| {
+            let local = transform.affine().inverse().transform_point3(Vec3::new(x, max_y, z));
+            let top = collider.top(local.x, local.z)?;
+            Some(transform.transform_point(Vec3::new(local.x, top, local.z)).y)
+        });
+        self.plane
+            .into_iter()
+            .chain(tops)
+            .filter(|height| *height <= max_y)
+            .max_by(f32::total_cmp)
+    }
+}
+
+/// Height of the infinite plane characters stand on, from
+/// `GameLevel::ground_plane`.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct GroundPlane(pub Option<f32>);
+
+impl Default for GroundPlane {
+    fn default() -> Self {
+        Self(Some(0.0))
+    }
 }
 
+/// Moves characters once per gameplay tick: player characters by their
+/// input actions and locomotion state, and every character by its jumps and
+/// gravity. Camera-relative characters move along the axes of the
+/// `OrbitCamera` following them, or the world's without one.
+///
+/// Gravity applies to every character, so with no `ground_plane` a character
+/// with no collider under it falls forever.
+#[allow(clippy::type_complexity)]
 pub fn player_movement_system(
     time: Res<Time>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut query: Query<(&mut Transform, &mut CharacterController, &mut AnimationController)>,
+    actions: Res<InputActions>,
+    ground_plane: Res<GroundPlane>,
+    cameras: Query<&OrbitCamera>,
+    colliders: Query<(Entity, &Collider), Without<CharacterController>>,
+    mut bodies: ParamSet<(TransformHelper, Query<(Entity, &mut Transform, &mut CharacterController)>)>,
 ) {
-    for (mut transform, mut controller, mut anim_ctrl) in &mut query {
-        let mut velocity = Vec3::ZERO;
-        let mut rotation = 0.0;
-        
-        let forward = transform.forward();
-        let right = transform.right();
-
-        // Toggle run with shift
-        if let Some(key) = controller.inputs.key("run") {
-            if keyboard_input.just_pressed(key) {
-                controller.is_running = !controller.is_running;
+    let dt = time.delta_secs();
+    // `GlobalTransform` lags behind the simulation, so colliders are placed
+    // from their transforms.
+    let transforms = bodies.p0();
+    let solid = colliders.iter().filter(|(_, collider)| !collider.is_trigger);
+    let ground = Ground {
+        plane: ground_plane.0,
+        colliders: solid
+            .filter_map(|(entity, collider)| Some((*collider, transforms.compute_global_transform(entity).ok()?)))
+            .collect(),
+    };
+    for (entity, mut transform, mut controller) in &mut bodies.p1() {
+        let controller = &mut *controller;
+        let mut jumped = false;
+        let mut movement = Vec3::ZERO;
+        if controller.inputs.is_some() {
+            match controller.movement.run_mode {
+                ButtonMode::Toggle if actions.tick_just_pressed(RUN_ACTION) => {
+                    controller.is_running = !controller.is_running;
+                }
+                ButtonMode::Toggle => {}
+                ButtonMode::Hold => controller.is_running = actions.pressed(RUN_ACTION),
             }
-        }
 
-        if let Some(key) = controller.inputs.key("forward") {
-            if keyboard_input.pressed(key) { velocity += *forward; }
-        }
-        if let Some(key) = controller.inputs.key("backward") {
-            if keyboard_input.pressed(key) { velocity -= *forward; }
-        }
-        if let Some(key) = controller.inputs.key("strafe_right") {
-            if keyboard_input.pressed(key) { velocity += *right; }
+            let mut input = actions.axis_2d(MOVE_ACTION).clamp_length_max(1.0);
+            let direction = match controller.movement.steering {
+                Steering::Tank => {
+                    input.x *= controller.movement.strafe_multiplier;
+                    if input.y < 0.0 {
+                        input.y *= controller.movement.backward_multiplier;
+                    }
+                    *transform.forward() * input.y + *transform.right() * input.x
+                }
+                Steering::CameraRelative => {
+                    // Turning moves sideways; the character faces where it goes.
+                    let input = (input + Vec2::X * actions.axis(TURN_ACTION)).clamp_length_max(1.0);
+                    let yaw = cameras.iter().find(|orbit| orbit.target == entity).map_or(0.0, |orbit| orbit.yaw);
+                    Quat::from_rotation_y(yaw) * Vec3::new(input.x, 0.0, -input.y)
+                }
+            };
+            update_locomotion(controller, &actions, direction, *transform.forward(), dt);
+
+            let speed = match controller.locomotion {
+                Locomotion::Standing if controller.is_running => controller.movement.run_speed,
+                Locomotion::Standing => controller.movement.walk_speed,
+                Locomotion::Crouching => controller.movement.crouch_speed,
+                Locomotion::Rolling { .. } | Locomotion::Emoting | Locomotion::Dead => 0.0,
+            };
+            movement = match controller.locomotion {
+                Locomotion::Rolling { direction, .. } => direction * controller.movement.roll_speed,
+                Locomotion::Standing | Locomotion::Crouching if controller.jump.is_grounded() => {
+                    controller.ramp(direction * speed, dt)
+                }
+                _ => direction * speed,
+            };
+
+            if matches!(controller.locomotion, Locomotion::Standing | Locomotion::Crouching) {
+                let max_turn = controller.movement.rot_speed * dt;
+                match controller.movement.steering {
+                    Steering::Tank => transform.rotate_y(-actions.axis(TURN_ACTION) * max_turn),
+                    Steering::CameraRelative => turn_toward(&mut transform, direction, max_turn),
+                }
+
+                jumped = controller.jump.is_grounded() && actions.tick_just_pressed(JUMP_ACTION);
+                if jumped {
+                    controller.locomotion = Locomotion::Standing;
+                }
+            }
         }
-        if let Some(key) = controller.inputs.key("strafe_left") {
-            if keyboard_input.pressed(key) { velocity -= *right; }
+        controller.is_moving = movement.length_squared() > 0.0;
+
+        if jumped {
+            controller.jump = JumpPhase::Airborne(0.0);
+            controller.takeoff_velocity = movement;
+            controller.velocity.y = controller.movement.jump_speed;
         }
-        
-        // Turning
-        if let Some(key) = controller.inputs.key("left") {
-            if keyboard_input.pressed(key) { rotation += 1.0; }
+        let vertical = controller.velocity.y;
+        controller.velocity = match controller.jump {
+            JumpPhase::Airborne(_) => controller
+                .takeoff_velocity
+                .lerp(movement, controller.movement.air_control.clamp(0.0, 1.0))
+                .with_y(vertical - controller.movement.gravity * dt),
+            _ => movement,
+        };
+        transform.translation += controller.velocity * dt;
+        land_or_fall(&mut transform.translation, controller, &ground, dt);
+    }
+}
+
+/// Turns `transform` about Y toward facing `direction`, by at most `max_angle`
+/// radians.
+fn turn_toward(transform: &mut Transform, direction: Vec3, max_angle: f32) {
+    let forward = transform.forward().with_y(0.0);
+    let direction = direction.with_y(0.0);
+    if direction.length_squared() == 0.0 {
+        return;
+    }
+    let angle = forward.cross(direction).y.atan2(forward.dot(direction));
+    transform.rotate_y(angle.clamp(-max_angle, max_angle));
+}
+
+/// Moves a player character between locomotion states by its input actions.
+/// `direction` is where the input moves it this tick.
+fn update_locomotion(
+    controller: &mut CharacterController,
+    actions: &InputActions,
+    direction: Vec3,
+    forward: Vec3,
+    dt: f32,
+) {
+    let grounded = controller.jump.is_grounded();
+    controller.locomotion = match controller.locomotion {
+        Locomotion::Dead => Locomotion::Dead,
+        Locomotion::Rolling { time, direction } if time + dt < controller.movement.roll_seconds => {
+            Locomotion::Rolling { time: time + dt, direction }
         }
-        if let Some(key) = controller.inputs.key("right") {
-            if keyboard_input.pressed(key) { rotation -= 1.0; }
+        Locomotion::Rolling { .. } => Locomotion::Standing,
+        Locomotion::Emoting if actions.tick_just_pressed(EMOTE_ACTION) => Locomotion::Standing,
+        Locomotion::Emoting => Locomotion::Emoting,
+        // Characters stand up when they leave the ground.
+        _ if !grounded => Locomotion::Standing,
+        current => {
+            let crouching = match controller.movement.crouch_mode {
+                ButtonMode::Toggle => (current == Locomotion::Crouching) != actions.tick_just_pressed(CROUCH_ACTION),
+                ButtonMode::Hold => actions.pressed(CROUCH_ACTION),
+            };
+            if actions.tick_just_pressed(ROLL_ACTION) {
+                let direction = direction.try_normalize().unwrap_or(forward);
+                Locomotion::Rolling { time: 0.0, direction }
+            } else if actions.tick_just_pressed(EMOTE_ACTION) {
+                Locomotion::Emoting
+            } else if crouching {
+                Locomotion::Crouching
+            } else {
+                Locomotion::Standing
+            }
         }
+    };
+}
 
-        // Apply Rotation
-        if rotation != 0.0 {
-            transform.rotate_y(rotation * controller.rot_speed * time.delta_secs());
+/// Keeps grounded characters on the ground, and lands or starts the fall of
+/// the others.
+fn land_or_fall(translation: &mut Vec3, controller: &mut CharacterController, ground: &Ground, dt: f32) {
+    match controller.jump {
+        JumpPhase::Airborne(time) => {
+            let below = ground.height_below(translation.x, translation.z, translation.y - controller.velocity.y * dt);
+            match below {
+                Some(height) if controller.velocity.y <= 0.0 && translation.y <= height => {
+                    translation.y = height;
+                    controller.velocity.y = 0.0;
+                    controller.jump = JumpPhase::Landed(0.0);
+                }
+                _ => controller.jump = JumpPhase::Airborne(time + dt),
+            }
         }
-
-        // Apply Movement and set animation
-        if velocity.length_squared() > 0.0 {
-            velocity = velocity.normalize();
-            let speed = if controller.is_running { controller.run_speed } else { controller.walk_speed };
-            transform.translation += velocity * speed * time.delta_secs();
-            
-            // Set animation based on running state
-            let target_anim = if controller.is_running {
-                anim_ctrl.animations.run.clone()
-            } else {
-                anim_ctrl.animations.walk.clone()
-            };
-            if anim_ctrl.current != target_anim {
-                anim_ctrl.current = target_anim;
+        JumpPhase::Grounded | JumpPhase::Landed(_) => {
+            match ground.height_below(translation.x, translation.z, translation.y + STEP_HEIGHT) {
+                Some(height) if translation.y - height <= STEP_HEIGHT => {
+                    translation.y = height;
+                    if let JumpPhase::Landed(time) = controller.jump {
+                        controller.jump = if time + dt < LANDING_SECONDS {
+                            JumpPhase::Landed(time + dt)
+                        } else {
+                            JumpPhase::Grounded
+                        };
+                    }
+                }
+                _ => {
+                    // Walked off an edge.
+                    controller.takeoff_velocity = controller.velocity;
+                    controller.jump = JumpPhase::Airborne(0.0);
+                }
             }
-        } else {
-            // Idle animation
-            if anim_ctrl.current != anim_ctrl.animations.idle {
-                anim_ctrl.current = anim_ctrl.animations.idle.clone();
+        }
+    }
+}
+
+/// Picks the animation of characters from their last tick: the clip of their
+/// locomotion state, then for player characters the jump clips in the air and
+/// on landing, else idle, walk or run. Other characters keep whatever clip
+/// actions play on them while standing, and go back to idle when a locomotion
+/// clip ends, e.g. when revived.
+pub fn character_animation_system(mut query: Query<(&CharacterController, &mut AnimationController)>) {
+    for (controller, mut anim_ctrl) in &mut query {
+        let animations = &anim_ctrl.animations;
+        let target_anim = match locomotion_clip(controller, animations) {
+            Some(clip) => clip,
+            None if controller.inputs.is_none() => {
+                let locomotion_clips = [
+                    &animations.death,
+                    &animations.dance,
+                    &animations.roll,
+                    &animations.crouch_forward,
+                    &animations.crouch_idle,
+                ];
+                if !locomotion_clips.contains(&&anim_ctrl.current) {
+                    continue;
+                }
+                &animations.idle
             }
+            None => match controller.jump {
+                JumpPhase::Airborne(time) if time < JUMP_ENTER_SECONDS && controller.velocity.y > 0.0 => {
+                    &animations.jump_enter
+                }
+                JumpPhase::Airborne(_) => &animations.jump_loop,
+                JumpPhase::Landed(_) if !controller.is_moving => &animations.jump_exit,
+                _ if !controller.is_moving => &animations.idle,
+                _ if controller.is_running => &animations.run,
+                _ => &animations.walk,
+            },
+        };
+        if anim_ctrl.current != *target_anim {
+            anim_ctrl.current = target_anim.clone();
         }
     }
 }
+
+/// The clip of the character's locomotion state, unless it is standing or
+/// has just left the ground crouching.
+fn locomotion_clip<'a>(controller: &CharacterController, animations: &'a AnimationMap) -> Option<&'a String> {
+    Some(match controller.locomotion {
+        Locomotion::Dead => &animations.death,
+        Locomotion::Emoting => &animations.dance,
+        Locomotion::Rolling { .. } => &animations.roll,
+        Locomotion::Crouching if !controller.jump.is_grounded() => return None,
+        Locomotion::Crouching if controller.is_moving => &animations.crouch_forward,
+        Locomotion::Crouching => &animations.crouch_idle,
+        Locomotion::Standing => return None,
+    })
+}
@@ -0,0 +1,196 @@
+use bevy::prelude::*;
+use crate::schema::*;
+use crate::runtime::input::{save_input_overrides, InputActions};
+use crate::runtime::storage::StorageBackend;
+use crate::capabilities::player::CharacterController;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RebindState {
+    Idle,
+    /// Clicked this frame; the click itself is not taken as the new binding.
+    Starting,
+    Listening,
+}
+
+/// A UI button that rebinds one input, spawned for `UiElement::Rebind`.
+#[derive(Component, Debug, Clone)]
+pub struct RebindButton {
+    pub input: String,
+    pub text: String,
+    state: RebindState,
+}
+
+impl RebindButton {
+    pub fn new(input: String, text: String) -> Self {
+        Self { input, text, state: RebindState::Idle }
+    }
+
+    /// Whether the button is waiting for a press.
+    pub fn is_listening(&self) -> bool {
+        self.state != RebindState::Idle
+    }
+}
+
+/// The button pressed this frame, as a binding. Keys win over mouse buttons,
+/// and mouse buttons over gamepad buttons.
+fn next_press<'a>(
+    keys: Option<&ButtonInput<KeyCode>>,
+    mouse_buttons: Option<&ButtonInput<MouseButton>>,
+    gamepads: impl Iterator<Item = &'a Gamepad>,
+) -> Option<ButtonBinding> {
+    let key = keys.and_then(|keys| keys.get_just_pressed().find_map(|key| key_name(*key)));
+    if let Some(key) = key {
+        return Some(ButtonBinding::Key(key.to_string()));
+    }
+    let mouse = mouse_buttons.and_then(|buttons| {
+        buttons
+            .get_just_pressed()
+            .map(|button| format!("{:?}", button))
+            .find(|name| parse_mouse_button(name).is_some())
+    });
+    if let Some(name) = mouse {
+        return Some(ButtonBinding::Mouse(name));
+    }
+    gamepads
+        .flat_map(|gamepad| gamepad.get_just_pressed())
+        .map(|button| format!("{:?}", button))
+        .find(|name| parse_gamepad_button(name).is_some())
+        .map(ButtonBinding::Gamepad)
+}
+
+/// Takes a press captured by a rebind button out of this frame's input, and
+/// out of the input actions until it is released.
+fn consume_press(
+    binding: ButtonBinding,
+    keys: Option<&mut ButtonInput<KeyCode>>,
+    mouse_buttons: Option<&mut ButtonInput<MouseButton>>,
+    gamepads: &mut Query<&mut Gamepad>,
+    actions: &mut InputActions,
+) {
+    match &binding {
+        ButtonBinding::Key(name) => {
+            if let Some((key, keys)) = InputMap::parse_key(name).zip(keys) {
+                keys.clear_just_pressed(key);
+            }
+        }
+        ButtonBinding::Mouse(name) => {
+            if let Some((button, buttons)) = parse_mouse_button(name).zip(mouse_buttons) {
+                buttons.clear_just_pressed(button);
+            }
+        }
+        ButtonBinding::Gamepad(name) => {
+            if let Some(button) = parse_gamepad_button(name) {
+                for mut gamepad in gamepads {
+                    gamepad.digital_mut().clear_just_pressed(button);
+                }
+            }
+        }
+    }
+    actions.consume(binding);
+}
+
+/// Starts listening when a rebind button is clicked, binds the next press to
+/// its input and persists the player's overrides. Runs before the input
+/// actions are updated, so the press it binds does not also act on the game.
+///
+/// The new binding applies to the active `InputActions` and to the `InputMap`
+/// of spawned characters, so it takes effect right away.
+#[allow(clippy::too_many_arguments)]
+pub fn rebind_system(
+    mut buttons: Query<(Entity, Ref<Interaction>, &mut RebindButton)>,
+    mut keys: Option<ResMut<ButtonInput<KeyCode>>>,
+    mut mouse_buttons: Option<ResMut<ButtonInput<MouseButton>>>,
+    mut gamepads: Query<&mut Gamepad>,
+    mut overrides: ResMut<InputOverrides>,
+    mut actions: ResMut<InputActions>,
+    mut controllers: Query<&mut CharacterController>,
+    mut storage: ResMut<StorageBackend>,
+) {
+    // Only one button listens at a time.
+    let clicked = buttons
+        .iter()
+        .find(|(_, interaction, _)| interaction.is_changed() && **interaction == Interaction::Pressed)
+        .map(|(entity, _, _)| entity);
+    if let Some(clicked) = clicked {
+        for (entity, _, mut button) in &mut buttons {
+            button.state = if entity == clicked { RebindState::Starting } else { RebindState::Idle };
+        }
+        return;
+    }
+
+    let Some(mut button) = buttons.iter_mut().map(|(_, _, button)| button).find(|button| button.is_listening()) else {
+        return;
+    };
+    if button.state == RebindState::Starting {
+        button.state = RebindState::Listening;
+        return;
+    }
+    if keys.as_ref().is_some_and(|keys| keys.just_pressed(KeyCode::Escape)) {
+        button.state = RebindState::Idle;
+        let escape = ButtonBinding::Key("Escape".to_string());
+        consume_press(escape, keys.as_deref_mut(), mouse_buttons.as_deref_mut(), &mut gamepads, &mut actions);
+        return;
+    }
+    let Some(binding) = next_press(keys.as_deref(), mouse_buttons.as_deref(), gamepads.iter()) else {
+        return;
+    };
+
+    let mut profile = actions.profile().clone();
+    let mut bound = profile.rebind(&button.input, binding.clone());
+    if let ButtonBinding::Key(key) = &binding {
+        for mut controller in &mut controllers {
+            if let Some(inputs) = controller.inputs.as_mut() {
+                bound |= inputs.set_key(&button.input, key);
+            }
+        }
+    }
+    let (name, _) = binding.check();
+    if !bound {
+        warn!("Cannot bind {} \"{}\" to input '{}'", binding.kind(), name, button.input);
+        return;
+    }
+
+    println!("Rebound input '{}' to {} \"{}\"", button.input, binding.kind(), name);
+    let others: Vec<String> = profile
+        .inputs_bound_to(&binding)
+        .into_iter()
+        .filter(|input| *input != button.input)
+        .collect();
+    if !others.is_empty() {
+        warn!("Input '{}' now shares {} \"{}\" with {}", button.input, binding.kind(), name, others.join(", "));
+    }
+    button.state = RebindState::Idle;
+    actions.set_profile(profile);
+    overrides.bindings.insert(button.input.clone(), binding.clone());
+    save_input_overrides(&overrides, storage.0.as_mut());
+    consume_press(binding, keys.as_deref_mut(), mouse_buttons.as_deref_mut(), &mut gamepads, &mut actions);
+}
+
+/// Shows each rebind button's text and current binding, e.g. `"Jump: Space"`.
+/// Keys are shown in preference to other bindings of the input.
+pub fn rebind_label_system(
+    buttons: Query<(&RebindButton, &Children)>,
+    actions: Res<InputActions>,
+    mut texts: Query<&mut Text>,
+) {
+    for (button, children) in &buttons {
+        let binding = if button.is_listening() {
+            "...".to_string()
+        } else {
+            let bindings = actions.profile().input_bindings(&button.input);
+            bindings
+                .iter()
+                .find(|binding| matches!(binding, ButtonBinding::Key(_)))
+                .or(bindings.first())
+                .map_or_else(|| "-".to_string(), |binding| binding.check().0.to_string())
+        };
+        let label = format!("{}: {}", button.text, binding);
+        for child in children.iter() {
+            if let Ok(mut text) = texts.get_mut(child) {
+                if text.0 != label {
+                    text.0 = label.clone();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,15 @@
+use bevy::prelude::*;
+use crate::runtime::tick::TransformInterpolation;
+
+/// Constant movement in units per second, set by `Action::SetVelocity`.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq)]
+#[require(TransformInterpolation)]
+pub struct Velocity(pub Vec3);
+
+pub fn velocity_system(time: Res<Time>, mut query: Query<(&Velocity, &mut Transform)>) {
+    for (velocity, mut transform) in &mut query {
+        if velocity.0 != Vec3::ZERO {
+            transform.translation += velocity.0 * time.delta_secs();
+        }
+    }
+}
@@ -1,10 +1,10 @@
 use bevy::prelude::*;
-use bevy_common_assets::ron::RonAssetPlugin;
 
 pub mod schema;
 pub mod runtime;
 pub mod capabilities;
 pub mod utils;
+pub mod validation;
 
 use crate::schema::*;
 use crate::runtime::*;
@@ -20,24 +20,109 @@ impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         app.init_state::<AppState>()
             .init_resource::<ActionQueue>()
-            .add_message::<UiMessage>()
-            .add_plugins(RonAssetPlugin::<GameLevel>::new(&["ron"]))
-            .add_plugins(RonAssetPlugin::<ProjectConfig>::new(&["ron"]))
-            .add_systems(Startup, setup)
+            .init_resource::<HotReload>()
+            .init_resource::<GlobalLogicState>()
+            .init_resource::<Variables>()
+            .init_resource::<PendingPrefabs>()
+            .init_resource::<ActionScheduler>()
+            .init_resource::<EventRegistry>()
+            .init_resource::<InputActions>()
+            .init_resource::<InputOverrides>()
+            .init_resource::<StorageBackend>()
+            .init_resource::<GameRng>()
+            .init_resource::<GroundPlane>()
+            .register_snapshot_component::<Transform>()
+            .register_snapshot_component::<CharacterController>()
+            .register_snapshot_component::<AnimationController>()
+            .register_snapshot_component::<Behavior>()
+            .register_snapshot_component::<Velocity>()
+            .register_snapshot_component::<OrbitCamera>()
+            .add_message::<GameEvent>()
+            .add_message::<BehaviorMessage>()
+            .add_plugins(VersionedRonAssetPlugin::<GameLevel>::new(&["ron"]))
+            .add_plugins(VersionedRonAssetPlugin::<ProjectConfig>::new(&["ron"]))
+            .add_plugins(VersionedRonAssetPlugin::<Prefab>::new(&["ron"]))
+            .add_plugins(VersionedRonAssetPlugin::<GlobalLogic>::new(&["ron"]))
+            .add_plugins(VersionedRonAssetPlugin::<BehaviorMachine>::new(&["ron"]))
+            .add_plugins(VersionedRonAssetPlugin::<UiMenu>::new(&["ron"]))
+            .add_plugins(VersionedRonAssetPlugin::<InputProfile>::new(&["ron"]))
+            .add_systems(Startup, (setup, load_input_overrides_system))
+            .add_systems(First, replay_time_system
+                .before(bevy::time::TimeSystems)
+                .run_if(resource_exists::<InputReplay>))
+            .add_systems(PreUpdate, (
+                input_actions_system.run_if(live_input),
+                replay_input_system.run_if(not(live_input).and(in_game_frame)),
+                record_input_system.run_if(resource_exists::<InputRecorder>.and(in_game_frame)),
+            ).chain().after(bevy::input::InputSystems))
+            .add_systems(PreUpdate, key_events_system.after(bevy::input::InputSystems))
+            .add_systems(PreUpdate, rebind_system
+                .after(bevy::input::InputSystems)
+                .before(input_actions_system)
+                .before(key_events_system))
+            .add_systems(Last, (
+                save_recording_system.run_if(resource_exists::<InputRecorder>),
+                replay_finished_system.run_if(resource_exists::<InputReplay>),
+            ))
             .add_systems(Update, check_project_loaded.run_if(in_state(AppState::LoadingProject)))
+            .add_systems(Update, detect_load_failures
+                .run_if(in_state(AppState::LoadingProject).or(in_state(AppState::LoadingScene))))
+            .add_systems(OnEnter(AppState::InGame), (
+                apply_pending_load_system,
+                reset_tick_phase_system,
+                check_replay_scene_system.run_if(resource_exists::<InputReplay>),
+            ))
+            .add_systems(OnEnter(AppState::Error), replay_load_failed_system.run_if(resource_exists::<InputReplay>))
+            .add_systems(OnEnter(AppState::Error), (spawn_error_screen, discard_pending_load_system))
+            .add_systems(OnExit(AppState::Error), despawn_error_screen)
+            .add_systems(Update, error_screen_button_system.run_if(in_state(AppState::Error)))
             .add_systems(Update, (
                 spawn_level,
                 button_system,
+                ui_menu_system,
+                ui_visibility_system,
             ))
             .add_systems(Update, (
+                register_events_system::<GlobalLogic>,
+                register_events_system::<BehaviorMachine>,
+                register_events_system::<GameLevel>,
+                register_events_system::<UiMenu>,
+            ))
+            .add_systems(Update, (apply_input_overrides_system, input_profile_system).chain())
+            .add_systems(Update, rebind_label_system)
+            .add_systems(Update, camera_orbit_system.run_if(in_state(AppState::InGame)))
+            .add_systems(Update, (character_animation_system, animation_playback_system))
+            // Gameplay advances in fixed ticks; presentation interpolates between them.
+            .add_systems(RunFixedMainLoop, restore_transforms_system
+                .in_set(RunFixedMainLoopSystems::BeforeFixedMainLoop))
+            .add_systems(RunFixedMainLoop, interpolate_transforms_system
+                .in_set(RunFixedMainLoopSystems::AfterFixedMainLoop))
+            .add_systems(FixedFirst, begin_tick_transforms_system)
+            .add_systems(GameLogic, (
+                trigger_system,
+                interact_system,
+                behavior_system,
+            ).chain().after(message_interpreter_system).before(action_executor_system))
+            .add_systems(GameLogic, (
                 message_interpreter_system,
+                action_scheduler_system,
                 action_executor_system,
-            ))
+                spawn_pending_prefabs,
+            ).chain())
+            // No ticks run while paused or outside the game, so messages are
+            // cleared every frame instead. Loading frames are not recorded, so
+            // the logic's timers wait for the game too.
             .add_systems(Update, (
+                run_game_logic,
+                bevy::ecs::message::signal_message_update_system,
+            ).chain().run_if(bevy::time::common_conditions::paused.or(not(in_state(AppState::InGame)))))
+            .add_systems(FixedUpdate, (
+                run_game_logic,
+                camera_steer_system,
                 player_movement_system,
-                camera_orbit_system,
-                animation_playback_system,
-            ));
+                velocity_system,
+            ).chain().run_if(in_state(AppState::InGame)))
+            .add_systems(FixedLast, (end_tick_transforms_system, end_input_tick_system));
     }
 }
 
@@ -61,23 +146,25 @@ fn setup(
     next_state.set(AppState::LoadingProject);
 }
 
+#[allow(clippy::type_complexity)]
 fn button_system(
     interaction_query: Query<
         (&Interaction, &mut BackgroundColor, &UiAction),
         (Changed<Interaction>, With<Button>),
     >,
-    mut ui_events: MessageWriter<UiMessage>,
+    mut game_events: MessageWriter<GameEvent>,
+    mut action_queue: ResMut<ActionQueue>,
 ) {
     let mut interaction_query = interaction_query;
     for (interaction, mut color, action) in &mut interaction_query {
         match *interaction {
             Interaction::Pressed => {
                 *color = BackgroundColor(Color::srgb(0.35, 0.75, 0.35));
-                match action {
-                    UiAction::LoadScene(path) => {
-                        println!("Button Pressed! Emitting UiMessage for scene: {}", path);
-                        ui_events.write(UiMessage::ButtonPressed(path.clone()));
-                    }
+                if let UiAction::Emit(name) = action {
+                    println!("Button Pressed! Emitting GameEvent: {}", name);
+                    game_events.write(GameEvent::new(EventId::of(name)));
+                } else {
+                    action_queue.extend(action.actions());
                 }
             }
             Interaction::Hovered => {
@@ -90,24 +177,104 @@ fn button_system(
     }
 }
 
+/// Shows UI nodes whose `UiVisibility` condition holds and hides the rest.
+fn ui_visibility_system(
+    mut nodes: Query<(&UiVisibility, &mut Node)>,
+    variables: Res<Variables>,
+    level_handle: Option<Res<LevelHandle>>,
+    asset_server: Res<AssetServer>,
+) {
+    let scene = active_scene(level_handle.as_deref(), &asset_server);
+    let mut context = ConditionContext {
+        scene: scene.as_deref(),
+        variables: Some(&variables),
+        ..default()
+    };
+    for (visibility, mut node) in &mut nodes {
+        let display = if visibility.0.evaluate(&mut context) { Display::Flex } else { Display::None };
+        if node.display != display {
+            node.display = display;
+        }
+    }
+}
+
+/// Options the runners pass to `start_app_with_options`.
+#[derive(Debug, Clone, Default)]
+pub struct StartOptions {
+    pub project_path: Option<String>,
+    /// Re-spawn the active scene when its file changes (requires bevy's `file_watcher`).
+    pub hot_reload: bool,
+    /// Whether the asset server watches asset files for changes
+    /// (`AssetPlugin::watch_for_changes_override`). Unset, it watches when
+    /// `hot_reload` is set and otherwise follows bevy's `file_watcher` feature.
+    pub watch_for_changes: Option<bool>,
+    /// Record input actions to this file, written on exit.
+    pub record: Option<String>,
+    /// Feed this recording instead of live input. Its project is used unless
+    /// `project_path` is set.
+    pub replay: Option<InputRecording>,
+    /// Run without a window or renderer, exiting once the replay has finished.
+    pub headless: bool,
+}
+
 pub fn start_app(project_path: Option<String>) {
+    start_app_with_options(StartOptions {
+        project_path,
+        ..default()
+    });
+}
+
+pub fn start_app_with_options(options: StartOptions) -> AppExit {
     let asset_path = if cfg!(target_arch = "wasm32") {
         "assets".to_string()
     } else {
         find_assets_folder().to_string_lossy().to_string()
     };
     
-    let config_path = project_path.unwrap_or_else(|| "project.ron".to_string());
+    let config_path = options
+        .project_path
+        .or_else(|| options.replay.as_ref().map(|recording| recording.project.clone()))
+        .unwrap_or_else(|| "project.ron".to_string());
     
     println!("Runtime Asset Path: {}", asset_path);
     println!("Project Config Path: {}", config_path);
+    if options.hot_reload {
+        println!("Scene hot reload enabled");
+    }
 
-    App::new()
-        .add_plugins(DefaultPlugins.set(AssetPlugin {
-            file_path: asset_path,
-            ..default()
-        }))
-        .insert_resource(ProjectConfigPath(config_path))
-        .add_plugins(GamePlugin)
-        .run();
+    let asset_plugin = AssetPlugin {
+        file_path: asset_path,
+        watch_for_changes_override: options.watch_for_changes.or(options.hot_reload.then_some(true)),
+        ..default()
+    };
+    let mut app = App::new();
+    if options.headless {
+        app.add_plugins(MinimalPlugins)
+            .add_plugins(bevy::state::app::StatesPlugin)
+            .add_plugins(asset_plugin)
+            .add_plugins(bevy::input::InputPlugin)
+            .init_asset::<Mesh>()
+            .init_asset::<StandardMaterial>()
+            .init_asset::<Gltf>()
+            .init_asset::<Scene>()
+            .init_asset::<AnimationGraph>()
+            .init_asset::<AnimationClip>();
+    } else {
+        app.add_plugins(DefaultPlugins.set(asset_plugin));
+    }
+    app.insert_resource(ProjectConfigPath(config_path))
+        .insert_resource(HotReload { enabled: options.hot_reload })
+        .insert_resource(StorageBackend::platform());
+    let seed = options.replay.as_ref().map_or_else(GameRng::entropy_seed, |recording| recording.seed);
+    app.insert_resource(GameRng::new(seed));
+    if let Some(path) = options.record {
+        app.insert_resource(InputRecorder::new(path));
+    }
+    if let Some(recording) = options.replay {
+        println!("Replaying {} recorded frames", recording.frames.len());
+        let mut replay = InputReplay::new(recording);
+        replay.exit_when_done = options.headless;
+        app.insert_resource(replay);
+    }
+    app.add_plugins(GamePlugin).run()
 }
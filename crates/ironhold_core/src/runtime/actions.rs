@@ -1,19 +1,138 @@
 use bevy::prelude::*;
+use serde::Deserialize;
+use std::collections::{HashMap, VecDeque};
+use crate::schema::prefab::ParamValue;
+use crate::schema::transform::{Rotation, Scale};
+use crate::schema::value::Value;
 
-#[derive(Debug, Clone)]
+/// Target name that resolves to the entity whose behavior queued the action.
+pub const SELF_TARGET: &str = "self";
+
+/// The engine operations data can trigger, written in RON in logic
+/// transitions, behaviors and UI buttons. Targets are `"self"` or an entity id.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
 pub enum Action {
     LoadScene(String),
+    /// Pauses or resumes virtual time, which gameplay systems run on.
+    SetPaused(bool),
+    /// Despawns the target entity (`"self"` or an entity id) and its children.
+    Despawn(String),
+    /// Sends an event to the target entity's behavior: `SendEvent(target, event)`.
+    SendEvent(String, String),
+    /// Sets a local variable of the behavior that runs the action.
+    SetLocal(String, Value),
+    /// Sets a project variable: `SetVar("difficulty", "hard")`.
+    SetVar(String, Value),
+    /// Adds a number or vector to a project variable: `AddVar("score", 10)`.
+    AddVar(String, Value),
+    /// Flips a bool project variable.
+    ToggleVar(String),
+    /// Shows the `UiMenu` at `path` on top of the current scene.
+    OpenUi(String),
+    /// Closes a menu opened with `OpenUi`.
+    CloseUi(String),
+    /// Plays (and loops) a clip of the target's model: `PlayAnimation("self", "Wave")`.
+    PlayAnimation(String, String),
+    /// Puts the target character in its death state: it plays the `death`
+    /// clip and ignores input.
+    Kill(String),
+    /// Brings a killed character back, standing.
+    Revive(String),
+    /// Moves the target at a constant velocity in units per second; zero stops it.
+    SetVelocity(String, (f32, f32, f32)),
+    /// Sends an event to the global logic.
+    EmitEvent(String),
+    /// Spawns a prefab into the current scene. With `at`, `position` is
+    /// relative to that entity. Skipped if one of its ids is already in use.
+    SpawnPrefab {
+        prefab: String,
+        #[serde(default)]
+        id: Option<String>,
+        #[serde(default)]
+        at: Option<String>,
+        #[serde(default)]
+        position: (f32, f32, f32),
+        #[serde(default)]
+        rotation: Option<Rotation>,
+        #[serde(default)]
+        scale: Option<Scale>,
+        #[serde(default)]
+        params: HashMap<String, ParamValue>,
+    },
+    /// Replaces the parts of the target's transform that are set.
+    SetTransform {
+        target: String,
+        #[serde(default)]
+        position: Option<(f32, f32, f32)>,
+        #[serde(default)]
+        rotation: Option<Rotation>,
+        #[serde(default)]
+        scale: Option<Scale>,
+    },
+    /// Plays a sound asset once, e.g. `PlaySound("sounds/click.ogg")`.
+    PlaySound(String),
+    /// Exits the application.
+    Quit,
+    /// Runs actions one after another; each finishes before the next starts.
+    Sequence(Vec<Action>),
+    /// Runs an action after waiting: `Delay(2.0, LoadScene("scenes/end.ron"))`.
+    Delay(f32, Box<Action>),
+    /// Starts all actions at once and finishes when the last one has.
+    Parallel(Vec<Action>),
+    /// Finishes when the global logic receives the event, e.g. `WaitForEvent("ui.continue")`.
+    WaitForEvent(String),
+    /// Runs one of the actions, picked with the game's seeded RNG:
+    /// `RandomChoice([SpawnPrefab(prefab: "prefabs/gem.ron"), AddVar("gold", 5)])`.
+    RandomChoice(Vec<Action>),
+    /// Saves the game to a slot: `SaveGame("quick")`.
+    SaveGame(String),
+    /// Loads the level saved in a slot, then restores the rest of the save.
+    LoadGame(String),
+}
+
+/// An action plus the entity whose behavior queued it, which `"self"`
+/// targets resolve to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueuedAction {
+    pub action: Action,
+    pub source: Option<Entity>,
 }
 
+/// Actions waiting to run, executed first in, first out.
 #[derive(Resource, Default)]
-pub struct ActionQueue(pub Vec<Action>);
+pub struct ActionQueue(pub VecDeque<QueuedAction>);
 
 impl ActionQueue {
     pub fn push(&mut self, action: Action) {
-        self.0.push(action);
+        self.0.push_back(QueuedAction { action, source: None });
+    }
+
+    pub fn extend(&mut self, actions: impl IntoIterator<Item = Action>) {
+        self.0.extend(actions.into_iter().map(|action| QueuedAction { action, source: None }));
+    }
+
+    /// Queues actions run on behalf of `source`.
+    pub fn extend_scoped(&mut self, source: Entity, actions: impl IntoIterator<Item = Action>) {
+        self.0.extend(actions.into_iter().map(|action| QueuedAction {
+            action,
+            source: Some(source),
+        }));
+    }
+
+    /// The queued actions, without their sources.
+    pub fn actions(&self) -> impl Iterator<Item = &Action> {
+        self.0.iter().map(|queued| &queued.action)
     }
-    
-    pub fn pop(&mut self) -> Option<Action> {
-        self.0.pop()
+
+    /// Queues actions ahead of everything already queued, keeping their order.
+    pub fn prepend(&mut self, actions: Vec<QueuedAction>) {
+        for queued in actions.into_iter().rev() {
+            self.0.push_front(queued);
+        }
+    }
+
+    /// Removes the oldest queued action.
+    pub fn pop(&mut self) -> Option<QueuedAction> {
+        self.0.pop_front()
     }
 }
@@ -0,0 +1,271 @@
+use bevy::prelude::*;
+use bevy::transform::helper::TransformHelper;
+use std::collections::{BTreeMap, HashMap};
+use crate::schema::*;
+use crate::runtime::actions::*;
+use crate::runtime::messages::*;
+use crate::runtime::logic::*;
+use crate::runtime::variables::Variables;
+use crate::runtime::rng::GameRng;
+use crate::runtime::events::EventId;
+use crate::runtime::input::InputActions;
+use crate::runtime::scene_manager::active_scene;
+use crate::capabilities::collider::Collider;
+use crate::capabilities::player::CharacterController;
+use crate::runtime::tick::LogicTime;
+
+/// Runs a `BehaviorMachine` for one entity, with its own state and locals.
+#[derive(Component, Debug, Clone)]
+pub struct Behavior {
+    pub machine: Handle<BehaviorMachine>,
+    /// How close a character must be to interact with this entity.
+    pub interact_radius: f32,
+    /// Applied over the machine's local defaults when it starts.
+    overrides: HashMap<String, Value>,
+    current: Option<String>,
+    locals: HashMap<String, Value>,
+    /// Seconds spent in the current state.
+    elapsed: f32,
+}
+
+impl Behavior {
+    pub fn new(machine: Handle<BehaviorMachine>, overrides: HashMap<String, Value>, interact_radius: f32) -> Self {
+        Self {
+            machine,
+            interact_radius,
+            overrides,
+            current: None,
+            locals: HashMap::new(),
+            elapsed: 0.0,
+        }
+    }
+
+    /// Name of the current state, or `None` until the machine has loaded.
+    pub fn current(&self) -> Option<&str> {
+        self.current.as_deref()
+    }
+
+    pub fn local(&self, name: &str) -> Option<&Value> {
+        self.locals.get(name)
+    }
+
+    pub fn locals(&self) -> &HashMap<String, Value> {
+        &self.locals
+    }
+
+    /// Seconds spent in the current state.
+    pub fn elapsed(&self) -> f32 {
+        self.elapsed
+    }
+
+    /// Puts the machine back in a saved state without running any actions.
+    pub fn restore(&mut self, current: Option<String>, locals: HashMap<String, Value>, elapsed: f32) {
+        self.current = current;
+        self.locals = locals;
+        self.elapsed = elapsed;
+    }
+
+    pub fn set_local(&mut self, name: impl Into<String>, value: Value) {
+        self.locals.insert(name.into(), value);
+    }
+
+    /// Sets up the locals and enters `initial_state` if the machine has not
+    /// started yet. Actions are queued on behalf of `entity`.
+    pub fn start(&mut self, entity: Entity, machine: &BehaviorMachine, queue: &mut ActionQueue) {
+        if self.current.is_some() {
+            return;
+        }
+        self.locals = machine.locals.clone();
+        self.locals.extend(self.overrides.iter().map(|(k, v)| (k.clone(), v.clone())));
+        if let Some(state) = machine.state(&machine.initial_state) {
+            queue.extend_scoped(entity, state.on_enter.iter().cloned());
+        }
+        self.current = Some(machine.initial_state.clone());
+        self.elapsed = 0.0;
+    }
+
+    /// Feeds `event`, caused by the entity with id `other`, to the machine.
+    /// Guards see this behavior's locals on top of `context`. Returns whether
+    /// a transition fired.
+    pub fn handle_event(
+        &mut self,
+        entity: Entity,
+        machine: &BehaviorMachine,
+        event: EventId,
+        other: Option<&str>,
+        context: &mut ConditionContext,
+        queue: &mut ActionQueue,
+    ) -> bool {
+        let Some(current) = &self.current else {
+            return false;
+        };
+        let mut context = ConditionContext {
+            locals: Some(&self.locals),
+            other,
+            scene: context.scene,
+            variables: context.variables,
+            rng: context.rng.as_deref_mut(),
+        };
+        let Some(step) = step(&machine.states, &machine.transitions, current, event, &mut context) else {
+            return false;
+        };
+        if let Some(to) = step.to {
+            self.current = Some(to);
+            self.elapsed = 0.0;
+        }
+        queue.extend_scoped(entity, step.actions);
+        true
+    }
+
+    /// Advances the current state's timer by `delta` seconds, firing a
+    /// `"timer"` event when it runs out.
+    pub fn tick(
+        &mut self,
+        entity: Entity,
+        machine: &BehaviorMachine,
+        delta: f32,
+        context: &mut ConditionContext,
+        queue: &mut ActionQueue,
+    ) {
+        let Some(current) = &self.current else {
+            return;
+        };
+        let before = self.elapsed;
+        self.elapsed += delta;
+        if timer_due(machine.state(current), before, self.elapsed) {
+            self.handle_event(entity, machine, EventId::of(TIMER_EVENT), None, context, queue);
+        }
+    }
+}
+
+/// Starts behaviors whose machine has loaded, advances their timers and
+/// routes `BehaviorMessage`s to them.
+#[allow(clippy::too_many_arguments)]
+pub fn behavior_system(
+    time: LogicTime,
+    machines: Res<Assets<BehaviorMachine>>,
+    mut messages: MessageReader<BehaviorMessage>,
+    mut behaviors: Query<(Entity, &mut Behavior)>,
+    level_handle: Option<Res<LevelHandle>>,
+    asset_server: Res<AssetServer>,
+    variables: Res<Variables>,
+    mut rng: ResMut<GameRng>,
+    mut action_queue: ResMut<ActionQueue>,
+) {
+    let scene = active_scene(level_handle.as_deref(), &asset_server);
+    let mut context = ConditionContext {
+        scene: scene.as_deref(),
+        variables: Some(&variables),
+        rng: Some(&mut rng),
+        ..default()
+    };
+
+    for (entity, mut behavior) in &mut behaviors {
+        let Some(machine) = machines.get(&behavior.machine) else {
+            continue;
+        };
+        behavior.start(entity, machine, &mut action_queue);
+        behavior.tick(entity, machine, time.delta_secs(), &mut context, &mut action_queue);
+    }
+
+    for message in messages.read() {
+        let Ok((entity, mut behavior)) = behaviors.get_mut(message.entity) else {
+            continue;
+        };
+        let Some(machine) = machines.get(&behavior.machine) else {
+            continue;
+        };
+        behavior.handle_event(entity, machine, message.event, message.other.as_deref(), &mut context, &mut action_queue);
+    }
+}
+
+/// Sends `"trigger.enter"` / `"trigger.exit"` to trigger colliders as
+/// characters move in and out of them. Positions are those of the current
+/// tick, not the `GlobalTransform` shown last frame.
+pub fn trigger_system(
+    transforms: TransformHelper,
+    triggers: Query<(Entity, &Collider)>,
+    characters: Query<(Entity, Option<&EntityId>), With<CharacterController>>,
+    mut overlaps: Local<BTreeMap<(Entity, Entity), Option<String>>>,
+    mut writer: MessageWriter<BehaviorMessage>,
+) {
+    let positions: Vec<_> = characters
+        .iter()
+        .filter_map(|(character, id)| {
+            let position = transforms.compute_global_transform(character).ok()?.translation();
+            Some((character, position, id))
+        })
+        .collect();
+    let mut current = BTreeMap::new();
+    for (trigger, collider) in &triggers {
+        if !collider.is_trigger {
+            continue;
+        }
+        let Ok(trigger_transform) = transforms.compute_global_transform(trigger) else {
+            continue;
+        };
+        let to_local = trigger_transform.affine().inverse();
+        for (character, position, id) in &positions {
+            if *character != trigger && collider.contains(to_local.transform_point3(*position)) {
+                current.insert((trigger, *character), id.map(|id| id.0.clone()));
+            }
+        }
+    }
+
+    for ((trigger, character), other) in &current {
+        if !overlaps.contains_key(&(*trigger, *character)) {
+            writer.write(BehaviorMessage {
+                entity: *trigger,
+                event: EventId::of(TRIGGER_ENTER_EVENT),
+                other: other.clone(),
+            });
+        }
+    }
+    for (key, other) in overlaps.iter() {
+        if !current.contains_key(key) {
+            writer.write(BehaviorMessage {
+                entity: key.0,
+                event: EventId::of(TRIGGER_EXIT_EVENT),
+                other: other.clone(),
+            });
+        }
+    }
+    *overlaps = current;
+}
+
+/// Sends `"interact"` to the nearest behavior in range of the player's
+/// characters when the `interact` action is pressed, measured at the current
+/// tick's positions.
+pub fn interact_system(
+    actions: Res<InputActions>,
+    transforms: TransformHelper,
+    characters: Query<(Entity, &CharacterController, Option<&EntityId>)>,
+    behaviors: Query<(Entity, &Behavior)>,
+    mut writer: MessageWriter<BehaviorMessage>,
+) {
+    if !actions.tick_just_pressed(INTERACT_ACTION) {
+        return;
+    }
+    let position_of = |entity| transforms.compute_global_transform(entity).ok().map(|global| global.translation());
+    for (character, controller, id) in &characters {
+        if controller.inputs.is_none() {
+            continue;
+        }
+        let Some(position) = position_of(character) else {
+            continue;
+        };
+        let nearest = behaviors
+            .iter()
+            .filter(|(entity, _)| *entity != character)
+            .filter_map(|(entity, behavior)| Some((entity, behavior, position_of(entity)?.distance(position))))
+            .filter(|(_, behavior, distance)| *distance <= behavior.interact_radius)
+            .min_by(|a, b| a.2.total_cmp(&b.2));
+        if let Some((entity, _, _)) = nearest {
+            writer.write(BehaviorMessage {
+                entity,
+                event: EventId::of(INTERACT_EVENT),
+                other: id.map(|id| id.0.clone()),
+            });
+        }
+    }
+}
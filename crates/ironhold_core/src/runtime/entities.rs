@@ -0,0 +1,222 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+use crate::schema::*;
+use crate::capabilities::player::CharacterController;
+use crate::capabilities::animation::AnimationController;
+use crate::capabilities::camera::OrbitCamera;
+use crate::capabilities::collider::Collider;
+use crate::runtime::behavior::Behavior;
+
+/// Id given to the entity spawned from `GameLevel::player`.
+pub const PLAYER_ENTITY_ID: &str = "player";
+/// Id given to the orbit camera spawned for `GameLevel::player`.
+pub const PLAYER_CAMERA_ENTITY_ID: &str = "player_camera";
+
+/// State carried over when a level is re-spawned in place (hot reload),
+/// keyed by `EntityId`.
+#[derive(Default, Debug, Clone)]
+pub struct PreservedState {
+    pub transforms: HashMap<String, Transform>,
+    /// Orbit camera `(yaw, pitch, radius)`.
+    pub orbits: HashMap<String, (f32, f32, f32)>,
+}
+
+impl PreservedState {
+    pub fn apply_orbit(&self, id: &str, orbit: &mut OrbitCamera) {
+        if let Some(&(yaw, pitch, radius)) = self.orbits.get(id) {
+            orbit.yaw = yaw;
+            orbit.pitch = pitch;
+            orbit.radius = radius;
+        }
+    }
+}
+
+/// Spawns `GameLevel::entities`, adding them to the id → entity map. An
+/// entity whose id is already in the map is not spawned.
+///
+/// Entities are spawned first and capabilities inserted afterwards, so blocks
+/// such as `OrbitCamera` can refer to any entity in `spawned` by id.
+pub fn spawn_entities(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    defs: &[EntityDef],
+    preserved: &PreservedState,
+    spawned: &mut HashMap<String, Entity>,
+) {
+    let mut fresh = Vec::new();
+    for def in defs {
+        if spawned.contains_key(&def.id) {
+            warn!("Entity id '{}' is already in use, not spawning it", def.id);
+            continue;
+        }
+        let transform = preserved
+            .transforms
+            .get(&def.id)
+            .copied()
+            .unwrap_or_else(|| def.transform());
+        let mut entity = commands.spawn((
+            transform,
+            Name::new(def.id.clone()),
+            EntityId(def.id.clone()),
+            LevelEntity,
+        ));
+        if let Some(model) = &def.model {
+            entity.insert(SceneRoot(asset_server.load(model.clone())));
+        }
+        spawned.insert(def.id.clone(), entity.id());
+        fresh.push((def, entity.id()));
+    }
+
+    for (def, entity) in fresh {
+        for block in &def.capabilities {
+            insert_capability(commands, asset_server, entity, def, block, spawned, preserved);
+        }
+    }
+}
+
+fn insert_capability(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    entity: Entity,
+    def: &EntityDef,
+    block: &CapabilityBlock,
+    spawned: &HashMap<String, Entity>,
+    preserved: &PreservedState,
+) {
+    match block {
+        CapabilityBlock::CharacterController { movement, inputs } => {
+            commands.entity(entity).insert(CharacterController {
+                movement: movement.clone(),
+                inputs: inputs.clone(),
+                ..default()
+            });
+        }
+        CapabilityBlock::OrbitCamera { target, camera } => {
+            let Some(&target_entity) = spawned.get(target) else {
+                warn!("Entity '{}': OrbitCamera target '{}' does not exist", def.id, target);
+                return;
+            };
+            let mut orbit = OrbitCamera::new(target_entity, camera);
+            preserved.apply_orbit(&def.id, &mut orbit);
+            commands.entity(entity).insert((Camera3d::default(), orbit));
+        }
+        CapabilityBlock::AnimationController { animations } => {
+            let Some(model) = &def.model else {
+                warn!("Entity '{}': AnimationController requires a model", def.id);
+                return;
+            };
+            commands
+                .entity(entity)
+                .insert(AnimationController::new(model, animations.clone(), asset_server));
+        }
+        CapabilityBlock::PointLight { color, intensity, range, shadows } => {
+            commands.entity(entity).insert(PointLight {
+                color: Color::srgb(color.0, color.1, color.2),
+                intensity: *intensity,
+                range: *range,
+                shadows_enabled: *shadows,
+                ..default()
+            });
+        }
+        CapabilityBlock::SpotLight { color, intensity, range, inner_angle, outer_angle, shadows } => {
+            commands.entity(entity).insert(SpotLight {
+                color: Color::srgb(color.0, color.1, color.2),
+                intensity: *intensity,
+                range: *range,
+                inner_angle: inner_angle.to_radians(),
+                outer_angle: outer_angle.to_radians(),
+                shadows_enabled: *shadows,
+                ..default()
+            });
+        }
+        CapabilityBlock::DirectionalLight { color, illuminance, shadows } => {
+            commands.entity(entity).insert(DirectionalLight {
+                color: Color::srgb(color.0, color.1, color.2),
+                illuminance: *illuminance,
+                shadows_enabled: *shadows,
+                ..default()
+            });
+        }
+        CapabilityBlock::Collider { shape, trigger } => {
+            commands.entity(entity).insert(Collider {
+                shape: *shape,
+                is_trigger: *trigger,
+            });
+        }
+        CapabilityBlock::Behavior { machine, locals, interact_radius } => {
+            commands.entity(entity).insert(Behavior::new(
+                asset_server.load(machine.clone()),
+                locals.clone(),
+                *interact_radius,
+            ));
+        }
+    }
+}
+
+/// Prefab instances queued by `Action::SpawnPrefab`, spawned once their
+/// prefab has loaded.
+#[derive(Resource, Default)]
+pub struct PendingPrefabs(pub Vec<PendingPrefab>);
+
+pub struct PendingPrefab {
+    pub handle: Handle<Prefab>,
+    pub instance: PrefabInstance,
+}
+
+/// Spawns a resolved prefab instance, parenting its children to the root.
+pub fn spawn_prefab(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    root: EntityDef,
+    children: Vec<EntityDef>,
+    spawned: &mut HashMap<String, Entity>,
+) {
+    let preserved = PreservedState::default();
+    let root_id = root.id.clone();
+    spawn_entities(commands, asset_server, &[root], &preserved, spawned);
+    spawn_entities(commands, asset_server, &children, &preserved, spawned);
+    for child in &children {
+        commands.entity(spawned[&child.id]).insert(ChildOf(spawned[&root_id]));
+    }
+}
+
+pub fn spawn_pending_prefabs(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    prefabs: Res<Assets<Prefab>>,
+    mut pending: ResMut<PendingPrefabs>,
+    ids: Query<(Entity, &EntityId)>,
+) {
+    if pending.0.is_empty() {
+        return;
+    }
+    let mut spawned: HashMap<String, Entity> = ids.iter().map(|(entity, id)| (id.0.clone(), entity)).collect();
+
+    for entry in std::mem::take(&mut pending.0) {
+        if asset_server.load_state(&entry.handle).is_failed() {
+            warn!("Prefab '{}' failed to load, not spawning it", entry.instance.prefab);
+            continue;
+        }
+        let Some(prefab) = prefabs.get(&entry.handle) else {
+            pending.0.push(entry);
+            continue;
+        };
+        // Default ids skip any already in use.
+        let mut index = 0;
+        while entry.instance.id.is_none() && spawned.contains_key(&entry.instance.id_or_default(index)) {
+            index += 1;
+        }
+        let id = entry.instance.id_or_default(index);
+        match prefab.instantiate(&entry.instance, &id) {
+            Ok((root, children)) => {
+                if let Some(taken) = std::iter::once(&root).chain(&children).find(|def| spawned.contains_key(&def.id)) {
+                    warn!("Prefab '{}': id '{}' is already in use, not spawning it", entry.instance.prefab, taken.id);
+                    continue;
+                }
+                println!("Spawning prefab '{}' as '{}'", entry.instance.prefab, id);
+                spawn_prefab(&mut commands, &asset_server, root, children, &mut spawned);
+            }
+            Err(e) => warn!("Prefab '{}': {}", entry.instance.prefab, e),
+        }
+    }
+}
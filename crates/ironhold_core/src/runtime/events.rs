@@ -0,0 +1,224 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Deref;
+use crate::schema::*;
+use crate::runtime::actions::Action;
+
+/// Numeric id of an event name. Ids are a hash of the name, so they are the
+/// same on every run and machine, whatever order assets load in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EventId(pub u64);
+
+impl EventId {
+    /// FNV-1a hash of `name`.
+    pub const fn of(name: &str) -> Self {
+        let bytes = name.as_bytes();
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let mut i = 0;
+        while i < bytes.len() {
+            hash ^= bytes[i] as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+            i += 1;
+        }
+        EventId(hash)
+    }
+}
+
+/// An event name written in content, e.g. a transition's `event`. Its id is
+/// computed once when the asset is parsed, so matching compares ids only.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventName {
+    name: String,
+    id: EventId,
+}
+
+impl EventName {
+    pub fn new(name: impl Into<String>) -> Self {
+        let name = name.into();
+        let id = EventId::of(&name);
+        Self { name, id }
+    }
+
+    pub fn id(&self) -> EventId {
+        self.id
+    }
+}
+
+impl Deref for EventName {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.name
+    }
+}
+
+impl fmt::Display for EventName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+impl<'de> Deserialize<'de> for EventName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(EventName::new)
+    }
+}
+
+/// An event for the global logic and `WaitForEvent` actions, e.g. a button's
+/// `Emit("ui.start")` or an `EmitEvent` action. The payload is a plain
+/// `Value`, so events can be recorded and replayed.
+#[derive(Message, Debug, Clone, PartialEq)]
+pub struct GameEvent {
+    pub id: EventId,
+    pub payload: Option<Value>,
+}
+
+impl GameEvent {
+    pub fn new(id: EventId) -> Self {
+        Self { id, payload: None }
+    }
+}
+
+/// Names of the events content refers to, interned as assets load.
+#[derive(Resource, Default, Debug)]
+pub struct EventRegistry {
+    names: HashMap<EventId, String>,
+}
+
+impl EventRegistry {
+    /// Registers `name` and returns its id.
+    pub fn intern(&mut self, name: &str) -> EventId {
+        let id = EventId::of(name);
+        match self.names.get(&id) {
+            Some(existing) if existing != name => {
+                warn!("Event names '{}' and '{}' have the same id {:?}", existing, name, id);
+            }
+            Some(_) => {}
+            None => {
+                self.names.insert(id, name.to_string());
+            }
+        }
+        id
+    }
+
+    /// The id of `name`, if it has been registered.
+    pub fn id(&self, name: &str) -> Option<EventId> {
+        let id = EventId::of(name);
+        self.names.contains_key(&id).then_some(id)
+    }
+
+    pub fn name(&self, id: EventId) -> Option<&str> {
+        self.names.get(&id).map(String::as_str)
+    }
+
+    /// Registers the events an action emits or waits for, including those of
+    /// nested actions.
+    pub fn intern_action(&mut self, action: &Action) {
+        match action {
+            Action::EmitEvent(name) | Action::WaitForEvent(name) => {
+                self.intern(name);
+            }
+            Action::Sequence(actions) | Action::Parallel(actions) | Action::RandomChoice(actions) => {
+                for action in actions {
+                    self.intern_action(action);
+                }
+            }
+            Action::Delay(_, action) => self.intern_action(action),
+            _ => {}
+        }
+    }
+
+    fn intern_elements(&mut self, elements: &[UiElement]) {
+        for element in elements {
+            match element {
+                UiElement::Button { action: UiAction::Emit(name), .. } => {
+                    self.intern(name);
+                }
+                UiElement::Button { action, .. } => {
+                    for action in action.actions() {
+                        self.intern_action(&action);
+                    }
+                }
+                UiElement::Rebind { .. } => {}
+            }
+        }
+    }
+
+    fn intern_machine_actions(&mut self, states: &[LogicState], transitions: &[Transition]) {
+        let actions = states
+            .iter()
+            .flat_map(|state| state.on_enter.iter().chain(&state.on_exit))
+            .chain(transitions.iter().flat_map(|transition| &transition.actions));
+        for action in actions {
+            self.intern_action(action);
+        }
+    }
+}
+
+/// Assets whose event names are registered when they load.
+pub trait EventSource: Asset {
+    fn intern_events(&self, registry: &mut EventRegistry);
+}
+
+impl EventSource for GlobalLogic {
+    fn intern_events(&self, registry: &mut EventRegistry) {
+        for transition in &self.transitions {
+            registry.intern(&transition.event);
+        }
+        registry.intern_machine_actions(&self.states, &self.transitions);
+    }
+}
+
+impl EventSource for BehaviorMachine {
+    fn intern_events(&self, registry: &mut EventRegistry) {
+        // Transition events of behaviors arrive as `BehaviorMessage`s, not `GameEvent`s.
+        registry.intern_machine_actions(&self.states, &self.transitions);
+    }
+}
+
+impl EventSource for GameLevel {
+    fn intern_events(&self, registry: &mut EventRegistry) {
+        registry.intern_elements(&self.ui);
+    }
+}
+
+impl EventSource for UiMenu {
+    fn intern_events(&self, registry: &mut EventRegistry) {
+        registry.intern_elements(&self.elements);
+    }
+}
+
+/// Registers the event names of `A` assets as they load or change.
+pub fn register_events_system<A: EventSource>(
+    mut asset_events: MessageReader<AssetEvent<A>>,
+    assets: Res<Assets<A>>,
+    mut registry: ResMut<EventRegistry>,
+) {
+    for event in asset_events.read() {
+        if let AssetEvent::Added { id } | AssetEvent::Modified { id } = event {
+            if let Some(asset) = assets.get(*id) {
+                asset.intern_events(&mut registry);
+            }
+        }
+    }
+}
+
+/// Sends a `key.<KeyCode>` event, e.g. `key.KeyE`, for every key pressed this
+/// frame, in name order. Sent as `GameEvent`s so gameplay ticks see each
+/// press once, whatever the frame rate.
+pub fn key_events_system(
+    keys: Option<Res<ButtonInput<KeyCode>>>,
+    mut registry: ResMut<EventRegistry>,
+    mut game_events: MessageWriter<GameEvent>,
+) {
+    let Some(keys) = keys else {
+        return;
+    };
+    let mut pressed: Vec<String> = keys.get_just_pressed().map(|key| format!("key.{:?}", key)).collect();
+    pressed.sort();
+    for name in pressed {
+        game_events.write(GameEvent::new(registry.intern(&name)));
+    }
+}
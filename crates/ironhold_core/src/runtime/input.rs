@@ -0,0 +1,371 @@
+use bevy::input::mouse::{MouseMotion, MouseWheel};
+use bevy::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+use crate::schema::*;
+use crate::capabilities::player::CharacterController;
+use crate::runtime::storage::{PersistentStorage, StorageBackend};
+
+/// Handle to the project's `InputProfile`, when it has one.
+#[derive(Resource)]
+pub struct InputProfileHandle(pub Handle<InputProfile>);
+
+/// Raw input read by `InputActions::update`.
+#[derive(Default)]
+pub struct InputSources<'a> {
+    pub keys: Option<&'a ButtonInput<KeyCode>>,
+    pub mouse_buttons: Option<&'a ButtonInput<MouseButton>>,
+    pub mouse_motion: Vec2,
+    pub mouse_wheel: f32,
+    pub gamepads: Vec<&'a Gamepad>,
+}
+
+impl InputSources<'_> {
+    fn pressed(&self, binding: &ButtonBinding) -> bool {
+        match binding {
+            ButtonBinding::Key(name) => InputMap::parse_key(name)
+                .zip(self.keys)
+                .is_some_and(|(key, keys)| keys.pressed(key)),
+            ButtonBinding::Mouse(name) => parse_mouse_button(name)
+                .zip(self.mouse_buttons)
+                .is_some_and(|(button, buttons)| buttons.pressed(button)),
+            ButtonBinding::Gamepad(name) => parse_gamepad_button(name)
+                .is_some_and(|button| self.gamepads.iter().any(|gamepad| gamepad.pressed(button))),
+        }
+    }
+}
+
+/// Evaluates bindings against `InputSources`, skipping consumed buttons.
+struct Evaluator<'a> {
+    sources: &'a InputSources<'a>,
+    consumed: &'a [ButtonBinding],
+    deadzone: f32,
+}
+
+impl Evaluator<'_> {
+    fn pressed(&self, binding: &ButtonBinding) -> bool {
+        !self.consumed.contains(binding) && self.sources.pressed(binding)
+    }
+
+    fn value(&self, binding: &ButtonBinding) -> f32 {
+        if self.pressed(binding) { 1.0 } else { 0.0 }
+    }
+
+    fn axis(&self, binding: &AxisBinding) -> f32 {
+        match binding {
+            AxisBinding::Buttons { negative, positive } => self.value(positive) - self.value(negative),
+            AxisBinding::GamepadAxis(name) => parse_gamepad_axis(name)
+                .map(|axis| {
+                    let values = self.sources.gamepads.iter().filter_map(|gamepad| gamepad.get(axis));
+                    largest(values.map(|value| if value.abs() < self.deadzone { 0.0 } else { value }))
+                })
+                .unwrap_or(0.0),
+            AxisBinding::MouseWheel => self.sources.mouse_wheel,
+        }
+    }
+
+    fn axis_2d(&self, binding: &Axis2dBinding) -> Vec2 {
+        match binding {
+            Axis2dBinding::Buttons { up, down, left, right } => Vec2::new(
+                self.value(right) - self.value(left),
+                self.value(up) - self.value(down),
+            ),
+            Axis2dBinding::GamepadStick(stick) => {
+                let values = self.sources.gamepads.iter().map(|gamepad| match stick {
+                    Stick::Left => gamepad.left_stick(),
+                    Stick::Right => gamepad.right_stick(),
+                });
+                largest_2d(values.map(|value| if value.length() < self.deadzone { Vec2::ZERO } else { value }))
+            }
+            Axis2dBinding::MouseMotion => self.sources.mouse_motion,
+            Axis2dBinding::MouseDrag(name) => {
+                if self.pressed(&ButtonBinding::Mouse(name.clone())) { self.sources.mouse_motion } else { Vec2::ZERO }
+            }
+        }
+    }
+}
+
+fn largest(values: impl Iterator<Item = f32>) -> f32 {
+    values.fold(0.0, |best, value| if value.abs() > best.abs() { value } else { best })
+}
+
+fn largest_2d(values: impl Iterator<Item = Vec2>) -> Vec2 {
+    values.fold(Vec2::ZERO, |best, value| if value.length_squared() > best.length_squared() { value } else { best })
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct ActionState {
+    pressed: bool,
+    just_pressed: bool,
+    just_released: bool,
+    /// Pressed or released since the last gameplay tick.
+    tick_pressed: bool,
+    tick_released: bool,
+    value: Vec2,
+}
+
+/// The state of every action in the active `InputProfile`, updated at the
+/// start of each frame. Gameplay reads actions here instead of raw input.
+#[derive(Resource, Debug, Default)]
+pub struct InputActions {
+    profile: InputProfile,
+    states: BTreeMap<String, ActionState>,
+    /// Held buttons `update` treats as released, see `consume`.
+    consumed: Vec<ButtonBinding>,
+}
+
+impl InputActions {
+    pub fn new(profile: InputProfile) -> Self {
+        Self { profile, states: BTreeMap::new(), consumed: Vec::new() }
+    }
+
+    pub fn profile(&self) -> &InputProfile {
+        &self.profile
+    }
+
+    /// Replaces the profile. Actions it no longer has are released.
+    pub fn set_profile(&mut self, profile: InputProfile) {
+        self.states.retain(|name, _| profile.actions.contains_key(name));
+        self.profile = profile;
+    }
+
+    /// Whether any binding of the action is active. Axes are pressed while
+    /// they are not zero.
+    pub fn pressed(&self, action: &str) -> bool {
+        self.states.get(action).is_some_and(|state| state.pressed)
+    }
+
+    pub fn just_pressed(&self, action: &str) -> bool {
+        self.states.get(action).is_some_and(|state| state.just_pressed)
+    }
+
+    pub fn just_released(&self, action: &str) -> bool {
+        self.states.get(action).is_some_and(|state| state.just_released)
+    }
+
+    /// Whether the action was pressed since the last gameplay tick. Systems in
+    /// `FixedUpdate` read this instead of `just_pressed`, so a press is seen by
+    /// exactly one tick whether a frame runs no ticks or several.
+    pub fn tick_just_pressed(&self, action: &str) -> bool {
+        self.states.get(action).is_some_and(|state| state.tick_pressed)
+    }
+
+    pub fn tick_just_released(&self, action: &str) -> bool {
+        self.states.get(action).is_some_and(|state| state.tick_released)
+    }
+
+    /// Clears the presses and releases seen by the tick that just ran.
+    pub fn end_tick(&mut self) {
+        for state in self.states.values_mut() {
+            state.tick_pressed = false;
+            state.tick_released = false;
+        }
+    }
+
+    /// Value of an `Axis` action; `1.0`/`0.0` for buttons.
+    pub fn axis(&self, action: &str) -> f32 {
+        self.states.get(action).map_or(0.0, |state| state.value.x)
+    }
+
+    /// Value of an `Axis2d` action.
+    pub fn axis_2d(&self, action: &str) -> Vec2 {
+        self.states.get(action).map_or(Vec2::ZERO, |state| state.value)
+    }
+
+    /// Names of the button actions pressed this frame, in name order.
+    pub fn just_pressed_actions(&self) -> impl Iterator<Item = &str> {
+        self.states.iter().filter(|(_, state)| state.just_pressed).map(|(name, _)| name.as_str())
+    }
+
+    /// Evaluates every action against this frame's input.
+    pub fn update(&mut self, sources: &InputSources) {
+        self.consumed.retain(|binding| sources.pressed(binding));
+        let evaluator = Evaluator { sources, consumed: &self.consumed, deadzone: self.profile.deadzone };
+        let stick_pixels = self.profile.stick_pixels;
+        let values: Vec<(String, Vec2)> = self
+            .profile
+            .actions
+            .iter()
+            .map(|(name, action)| {
+                let value = match action {
+                    InputAction::Button(bindings) => {
+                        Vec2::new(if bindings.iter().any(|binding| evaluator.pressed(binding)) { 1.0 } else { 0.0 }, 0.0)
+                    }
+                    InputAction::Axis(bindings) => {
+                        Vec2::new(largest(bindings.iter().map(|binding| evaluator.axis(binding))), 0.0)
+                    }
+                    InputAction::Axis2d(bindings) => {
+                        // Sticks are scaled to the mouse's pixels per frame in actions that read both.
+                        let stick_scale = if action.reads_mouse() { stick_pixels } else { 1.0 };
+                        largest_2d(bindings.iter().map(|binding| match binding {
+                            Axis2dBinding::GamepadStick(_) => evaluator.axis_2d(binding) * stick_scale,
+                            _ => evaluator.axis_2d(binding),
+                        }))
+                    }
+                };
+                (name.clone(), value)
+            })
+            .collect();
+        for (name, value) in values {
+            self.set_value(name, value);
+        }
+    }
+
+    /// Ignores `binding` until it is released, e.g. the press a rebind
+    /// button captured, so it does not also act on the game.
+    pub fn consume(&mut self, binding: ButtonBinding) {
+        if !self.consumed.contains(&binding) {
+            self.consumed.push(binding);
+        }
+    }
+
+    /// Sets every action's value directly instead of reading input, e.g. when
+    /// replaying a recording. Actions missing from `values` are released.
+    pub fn set_values(&mut self, values: &BTreeMap<String, Vec2>) {
+        let names: BTreeSet<String> = self.states.keys().chain(values.keys()).cloned().collect();
+        for name in names {
+            let value = values.get(&name).copied().unwrap_or(Vec2::ZERO);
+            self.set_value(name, value);
+        }
+    }
+
+    /// The actions with a non-zero value, in name order.
+    pub fn values(&self) -> impl Iterator<Item = (&str, Vec2)> {
+        self.states
+            .iter()
+            .filter(|(_, state)| state.value != Vec2::ZERO)
+            .map(|(name, state)| (name.as_str(), state.value))
+    }
+
+    fn set_value(&mut self, name: String, value: Vec2) {
+        let state = self.states.entry(name).or_default();
+        let pressed = value != Vec2::ZERO;
+        let just_pressed = pressed && !state.pressed;
+        let just_released = !pressed && state.pressed;
+        *state = ActionState {
+            pressed,
+            just_pressed,
+            just_released,
+            tick_pressed: state.tick_pressed || just_pressed,
+            tick_released: state.tick_released || just_released,
+            value,
+        };
+    }
+}
+
+pub fn input_actions_system(
+    keys: Option<Res<ButtonInput<KeyCode>>>,
+    mouse_buttons: Option<Res<ButtonInput<MouseButton>>>,
+    mut mouse_motion: MessageReader<MouseMotion>,
+    mut mouse_wheel: MessageReader<MouseWheel>,
+    gamepads: Query<&Gamepad>,
+    mut actions: ResMut<InputActions>,
+) {
+    let sources = InputSources {
+        keys: keys.as_deref(),
+        mouse_buttons: mouse_buttons.as_deref(),
+        mouse_motion: mouse_motion.read().map(|event| event.delta).sum(),
+        mouse_wheel: mouse_wheel.read().map(|event| event.y).sum(),
+        gamepads: gamepads.iter().collect(),
+    };
+    actions.update(&sources);
+}
+
+/// Ends the gameplay tick for `InputActions`; runs in `FixedLast`.
+pub fn end_input_tick_system(mut actions: ResMut<InputActions>) {
+    actions.end_tick();
+}
+
+/// Applies the project's `InputProfile` when it loads or changes, with the
+/// player's `InputOverrides` on top. Projects without one use the default
+/// bindings with the keys of the player's `InputMap`.
+pub fn input_profile_system(
+    handle: Option<Res<InputProfileHandle>>,
+    profiles: Res<Assets<InputProfile>>,
+    mut events: MessageReader<AssetEvent<InputProfile>>,
+    characters: Query<&CharacterController, Added<CharacterController>>,
+    overrides: Res<InputOverrides>,
+    mut actions: ResMut<InputActions>,
+) {
+    match handle {
+        Some(handle) => {
+            for event in events.read() {
+                if event.is_loaded_with_dependencies(&handle.0) || event.is_modified(&handle.0) {
+                    if let Some(profile) = profiles.get(&handle.0) {
+                        println!("Input profile applied: {} actions", profile.actions.len());
+                        let mut profile = profile.clone();
+                        overrides.apply_to_profile(&mut profile);
+                        actions.set_profile(profile);
+                    }
+                }
+            }
+        }
+        None => {
+            events.clear();
+            if let Some(inputs) = characters.iter().find_map(|controller| controller.inputs.as_ref()) {
+                let mut profile = InputProfile::from_input_map(inputs);
+                overrides.apply_to_profile(&mut profile);
+                actions.set_profile(profile);
+            }
+        }
+    }
+}
+
+/// Re-applies the player's overrides on top of a character's `InputMap`
+/// when it spawns.
+pub fn apply_input_overrides_system(
+    overrides: Res<InputOverrides>,
+    mut characters: Query<&mut CharacterController, Added<CharacterController>>,
+) {
+    if overrides.bindings.is_empty() {
+        return;
+    }
+    for mut controller in &mut characters {
+        if let Some(inputs) = controller.inputs.as_mut() {
+            overrides.apply_to_map(inputs);
+        }
+    }
+}
+
+/// Reads the persisted overrides. Missing or unreadable overrides are
+/// treated as none.
+pub fn load_input_overrides(storage: &dyn PersistentStorage) -> InputOverrides {
+    match storage.read(InputOverrides::STORAGE_KEY) {
+        Ok(Some(document)) => ron::from_str(&document).unwrap_or_else(|e| {
+            warn!("Ignoring saved input overrides: {}", e);
+            InputOverrides::default()
+        }),
+        Ok(None) => InputOverrides::default(),
+        Err(e) => {
+            warn!("Failed to read input overrides: {}", e);
+            InputOverrides::default()
+        }
+    }
+}
+
+pub fn save_input_overrides(overrides: &InputOverrides, storage: &mut dyn PersistentStorage) {
+    let document = match ron::ser::to_string_pretty(overrides, ron::ser::PrettyConfig::default()) {
+        Ok(document) => document,
+        Err(e) => {
+            warn!("Failed to serialize input overrides: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = storage.write(InputOverrides::STORAGE_KEY, &document) {
+        warn!("Failed to save input overrides: {}", e);
+    }
+}
+
+/// Loads the persisted overrides at startup and applies them to the active bindings.
+pub fn load_input_overrides_system(
+    storage: Res<StorageBackend>,
+    mut overrides: ResMut<InputOverrides>,
+    mut actions: ResMut<InputActions>,
+) {
+    *overrides = load_input_overrides(storage.0.as_ref());
+    if !overrides.bindings.is_empty() {
+        println!("Loaded {} input override(s)", overrides.bindings.len());
+        let mut profile = actions.profile().clone();
+        overrides.apply_to_profile(&mut profile);
+        actions.set_profile(profile);
+    }
+}
@@ -0,0 +1,263 @@
+use bevy::asset::{AssetLoadError, LoadState, RecursiveDependencyLoadState, UntypedAssetId};
+use bevy::prelude::*;
+use crate::schema::*;
+use crate::runtime::actions::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadFailureKind {
+    Project,
+    Scene,
+}
+
+/// Describes the asset that sent the app into `AppState::Error`.
+#[derive(Resource, Debug, Clone)]
+pub struct LoadFailure {
+    pub kind: LoadFailureKind,
+    /// The project or scene that was loading, which Retry loads again.
+    pub asset: String,
+    /// The file that failed: `asset`, or an include or prefab it needs.
+    pub path: String,
+    pub message: String,
+    /// Line and column of the RON error, when known.
+    pub position: Option<(usize, usize)>,
+}
+
+impl LoadFailure {
+    /// `path:line:column`, or just the path if the position is unknown.
+    pub fn location(&self) -> String {
+        match self.position {
+            Some((line, column)) => format!("{}:{}:{}", self.path, line, column),
+            None => self.path.clone(),
+        }
+    }
+}
+
+/// Marker for entities belonging to the fallback error screen.
+#[derive(Component)]
+pub struct ErrorScreen;
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorScreenButton {
+    Retry,
+    BackToInitialScene,
+}
+
+/// Watches the project/scene handle for the current loading state and
+/// switches to `AppState::Error` if the load failed.
+pub fn detect_load_failures(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    state: Res<State<AppState>>,
+    project_handle: Option<Res<ProjectConfigHandle>>,
+    level_handle: Option<Res<LevelHandle>>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let (kind, id): (LoadFailureKind, UntypedAssetId) = match state.get() {
+        AppState::LoadingProject => match &project_handle {
+            Some(handle) => (LoadFailureKind::Project, handle.0.id().untyped()),
+            None => return,
+        },
+        AppState::LoadingScene => match &level_handle {
+            Some(handle) => (LoadFailureKind::Scene, handle.0.id().untyped()),
+            None => return,
+        },
+        _ => return,
+    };
+
+    // A failed include or prefab fails the scene that needs it.
+    let error = match asset_server.load_state(id) {
+        LoadState::Failed(error) => error,
+        _ => match asset_server.get_recursive_dependency_load_state(id) {
+            Some(RecursiveDependencyLoadState::Failed(error)) => error,
+            _ => return,
+        },
+    };
+
+    let asset = asset_server.get_path(id).map(|p| p.to_string());
+    let path = match error.as_ref() {
+        AssetLoadError::AssetLoaderError(loader_error) => Some(loader_error.path().to_string()),
+        _ => asset.clone(),
+    }
+    .unwrap_or_else(|| "<unknown>".to_string());
+    let asset = asset.unwrap_or_else(|| path.clone());
+    let (message, position) = describe_load_error(&error);
+    let failure = LoadFailure { kind, asset, path, message, position };
+
+    error!("Failed to load {}: {}", failure.location(), failure.message);
+    commands.insert_resource(failure);
+    next_state.set(AppState::Error);
+}
+
+/// Extracts a readable message (and RON position) from an asset load error.
+fn describe_load_error(error: &AssetLoadError) -> (String, Option<(usize, usize)>) {
+    if let AssetLoadError::AssetLoaderError(loader_error) = error {
+        if let Some(VersionedRonLoaderError::Schema(schema)) =
+            loader_error.error().downcast_ref::<VersionedRonLoaderError>()
+        {
+            return (schema.message(), schema.position());
+        }
+        return (loader_error.error().to_string(), None);
+    }
+    (error.to_string(), None)
+}
+
+pub fn spawn_error_screen(
+    mut commands: Commands,
+    failure: Res<LoadFailure>,
+    level_entities: Query<Entity, With<LevelEntity>>,
+) {
+    // Whatever was on screen belongs to the scene we failed to replace.
+    for entity in level_entities.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let title = match failure.kind {
+        LoadFailureKind::Project => "Failed to load project",
+        LoadFailureKind::Scene => "Failed to load scene",
+    };
+
+    commands.spawn((Camera2d, ErrorScreen));
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(12.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.08, 0.02, 0.02)),
+            ErrorScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(title),
+                TextFont {
+                    font_size: 40.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.95, 0.35, 0.35)),
+            ));
+            parent.spawn((
+                Text::new(failure.location()),
+                TextFont {
+                    font_size: 24.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.9, 0.9, 0.9)),
+            ));
+            parent.spawn((
+                Text::new(failure.message.clone()),
+                TextFont {
+                    font_size: 20.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.8, 0.8, 0.8)),
+                Node {
+                    max_width: Val::Percent(80.0),
+                    ..default()
+                },
+            ));
+
+            parent
+                .spawn(Node {
+                    column_gap: Val::Px(16.0),
+                    margin: UiRect::top(Val::Px(24.0)),
+                    ..default()
+                })
+                .with_children(|row| {
+                    spawn_error_button(row, "Retry", ErrorScreenButton::Retry);
+                    if failure.kind == LoadFailureKind::Scene {
+                        spawn_error_button(row, "Back to start", ErrorScreenButton::BackToInitialScene);
+                    }
+                });
+        });
+}
+
+fn spawn_error_button(parent: &mut ChildSpawnerCommands, text: &str, button: ErrorScreenButton) {
+    parent
+        .spawn((
+            Button,
+            Node {
+                width: Val::Px(200.0),
+                height: Val::Px(65.0),
+                border: UiRect::all(Val::Px(5.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BorderColor::from(Color::BLACK),
+            BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
+            button,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(text),
+                TextFont {
+                    font_size: 28.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.9, 0.9, 0.9)),
+            ));
+        });
+}
+
+pub fn despawn_error_screen(mut commands: Commands, query: Query<Entity, With<ErrorScreen>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+    commands.remove_resource::<LoadFailure>();
+}
+
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn error_screen_button_system(
+    mut commands: Commands,
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &ErrorScreenButton),
+        Changed<Interaction>,
+    >,
+    failure: Option<Res<LoadFailure>>,
+    project_handle: Option<Res<ProjectConfigHandle>>,
+    configs: Res<Assets<ProjectConfig>>,
+    asset_server: Res<AssetServer>,
+    mut action_queue: ResMut<ActionQueue>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let Some(failure) = failure else { return; };
+
+    for (interaction, mut color, button) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                *color = BackgroundColor(Color::srgb(0.35, 0.75, 0.35));
+                match (button, failure.kind) {
+                    (ErrorScreenButton::Retry, LoadFailureKind::Project) => {
+                        println!("Retrying project load: {}", failure.asset);
+                        let handle = asset_server.load(failure.asset.clone());
+                        commands.insert_resource(ProjectConfigHandle(handle));
+                        next_state.set(AppState::LoadingProject);
+                    }
+                    (ErrorScreenButton::Retry, LoadFailureKind::Scene) => {
+                        action_queue.push(Action::LoadScene(failure.asset.clone()));
+                    }
+                    (ErrorScreenButton::BackToInitialScene, _) => {
+                        let initial_scene = project_handle
+                            .as_ref()
+                            .and_then(|handle| configs.get(&handle.0))
+                            .map(|config| config.initial_scene.clone());
+                        if let Some(initial_scene) = initial_scene {
+                            action_queue.push(Action::LoadScene(initial_scene));
+                        }
+                    }
+                }
+            }
+            Interaction::Hovered => {
+                *color = BackgroundColor(Color::srgb(0.25, 0.25, 0.25));
+            }
+            Interaction::None => {
+                *color = BackgroundColor(Color::srgb(0.15, 0.15, 0.15));
+            }
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use bevy::prelude::*;
+use crate::schema::*;
+use crate::runtime::actions::*;
+use crate::runtime::events::EventId;
+use crate::runtime::variables::Variables;
+use crate::runtime::rng::GameRng;
+use std::collections::HashMap;
+
+/// The running state of the project's `GlobalLogic`, exposed for inspection.
+#[derive(Resource, Default, Debug, Clone)]
+pub struct GlobalLogicState {
+    current: Option<String>,
+    /// Seconds spent in the current state.
+    elapsed: f32,
+}
+
+impl GlobalLogicState {
+    /// Name of the current state, or `None` before the logic has started.
+    pub fn current(&self) -> Option<&str> {
+        self.current.as_deref()
+    }
+
+    /// Seconds spent in the current state.
+    pub fn elapsed(&self) -> f32 {
+        self.elapsed
+    }
+
+    /// Puts the machine back in a saved state without running any actions.
+    pub fn restore(&mut self, current: Option<String>, elapsed: f32) {
+        self.current = current;
+        self.elapsed = elapsed;
+    }
+
+    /// Enters `initial_state` if the machine has not started yet.
+    pub fn start(&mut self, logic: &GlobalLogic, queue: &mut ActionQueue) {
+        if self.current.is_some() {
+            return;
+        }
+        println!("Global logic starting in state '{}'", logic.initial_state);
+        if let Some(state) = logic.state(&logic.initial_state) {
+            queue.extend(state.on_enter.iter().cloned());
+        }
+        self.current = Some(logic.initial_state.clone());
+    }
+
+    /// Feeds `event` to the machine, queueing the actions of the transition
+    /// it triggers. Returns whether a transition fired.
+    pub fn handle_event(
+        &mut self,
+        logic: &GlobalLogic,
+        event: EventId,
+        context: &mut ConditionContext,
+        queue: &mut ActionQueue,
+    ) -> bool {
+        let Some(current) = &self.current else {
+            return false;
+        };
+        let Some(step) = step(&logic.states, &logic.transitions, current, event, context) else {
+            return false;
+        };
+        if let Some(to) = &step.to {
+            println!("Global logic: '{}' --{}--> '{}'", current, step.event, to);
+            self.current = Some(to.clone());
+            self.elapsed = 0.0;
+        }
+        queue.extend(step.actions);
+        true
+    }
+
+    /// Advances the current state's timer by `delta` seconds, firing a
+    /// `"timer"` event when it runs out.
+    pub fn tick(
+        &mut self,
+        logic: &GlobalLogic,
+        delta: f32,
+        context: &mut ConditionContext,
+        queue: &mut ActionQueue,
+    ) {
+        let Some(current) = &self.current else {
+            return;
+        };
+        let before = self.elapsed;
+        self.elapsed += delta;
+        if timer_due(logic.state(current), before, self.elapsed) {
+            self.handle_event(logic, EventId::of(TIMER_EVENT), context, queue);
+        }
+    }
+}
+
+/// Whether `state`'s timer ran out between `before` and `after` seconds in it.
+pub fn timer_due(state: Option<&LogicState>, before: f32, after: f32) -> bool {
+    state
+        .and_then(|state| state.timer)
+        .is_some_and(|timer| before < timer && after >= timer)
+}
+
+/// What guards can look at.
+#[derive(Debug, Default)]
+pub struct ConditionContext<'a> {
+    /// Path of the active scene.
+    pub scene: Option<&'a str>,
+    /// Locals of the behavior handling the event.
+    pub locals: Option<&'a HashMap<String, Value>>,
+    /// Id of the entity that caused the event.
+    pub other: Option<&'a str>,
+    /// Project variables, looked up by expression names without `self.`;
+    /// without them those names are undefined.
+    pub variables: Option<&'a Variables>,
+    /// Source of `Random` conditions and `random()`; without it they are
+    /// false and undefined.
+    pub rng: Option<&'a mut GameRng>,
+}
+
+impl ExpressionContext for ConditionContext<'_> {
+    /// Value of an expression variable: `self.<name>` is a behavior local,
+    /// anything else a project variable.
+    fn lookup(&self, name: &str) -> Option<Value> {
+        match name.strip_prefix("self.") {
+            Some(local) => self.locals?.get(local).cloned(),
+            None => self.variables?.get(name).cloned(),
+        }
+    }
+
+    fn random(&mut self) -> Option<f64> {
+        self.rng.as_deref_mut().map(GameRng::next_f64)
+    }
+}
+
+impl Condition {
+    pub fn evaluate(&self, context: &mut ConditionContext) -> bool {
+        match self {
+            Condition::SceneIs(path) => context.scene == Some(path.as_str()),
+            Condition::LocalIs(name, value) => {
+                context.locals.and_then(|locals| locals.get(name)) == Some(value)
+            }
+            Condition::OtherIs(id) => context.other == Some(id.as_str()),
+            Condition::Expr(expression) => expression.evaluate(context),
+            Condition::Random(p) => context.rng.as_deref_mut().is_some_and(|rng| rng.chance(*p)),
+            Condition::Not(inner) => !inner.evaluate(context),
+            Condition::All(conditions) => conditions.iter().all(|c| c.evaluate(context)),
+            Condition::Any(conditions) => conditions.iter().any(|c| c.evaluate(context)),
+        }
+    }
+}
+
+/// The effect of one event on a state machine.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    /// Name of the event that fired the transition.
+    pub event: String,
+    /// New state, if the transition changes it.
+    pub to: Option<String>,
+    /// `on_exit` of the old state, the transition's actions, then `on_enter`
+    /// of the new state.
+    pub actions: Vec<Action>,
+}
+
+/// Finds the first transition out of `current` for `event` whose guard holds.
+pub fn step(
+    states: &[LogicState],
+    transitions: &[Transition],
+    current: &str,
+    event: EventId,
+    context: &mut ConditionContext,
+) -> Option<Step> {
+    let transition = transitions.iter().find(|t| {
+        (t.from == current || t.from == ANY_STATE)
+            && t.event.id() == event
+            && t.guard.as_ref().is_none_or(|guard| guard.evaluate(context))
+    })?;
+
+    let find = |name: &str| states.iter().find(|state| state.name == name);
+    let mut actions = Vec::new();
+    if let Some(to) = &transition.to {
+        if let Some(state) = find(current) {
+            actions.extend(state.on_exit.iter().cloned());
+        }
+        actions.extend(transition.actions.iter().cloned());
+        match find(to) {
+            Some(state) => actions.extend(state.on_enter.iter().cloned()),
+            None => warn!("Transition to unknown state '{}'", to),
+        }
+    } else {
+        actions.extend(transition.actions.iter().cloned());
+    }
+
+    Some(Step {
+        event: transition.event.to_string(),
+        to: transition.to.clone(),
+        actions,
+    })
+}
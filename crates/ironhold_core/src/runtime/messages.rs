@@ -1,6 +1,11 @@
 use bevy::prelude::*;
+use crate::runtime::events::EventId;
 
-#[derive(Message, Debug, Clone)]
-pub enum UiMessage {
-    ButtonPressed(String), // The path to load or identifier
+/// An event for one entity's behavior, e.g. `"trigger.enter"` or `"interact"`.
+#[derive(Message, Debug, Clone, PartialEq, Eq)]
+pub struct BehaviorMessage {
+    pub entity: Entity,
+    pub event: EventId,
+    /// Id of the entity that caused the event, if any.
+    pub other: Option<String>,
 }
@@ -1,7 +1,37 @@
 pub mod actions;
 pub mod messages;
 pub mod scene_manager;
+pub mod load_errors;
+pub mod entities;
+pub mod logic;
+pub mod behavior;
+pub mod variables;
+pub mod ui;
+pub mod scheduler;
+pub mod events;
+pub mod input;
+pub mod storage;
+pub mod replay;
+pub mod tick;
+pub mod rng;
+pub mod snapshot;
+pub mod save;
 
 pub use actions::*;
 pub use messages::*;
 pub use scene_manager::*;
+pub use load_errors::*;
+pub use entities::*;
+pub use logic::*;
+pub use behavior::*;
+pub use variables::*;
+pub use ui::*;
+pub use scheduler::*;
+pub use events::*;
+pub use input::*;
+pub use storage::*;
+pub use replay::*;
+pub use tick::*;
+pub use rng::*;
+pub use snapshot::*;
+pub use save::*;
@@ -0,0 +1,277 @@
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::time::Duration;
+use crate::schema::*;
+use crate::runtime::input::InputActions;
+use crate::runtime::variables::Variables;
+use crate::runtime::tick::TransformInterpolation;
+use crate::runtime::rng::GameRng;
+use crate::capabilities::camera::OrbitCamera;
+use crate::ProjectConfigPath;
+
+/// Records the input actions of every in-game frame, written to `path` when
+/// the app exits.
+#[derive(Resource, Debug)]
+pub struct InputRecorder {
+    pub path: PathBuf,
+    /// Starts with the first in-game frame.
+    pub recording: Option<InputRecording>,
+}
+
+impl InputRecorder {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), recording: None }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let Some(recording) = &self.recording else {
+            return Err("nothing was recorded".to_string());
+        };
+        let document = recording.to_ron().map_err(|e| e.to_string())?;
+        std::fs::write(&self.path, document).map_err(|e| format!("{}: {}", self.path.display(), e))
+    }
+}
+
+/// Feeds a recording's input actions in place of live input, one recorded
+/// frame per in-game frame, advancing time by the recorded deltas.
+#[derive(Resource, Debug)]
+pub struct InputReplay {
+    recording: InputRecording,
+    next: usize,
+    /// Exit the app once every frame has been replayed.
+    pub exit_when_done: bool,
+    /// `state_hash` after the last frame.
+    pub final_hash: Option<u64>,
+    /// Whether time has been handed back to the clock after the last frame.
+    live_time: bool,
+}
+
+impl InputReplay {
+    pub fn new(recording: InputRecording) -> Self {
+        Self { recording, next: 0, exit_when_done: false, final_hash: None, live_time: false }
+    }
+
+    pub fn recording(&self) -> &InputRecording {
+        &self.recording
+    }
+
+    /// Number of frames replayed so far.
+    pub fn frame(&self) -> usize {
+        self.next
+    }
+
+    pub fn finished(&self) -> bool {
+        self.next >= self.recording.frames.len()
+    }
+}
+
+/// FNV-1a, so hashes are the same on every run and platform.
+pub(crate) struct StateHasher(u64);
+
+impl StateHasher {
+    pub(crate) fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for StateHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+/// Hash of the gameplay state: the app state, the simulated transform of every
+/// entity with an `EntityId`, the yaw and pitch of orbit cameras and the
+/// variables. Runs that end with the same hash ended in the same state.
+pub fn state_hash<'a>(
+    state: &AppState,
+    entities: impl Iterator<Item = (&'a EntityId, &'a Transform, Option<&'a OrbitCamera>)>,
+    variables: &Variables,
+) -> u64 {
+    let mut hasher = StateHasher::new();
+    format!("{:?}", state).hash(&mut hasher);
+
+    let mut entities: Vec<_> = entities.collect();
+    entities.sort_by(|a, b| a.0 .0.cmp(&b.0 .0));
+    for (id, transform, orbit) in entities {
+        id.0.hash(&mut hasher);
+        let values = transform.translation.to_array().into_iter()
+            .chain(transform.rotation.to_array())
+            .chain(transform.scale.to_array())
+            .chain(orbit.into_iter().flat_map(|orbit| [orbit.yaw, orbit.pitch]));
+        for value in values {
+            value.to_bits().hash(&mut hasher);
+        }
+    }
+
+    for (name, value) in variables.iter() {
+        name.hash(&mut hasher);
+        value.to_string().hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+fn frame_duration(delta: f64) -> Duration {
+    Duration::from_nanos((delta * 1e9).round() as u64)
+}
+
+/// Appends this frame's input actions to the recording.
+pub fn record_input_system(
+    time: Res<Time>,
+    actions: Res<InputActions>,
+    rng: Res<GameRng>,
+    mut recorder: ResMut<InputRecorder>,
+    config_path: Res<ProjectConfigPath>,
+    project_handle: Option<Res<ProjectConfigHandle>>,
+    projects: Res<Assets<ProjectConfig>>,
+) {
+    let recorder = &mut *recorder;
+    let recording = recorder.recording.get_or_insert_with(|| {
+        let initial_scene = project_handle
+            .and_then(|handle| projects.get(&handle.0))
+            .map(|project| project.initial_scene.clone())
+            .unwrap_or_default();
+        println!("Recording input to {}", recorder.path.display());
+        InputRecording::new(config_path.0.clone(), initial_scene, rng.seed())
+    });
+    recording.frames.push(RecordedFrame {
+        delta: time.delta().as_secs_f64(),
+        actions: actions
+            .values()
+            .map(|(name, value)| (name.to_string(), (value.x, value.y)))
+            .collect(),
+    });
+}
+
+/// Writes the recording when the app exits.
+pub fn save_recording_system(mut exits: MessageReader<AppExit>, recorder: Res<InputRecorder>) {
+    if exits.read().last().is_none() {
+        return;
+    }
+    match recorder.save() {
+        Ok(()) => println!(
+            "Saved {} recorded frames to {}",
+            recorder.recording.as_ref().map_or(0, |recording| recording.frames.len()),
+            recorder.path.display()
+        ),
+        Err(e) => warn!("Failed to save input recording: {}", e),
+    }
+}
+
+/// Advances time by the next recorded frame's delta while in game, and not at
+/// all while scenes load. Once the replay has finished, time follows the
+/// clock again along with the player's input.
+pub fn replay_time_system(
+    mut replay: ResMut<InputReplay>,
+    state: Res<State<AppState>>,
+    next_state: Res<NextState<AppState>>,
+    mut strategy: ResMut<TimeUpdateStrategy>,
+) {
+    if replay.finished() {
+        if !replay.live_time {
+            *strategy = TimeUpdateStrategy::Automatic;
+            replay.live_time = true;
+        }
+        return;
+    }
+    let delta = match replay.recording.frames.get(replay.next) {
+        Some(frame) if in_game_frame(state, next_state) => frame_duration(frame.delta),
+        _ => Duration::ZERO,
+    };
+    *strategy = TimeUpdateStrategy::ManualDuration(delta);
+}
+
+/// Whether this frame's gameplay ticks run in `AppState::InGame`. The state
+/// changes after `PreUpdate`, so a pending transition decides: the frame that
+/// enters the game is recorded and the one that leaves it is not.
+pub fn in_game_frame(state: Res<State<AppState>>, next_state: Res<NextState<AppState>>) -> bool {
+    match next_state.as_ref() {
+        NextState::Pending(next) => *next == AppState::InGame,
+        NextState::Unchanged => *state.get() == AppState::InGame,
+    }
+}
+
+/// Whether input actions come from live input: there is no replay, or it has
+/// finished.
+pub fn live_input(replay: Option<Res<InputReplay>>) -> bool {
+    replay.is_none_or(|replay| replay.finished())
+}
+
+/// Sets the input actions of the next recorded frame.
+pub fn replay_input_system(mut replay: ResMut<InputReplay>, mut actions: ResMut<InputActions>) {
+    let Some(frame) = replay.recording.frames.get(replay.next) else {
+        return;
+    };
+    let values: BTreeMap<String, Vec2> = frame
+        .actions
+        .iter()
+        .map(|(name, (x, y))| (name.clone(), Vec2::new(*x, *y)))
+        .collect();
+    actions.set_values(&values);
+    replay.next += 1;
+}
+
+/// Stops a replay that cannot match its recording: the recording started in
+/// another scene than the one loaded. Runs when the game first enters
+/// `AppState::InGame`.
+pub fn check_replay_scene_system(
+    replay: Res<InputReplay>,
+    level: Option<Res<LevelHandle>>,
+    asset_server: Res<AssetServer>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    // The frame that enters the game has already been fed.
+    let expected = &replay.recording.initial_scene;
+    if replay.next > 1 || expected.is_empty() {
+        return;
+    }
+    let loaded = level.and_then(|level| asset_server.get_path(level.0.id()));
+    let loaded = loaded.as_ref().map(|path| path.path().to_string_lossy());
+    if loaded.as_deref() != Some(expected.as_str()) {
+        error!(
+            "Replay recorded in scene {} but the game started in {}",
+            expected,
+            loaded.as_deref().unwrap_or("no scene")
+        );
+        exit.write(AppExit::error());
+    }
+}
+
+/// Stops a replay whose project or scene failed to load.
+pub fn replay_load_failed_system(mut exit: MessageWriter<AppExit>) {
+    error!("Replay stopped: the game failed to load");
+    exit.write(AppExit::error());
+}
+
+/// Reports the final state hash once every frame has been replayed.
+pub fn replay_finished_system(
+    mut replay: ResMut<InputReplay>,
+    state: Res<State<AppState>>,
+    entities: Query<(&EntityId, &Transform, Option<&TransformInterpolation>, Option<&OrbitCamera>)>,
+    variables: Res<Variables>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    if !replay.finished() || replay.final_hash.is_some() {
+        return;
+    }
+    // Interpolated entities are hashed as of the last tick, not as shown.
+    let simulated = entities.iter().map(|(id, transform, interpolation, orbit)| {
+        (id, interpolation.map_or(transform, |interpolation| &interpolation.current), orbit)
+    });
+    let hash = state_hash(state.get(), simulated, &variables);
+    println!("Replay finished after {} frames, state hash {:016x}", replay.next, hash);
+    replay.final_hash = Some(hash);
+    if replay.exit_when_done {
+        exit.write(AppExit::Success);
+    }
+}
@@ -0,0 +1,176 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use crate::schema::*;
+use crate::schema::migration::{from_versioned_str, Migration, SchemaError, VersionedAsset};
+use crate::runtime::entities::PendingPrefabs;
+use crate::runtime::snapshot::*;
+use crate::runtime::storage::{PersistentStorage, StorageBackend, StorageError};
+
+/// A saved game: the `Snapshot` of the world when `SaveGame(slot)` ran, plus
+/// what a load menu shows about it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SaveGame {
+    pub schema_version: u32,
+    /// Seconds since the Unix epoch.
+    pub saved_at: u64,
+    /// Path of the level that was active.
+    pub scene: String,
+    pub snapshot: Snapshot,
+}
+
+impl VersionedAsset for SaveGame {
+    const KIND: &'static str = "SaveGame";
+    const CURRENT_VERSION: u32 = 1;
+    const MIGRATIONS: &'static [Migration] = &[];
+
+    fn parse(document: &str) -> Result<Self, SchemaError> {
+        Ok(ron::from_str(document)?)
+    }
+}
+
+impl SaveGame {
+    /// Storage key of `slot`, e.g. `save.quick`.
+    pub fn storage_key(slot: &str) -> String {
+        format!("save.{}", slot)
+    }
+
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+    }
+}
+
+/// Why a game could not be saved or loaded.
+#[derive(Debug)]
+pub enum SaveError {
+    /// Slots are storage keys, so only letters, digits, `-` and `_` are allowed.
+    InvalidSlot(String),
+    /// Nothing has been saved in the slot.
+    EmptySlot(String),
+    /// No level is active, so there is nothing to save.
+    NoLevel,
+    Storage(StorageError),
+    Schema(SchemaError),
+    Snapshot(SnapshotError),
+    Encode(String),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::InvalidSlot(slot) => write!(
+                f,
+                "invalid save slot \"{}\": use letters, digits, '-' and '_'",
+                slot
+            ),
+            SaveError::EmptySlot(slot) => write!(f, "save slot \"{}\" is empty", slot),
+            SaveError::NoLevel => write!(f, "no level is active"),
+            SaveError::Storage(e) => write!(f, "{}", e),
+            SaveError::Schema(e) => write!(f, "{}", e),
+            SaveError::Snapshot(e) => write!(f, "{}", e),
+            SaveError::Encode(message) => write!(f, "cannot write save: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+/// Checks that `slot` can be used as a storage key.
+pub fn check_slot(slot: &str) -> Result<(), SaveError> {
+    let valid = !slot.is_empty() && slot.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(SaveError::InvalidSlot(slot.to_string()))
+    }
+}
+
+/// The game saved in `slot`.
+pub fn read_save_game(storage: &dyn PersistentStorage, slot: &str) -> Result<SaveGame, SaveError> {
+    check_slot(slot)?;
+    let document = storage
+        .read(&SaveGame::storage_key(slot))
+        .map_err(SaveError::Storage)?
+        .ok_or_else(|| SaveError::EmptySlot(slot.to_string()))?;
+    from_versioned_str(&document).map_err(SaveError::Schema)
+}
+
+/// Captures the world and writes it to `slot`.
+pub fn save_game(world: &mut World, slot: &str) -> Result<SaveGame, SaveError> {
+    check_slot(slot)?;
+    let snapshot = capture_snapshot(world).map_err(SaveError::Snapshot)?;
+    let scene = snapshot.level.clone().ok_or(SaveError::NoLevel)?;
+    let save = SaveGame {
+        schema_version: SaveGame::CURRENT_VERSION,
+        saved_at: unix_time(),
+        scene,
+        snapshot,
+    };
+    let document = save.to_ron().map_err(|e| SaveError::Encode(e.to_string()))?;
+    world
+        .resource_mut::<StorageBackend>()
+        .0
+        .write(&SaveGame::storage_key(slot), &document)
+        .map_err(SaveError::Storage)?;
+    Ok(save)
+}
+
+/// Starts loading the level saved in `slot`; `apply_pending_load_system`
+/// restores the rest of the save once it has spawned.
+pub fn load_game(world: &mut World, slot: &str) -> Result<(), SaveError> {
+    let save = read_save_game(world.resource::<StorageBackend>().0.as_ref(), slot)?;
+    let handle = world.resource::<AssetServer>().load(save.scene.clone());
+    world.insert_resource(LevelHandle(handle));
+    world.resource_mut::<PendingPrefabs>().0.clear();
+    world.insert_resource(PendingLoad(save.snapshot));
+    world.resource_mut::<NextState<AppState>>().set(AppState::LoadingScene);
+    Ok(())
+}
+
+/// A loaded save waiting for its level to spawn.
+#[derive(Resource, Debug, Clone)]
+pub struct PendingLoad(pub Snapshot);
+
+/// Restores a loaded save on top of the freshly spawned level. Entities the
+/// save has but the level does not, e.g. prefabs spawned during play, are
+/// skipped with a warning.
+pub fn apply_pending_load_system(world: &mut World) {
+    let Some(PendingLoad(mut snapshot)) = world.remove_resource::<PendingLoad>() else {
+        return;
+    };
+    let mut query = world.query::<&EntityId>();
+    let ids: Vec<String> = query.iter(world).map(|id| id.0.clone()).collect();
+    snapshot.entities.retain(|id, _| {
+        let exists = ids.contains(id);
+        if !exists {
+            warn!("Saved entity '{}' is not in the level, skipping it", id);
+        }
+        exists
+    });
+    match restore_snapshot(world, &snapshot) {
+        Ok(()) => println!("Game loaded: {}", snapshot.level.as_deref().unwrap_or_default()),
+        Err(e) => warn!("Failed to restore saved game: {}", e),
+    }
+}
+
+/// Drops a loaded save whose level failed to load, so it is not restored on
+/// top of whatever level loads next.
+pub fn discard_pending_load_system(mut commands: Commands, pending: Option<Res<PendingLoad>>) {
+    if pending.is_some() {
+        warn!("Saved game not restored: its level failed to load");
+        commands.remove_resource::<PendingLoad>();
+    }
+}
+
+fn unix_time() -> u64 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        (web_sys::js_sys::Date::now() / 1000.0) as u64
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs())
+    }
+}
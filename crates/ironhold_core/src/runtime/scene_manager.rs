@@ -1,21 +1,76 @@
+use bevy::asset::{RecursiveDependencyLoadState, UntypedAssetId};
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use crate::schema::*;
 use crate::runtime::actions::*;
 use crate::runtime::messages::*;
-use crate::capabilities::player::CharacterController;
+use crate::runtime::entities::*;
+use crate::runtime::logic::*;
+use crate::runtime::behavior::Behavior;
+use crate::runtime::variables::Variables;
+use crate::runtime::rng::GameRng;
+use crate::runtime::replay::InputReplay;
+use crate::runtime::ui::*;
+use crate::runtime::scheduler::ActionScheduler;
+use crate::runtime::tick::LogicTime;
+use crate::runtime::events::*;
+use crate::runtime::save::{load_game, save_game};
+use crate::runtime::input::InputProfileHandle;
+use crate::capabilities::player::{CharacterController, GroundPlane, Locomotion};
 use crate::capabilities::animation::AnimationController;
 use crate::capabilities::camera::OrbitCamera;
-use std::collections::HashMap;
+use crate::capabilities::velocity::Velocity;
+use std::collections::{HashMap, HashSet};
 
+/// When enabled, the active level is re-spawned whenever its file changes on disk.
+#[derive(Resource, Default, Debug, Clone, Copy)]
+pub struct HotReload {
+    pub enabled: bool,
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn check_project_loaded(
     mut commands: Commands,
     config_handle: Res<ProjectConfigHandle>,
     configs: Res<Assets<ProjectConfig>>,
     asset_server: Res<AssetServer>,
+    mut hot_reload: ResMut<HotReload>,
+    mut variables: ResMut<Variables>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+    mut rng: ResMut<GameRng>,
+    replay: Option<Res<InputReplay>>,
     mut next_state: ResMut<NextState<AppState>>,
 ) {
     if let Some(config) = configs.get(&config_handle.0) {
         println!("Project Config Loaded. Initial Scene: {}", config.initial_scene);
+        variables.declare(&config.variables);
+
+        if config.tick_rate > 0.0 {
+            fixed_time.set_timestep_hz(config.tick_rate);
+        } else {
+            warn!("Ignoring tick_rate {}, it must be positive", config.tick_rate);
+        }
+
+        // A replay keeps the seed it was recorded with.
+        if let (Some(seed), None) = (config.seed, &replay) {
+            rng.reseed(seed);
+        }
+        println!("Random seed: {}", rng.seed());
+
+        if config.hot_reload && !hot_reload.enabled {
+            println!("Scene hot reload enabled by project config");
+            hot_reload.enabled = true;
+        }
+
+        if let Some(logic) = &config.logic {
+            println!("Loading global logic: {}", logic);
+            commands.insert_resource(GlobalLogicHandle(asset_server.load(logic.clone())));
+        }
+
+        if let Some(input) = &config.input {
+            println!("Loading input profile: {}", input);
+            commands.insert_resource(InputProfileHandle(asset_server.load(input.clone())));
+        }
         
         // Load the initial scene
         let scene_handle = asset_server.load(config.initial_scene.clone());
@@ -25,149 +80,245 @@ pub fn check_project_loaded(
     }
 }
 
+/// Ids of the level behind `handle` and of the scenes and prefabs it pulls
+/// in, directly or through its includes.
+fn level_asset_ids(handle: &Handle<GameLevel>, levels: &Assets<GameLevel>) -> HashSet<UntypedAssetId> {
+    let mut ids = HashSet::from([handle.id().untyped()]);
+    let mut pending = vec![handle.id()];
+    while let Some(id) = pending.pop() {
+        let Some(level) = levels.get(id) else {
+            continue;
+        };
+        for dependency in &level.dependencies {
+            if ids.insert(dependency.id()) {
+                if let Ok(included) = dependency.id().try_typed::<GameLevel>() {
+                    pending.push(included);
+                }
+            }
+        }
+    }
+    ids
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn spawn_level(
     mut commands: Commands,
     level_handle: Option<Res<LevelHandle>>,
     levels: Res<Assets<GameLevel>>,
+    prefabs: Res<Assets<Prefab>>,
     asset_server: Res<AssetServer>,
     mut events: MessageReader<AssetEvent<GameLevel>>,
+    mut prefab_events: MessageReader<AssetEvent<Prefab>>,
     mut next_state: ResMut<NextState<AppState>>,
     state: Res<State<AppState>>,
     current_entities: Query<Entity, With<LevelEntity>>,
+    hot_reload: Res<HotReload>,
+    tracked: Query<(&EntityId, &Transform, Option<&OrbitCamera>, Has<CharacterController>)>,
+    mut variables: ResMut<Variables>,
 ) {
     let Some(level_handle) = level_handle else { return; };
     
     let mut ready_to_spawn = false;
+    let mut modified = false;
 
+    // Edits to included scenes and prefabs count as edits to the level.
+    let level_assets = if hot_reload.enabled {
+        level_asset_ids(&level_handle.0, &levels)
+    } else {
+        HashSet::new()
+    };
     for event in events.read() {
         if event.is_loaded_with_dependencies(&level_handle.0) {
             ready_to_spawn = true;
         }
+        if let AssetEvent::Modified { id } = event {
+            if level_assets.contains(&id.untyped()) {
+                ready_to_spawn = true;
+                modified = true;
+            }
+        }
+    }
+    for event in prefab_events.read() {
+        if let AssetEvent::Modified { id } = event {
+            if level_assets.contains(&id.untyped()) {
+                ready_to_spawn = true;
+                modified = true;
+            }
+        }
     }
 
-    if *state.get() == AppState::LoadingScene || *state.get() == AppState::LoadingProject {
-         if levels.get(&level_handle.0).is_some() {
-             ready_to_spawn = true;
-         }
+    // Includes and prefabs must be loaded before the level can be spawned.
+    let dependencies_loaded = matches!(
+        asset_server.get_recursive_dependency_load_state(&level_handle.0),
+        None | Some(RecursiveDependencyLoadState::Loaded)
+    );
+    if (*state.get() == AppState::LoadingScene || *state.get() == AppState::LoadingProject)
+        && levels.get(&level_handle.0).is_some()
+        && dependencies_loaded
+    {
+        ready_to_spawn = true;
     }
 
     if ready_to_spawn {
         if let Some(level) = levels.get(&level_handle.0) {
             
-            // Only spawn if we are NOT already InGame to avoid duplication loops 
-            if *state.get() == AppState::InGame {
+            // Only spawn if we are NOT already InGame to avoid duplication loops,
+            // unless one of its files was edited and hot reload is on.
+            let reloading = *state.get() == AppState::InGame;
+            if reloading && !modified {
                 return; 
             }
-            
-            println!("Level Loaded! Spawning {} models and {} ui elements", level.models.len(), level.ui.len());
+
+            let level_path = asset_server
+                .get_path(&level_handle.0)
+                .map(|path| path.to_string())
+                .unwrap_or_default();
+            let level = level.flattened(&level_path, &|path| {
+                asset_server
+                    .get_handle::<GameLevel>(path.to_string())
+                    .and_then(|handle| levels.get(&handle))
+                    .cloned()
+            });
+
+            // Keep characters and cameras where they were when re-spawning in place.
+            let mut preserved = PreservedState::default();
+            if reloading {
+                println!("Level file changed, hot reloading...");
+                for (id, transform, orbit, is_character) in tracked.iter() {
+                    if is_character {
+                        preserved.transforms.insert(id.0.clone(), *transform);
+                    }
+                    if let Some(orbit) = orbit {
+                        preserved.orbits.insert(id.0.clone(), (orbit.yaw, orbit.pitch, orbit.radius));
+                    }
+                }
+            }
+            println!(
+                "Level Loaded! Spawning {} models, {} entities, {} prefabs and {} ui elements",
+                level.models.len(),
+                level.entities.len(),
+                level.prefabs.len(),
+                level.ui.len()
+            );
             
             for entity in current_entities.iter() {
                 commands.entity(entity).despawn();
             }
 
+            variables.declare(&level.variables);
+            commands.insert_resource(GroundPlane(level.ground_plane));
+
+            let mut spawned = HashMap::new();
+
             for model in &level.models {
-                commands.spawn((
+                if let Some(id) = model.id.as_ref().filter(|id| spawned.contains_key(*id)) {
+                    warn!("Model id '{}' is already in use, not spawning it", id);
+                    continue;
+                }
+                let mut entity = commands.spawn((
                     SceneRoot(asset_server.load(model.path.clone())),
-                    Transform::from_translation(Vec3::from(model.position)),
+                    model.transform(),
                     LevelEntity,
                 ));
+                if let Some(id) = &model.id {
+                    entity.insert((Name::new(id.clone()), EntityId(id.clone())));
+                    spawned.insert(id.clone(), entity.id());
+                }
             }
 
             if !level.ui.is_empty() {
-                 commands.spawn((
-                    Node {
-                        width: Val::Percent(100.0),
-                        height: Val::Percent(100.0),
-                        align_items: AlignItems::Center,
-                        justify_content: JustifyContent::Center,
-                        ..default()
-                    },
-                    LevelEntity,
-                ))
-                .with_children(|parent| {
-                    for element in &level.ui {
-                        match element {
-                            UiElement::Button { text, action } => {
-                                parent.spawn((
-                                    Button,
-                                    Node {
-                                        width: Val::Px(150.0),
-                                        height: Val::Px(65.0),
-                                        border: UiRect::all(Val::Px(5.0)),
-                                        justify_content: JustifyContent::Center,
-                                        align_items: AlignItems::Center,
-                                        ..default()
-                                    },
-                                    BorderColor::from(Color::BLACK),
-                                    BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
-                                    action.clone(),
-                                ))
-                                .with_children(|parent| {
-                                    parent.spawn((
-                                        Text::new(text),
-                                        TextFont {
-                                            font_size: 33.0,
-                                            ..default()
-                                        },
-                                        TextColor(Color::srgb(0.9, 0.9, 0.9)),
-                                    ));
-                                });
-                            }
-                        }
-                    }
-                });
+                commands
+                    .spawn((ui_root(), LevelEntity))
+                    .with_children(|parent| spawn_ui_elements(parent, &level.ui));
             }
             
             // Spawn Player
-            if let Some(player_config) = &level.player {
-                let gltf_path = player_config.model_path.split('#').next().unwrap_or("").to_string();
-                let gltf_handle = asset_server.load(gltf_path.clone());
+            let player_taken = [PLAYER_ENTITY_ID, PLAYER_CAMERA_ENTITY_ID].into_iter().find(|id| spawned.contains_key(*id));
+            if let (Some(_), Some(id)) = (&level.player, player_taken) {
+                warn!("Id '{}' is already in use, not spawning the player", id);
+            }
+            if let Some(player_config) = level.player.as_ref().filter(|_| player_taken.is_none()) {
+                let player_transform = preserved
+                    .transforms
+                    .get(PLAYER_ENTITY_ID)
+                    .copied()
+                    .unwrap_or_else(|| Transform::from_translation(Vec3::from(player_config.initial_position)));
 
                 let player_entity = commands.spawn((
                     SceneRoot(asset_server.load(player_config.model_path.clone())),
-                    Transform::from_translation(Vec3::from(player_config.initial_position)),
+                    player_transform,
+                    Name::new(PLAYER_ENTITY_ID),
+                    EntityId(PLAYER_ENTITY_ID.to_string()),
                     LevelEntity,
                     CharacterController {
-                        walk_speed: 3.0,
-                        run_speed: 6.0,
-                        rot_speed: 3.0,
-                        inputs: player_config.inputs.clone(),
-                        is_running: false,
+                        movement: player_config.movement.clone(),
+                        inputs: Some(player_config.inputs.clone()),
+                        ..default()
                     },
-                    AnimationController {
-                        animations: player_config.animations.clone(),
-                        current: player_config.animations.idle.clone(),
-                        last_played: String::new(),
-                        gltf_path,
-                        gltf_handle,
-                        node_indices: HashMap::new(),
-                        graph_initialized: false,
-                    }
+                    AnimationController::new(&player_config.model_path, player_config.animations.clone(), &asset_server),
                 )).id();
+                spawned.insert(PLAYER_ENTITY_ID.to_string(), player_entity);
 
                 // Spawn Orbit Camera matching config
-                let start_pos = Vec3::from(player_config.initial_position) + Vec3::from(player_config.camera.offset);
+                let start_pos = player_transform.translation + Vec3::from(player_config.camera.offset);
+                let mut orbit = OrbitCamera::new(player_entity, &player_config.camera);
+                preserved.apply_orbit(PLAYER_CAMERA_ENTITY_ID, &mut orbit);
                 
-                commands.spawn((
+                let camera_entity = commands.spawn((
                     Camera3d::default(),
-                    Transform::from_translation(start_pos).looking_at(Vec3::from(player_config.initial_position), Vec3::Y),
+                    Transform::from_translation(start_pos).looking_at(player_transform.translation, Vec3::Y),
+                    Name::new(PLAYER_CAMERA_ENTITY_ID),
+                    EntityId(PLAYER_CAMERA_ENTITY_ID.to_string()),
                     LevelEntity,
-                    OrbitCamera {
-                        target: player_entity,
-                        radius: Vec3::from(player_config.camera.offset).length(),
-                        offset: Vec3::from(player_config.camera.offset),
-                        zoom_speed: player_config.camera.zoom_speed,
-                        orbit_speed: player_config.camera.orbit_speed,
-                        min_radius: player_config.camera.min_radius,
-                        max_radius: player_config.camera.max_radius,
-                        pitch: 0.5, // Approx starting pitch
-                        yaw: 0.0,
-                        look_at_offset: Vec3::from(player_config.camera.look_at_offset),
+                    orbit,
+                )).id();
+                spawned.insert(PLAYER_CAMERA_ENTITY_ID.to_string(), camera_entity);
+            }
+
+            let mut defs = level.entities.clone();
+            let mut children = Vec::new();
+            // Like `SpawnPrefab`, an instance is skipped whole if any of its ids is taken.
+            let mut claimed: HashSet<String> = spawned.keys().chain(defs.iter().map(|def| &def.id)).cloned().collect();
+            for (index, instance) in level.prefabs.iter().enumerate() {
+                let Some(prefab) = asset_server
+                    .get_handle::<Prefab>(instance.prefab.clone())
+                    .and_then(|handle| prefabs.get(&handle))
+                else {
+                    warn!("Prefab '{}' is not loaded, skipping", instance.prefab);
+                    continue;
+                };
+                match prefab.instantiate(instance, &instance.id_or_default(index)) {
+                    Ok((root, root_children)) => {
+                        let mut ids = std::iter::once(&root).chain(&root_children).map(|def| &def.id);
+                        if let Some(taken) = ids.find(|id| claimed.contains(*id)) {
+                            warn!("Prefab '{}': id '{}' is already in use, not spawning it", instance.prefab, taken);
+                            continue;
+                        }
+                        claimed.extend(std::iter::once(&root).chain(&root_children).map(|def| def.id.clone()));
+                        children.extend(root_children.into_iter().map(|child| (root.id.clone(), child)));
+                        defs.push(root);
                     }
-                ));
-            } else {
-                // No player - spawn a default camera for UI/static scenes
-                println!("No player in scene, spawning default camera...");
+                    Err(e) => warn!("Prefab '{}': {}", instance.prefab, e),
+                }
+            }
+
+            spawn_entities(&mut commands, &asset_server, &defs, &preserved, &mut spawned);
+
+            let child_defs: Vec<EntityDef> = children.iter().map(|(_, child)| child.clone()).collect();
+            spawn_entities(&mut commands, &asset_server, &child_defs, &preserved, &mut spawned);
+            for (parent_id, child) in &children {
+                commands.entity(spawned[&child.id]).insert(ChildOf(spawned[parent_id]));
+            }
+
+            let has_camera = level.player.is_some()
+                || defs.iter().any(|def| {
+                    def.capabilities
+                        .iter()
+                        .any(|block| matches!(block, CapabilityBlock::OrbitCamera { .. }))
+                });
+            if !has_camera {
+                // No player or camera entity - spawn a default camera for UI/static scenes
+                println!("No camera in scene, spawning default camera...");
                 commands.spawn((
                     Camera3d::default(),
                     Transform::from_xyz(0.0, 5.0, 10.0).looking_at(Vec3::ZERO, Vec3::Y),
@@ -175,38 +326,268 @@ pub fn spawn_level(
                 ));
             }
             
-            next_state.set(AppState::InGame);
+            if !reloading {
+                next_state.set(AppState::InGame);
+            }
         }
     }
 }
 
+/// Path of the scene behind `level_handle`.
+pub fn active_scene(level_handle: Option<&LevelHandle>, asset_server: &AssetServer) -> Option<String> {
+    level_handle
+        .and_then(|handle| asset_server.get_path(&handle.0))
+        .map(|path| path.to_string())
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn message_interpreter_system(
-    mut ui_events: MessageReader<UiMessage>,
+    mut game_events: MessageReader<GameEvent>,
+    time: LogicTime,
+    logic_handle: Option<Res<GlobalLogicHandle>>,
+    logics: Res<Assets<GlobalLogic>>,
+    mut logic_state: ResMut<GlobalLogicState>,
+    level_handle: Option<Res<LevelHandle>>,
+    asset_server: Res<AssetServer>,
+    variables: Res<Variables>,
+    mut rng: ResMut<GameRng>,
     mut action_queue: ResMut<ActionQueue>,
+    mut scheduler: ResMut<ActionScheduler>,
 ) {
-    for event in ui_events.read() {
-        match event {
-            UiMessage::ButtonPressed(path) => {
-                action_queue.push(Action::LoadScene(path.clone()));
-            }
+    let events: Vec<EventId> = game_events.read().map(|event| event.id).collect();
+    for &event in &events {
+        scheduler.notify(event);
+    }
+
+    let Some(logic) = logic_handle.as_ref().and_then(|handle| logics.get(&handle.0)) else {
+        return;
+    };
+    logic_state.start(logic, &mut action_queue);
+
+    let scene = active_scene(level_handle.as_deref(), &asset_server);
+    let mut context = ConditionContext {
+        scene: scene.as_deref(),
+        variables: Some(&variables),
+        rng: Some(&mut rng),
+        ..default()
+    };
+    logic_state.tick(logic, time.delta_secs(), &mut context, &mut action_queue);
+    for &event in &events {
+        logic_state.handle_event(logic, event, &mut context, &mut action_queue);
+    }
+}
+
+/// Entities actions can target, looked up by `EntityId`.
+#[derive(SystemParam)]
+pub struct ActionTargets<'w, 's> {
+    existing: Query<'w, 's, ()>,
+    ids: Query<'w, 's, (Entity, &'static EntityId)>,
+    behaviors: Query<'w, 's, &'static mut Behavior>,
+    animations: Query<'w, 's, &'static mut AnimationController>,
+    characters: Query<'w, 's, &'static mut CharacterController>,
+    transforms: Query<'w, 's, (&'static mut Transform, &'static GlobalTransform)>,
+}
+
+impl ActionTargets<'_, '_> {
+    /// Resolves `"self"` to `source`, if it still exists, and anything else to
+    /// the entity with that id.
+    pub fn resolve(&self, target: &str, source: Option<Entity>) -> Option<Entity> {
+        let entity = if target == SELF_TARGET {
+            source.filter(|source| self.existing.contains(*source))
+        } else {
+            self.ids.iter().find(|(_, id)| id.0 == target).map(|(entity, _)| entity)
+        };
+        if entity.is_none() {
+            warn!("Action target '{}' does not exist", target);
+        }
+        entity
+    }
+
+    fn set_locomotion(&mut self, target: &str, source: Option<Entity>, locomotion: Locomotion) {
+        let Some(entity) = self.resolve(target, source) else { return; };
+        match self.characters.get_mut(entity) {
+            Ok(mut character) => character.locomotion = locomotion,
+            Err(_) => warn!("'{}' has no CharacterController to set {:?}", target, locomotion),
         }
     }
+
+    fn id(&self, entity: Entity) -> Option<String> {
+        self.ids.get(entity).ok().map(|(_, id)| id.0.clone())
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn action_executor_system(
     mut commands: Commands,
     mut action_queue: ResMut<ActionQueue>,
     asset_server: Res<AssetServer>,
+    mut time: ResMut<Time<Virtual>>,
     mut next_state: ResMut<NextState<AppState>>,
+    mut targets: ActionTargets,
+    mut behavior_messages: MessageWriter<BehaviorMessage>,
+    mut game_events: MessageWriter<GameEvent>,
+    mut registry: ResMut<EventRegistry>,
+    mut app_exit: MessageWriter<AppExit>,
+    mut variables: ResMut<Variables>,
+    mut pending_prefabs: ResMut<PendingPrefabs>,
+    mut scheduler: ResMut<ActionScheduler>,
+    mut rng: ResMut<GameRng>,
+    menus: Query<(Entity, &OpenMenu)>,
 ) {
-    while let Some(action) = action_queue.pop() {
+    let mut open_menus: Vec<String> = menus.iter().map(|(_, menu)| menu.path.clone()).collect();
+    while let Some(QueuedAction { action, source }) = action_queue.pop() {
         match action {
             Action::LoadScene(path) => {
                 println!("Executing Action::LoadScene: {}", path);
                 let handle = asset_server.load(path);
                 commands.insert_resource(LevelHandle(handle));
+                pending_prefabs.0.clear();
                 next_state.set(AppState::LoadingScene);
             }
+            Action::SetPaused(paused) => {
+                println!("Executing Action::SetPaused: {}", paused);
+                if paused {
+                    time.pause();
+                } else {
+                    time.unpause();
+                }
+            }
+            Action::Despawn(target) => {
+                if let Some(entity) = targets.resolve(&target, source) {
+                    commands.entity(entity).despawn();
+                }
+            }
+            Action::SendEvent(target, event) => {
+                if let Some(entity) = targets.resolve(&target, source) {
+                    let other = source.and_then(|source| targets.id(source));
+                    behavior_messages.write(BehaviorMessage { entity, event: EventId::of(&event), other });
+                }
+            }
+            Action::SetLocal(name, value) => {
+                match source.and_then(|source| targets.behaviors.get_mut(source).ok()) {
+                    Some(mut behavior) => behavior.set_local(name, value),
+                    None => warn!("SetLocal(\"{}\") can only run from a behavior", name),
+                }
+            }
+            Action::SetVar(name, value) => {
+                println!("Executing Action::SetVar: {} = {}", name, value);
+                variables.set(name, value);
+            }
+            Action::AddVar(name, amount) => {
+                if let Err(e) = variables.add(&name, &amount) {
+                    warn!("AddVar: {}", e);
+                }
+            }
+            Action::ToggleVar(name) => {
+                if let Err(e) = variables.toggle(&name) {
+                    warn!("ToggleVar: {}", e);
+                }
+            }
+            Action::OpenUi(path) => {
+                if open_menus.contains(&path) {
+                    continue;
+                }
+                open_menus.push(path.clone());
+                let handle = asset_server.load(path.clone());
+                commands.spawn((ui_root(), GlobalZIndex(1), OpenMenu::new(path, handle), LevelEntity));
+            }
+            Action::CloseUi(path) => {
+                open_menus.retain(|open| *open != path);
+                for (entity, menu) in &menus {
+                    if menu.path == path {
+                        commands.entity(entity).despawn();
+                    }
+                }
+            }
+            Action::PlayAnimation(target, clip) => {
+                let Some(entity) = targets.resolve(&target, source) else { continue; };
+                match targets.animations.get_mut(entity) {
+                    Ok(mut controller) => controller.current = clip,
+                    Err(_) => warn!("PlayAnimation: '{}' has no AnimationController", target),
+                }
+            }
+            Action::Kill(target) => targets.set_locomotion(&target, source, Locomotion::Dead),
+            Action::Revive(target) => targets.set_locomotion(&target, source, Locomotion::Standing),
+            Action::SetVelocity(target, velocity) => {
+                // The target may be despawned by an earlier action this frame.
+                if let Some(entity) = targets.resolve(&target, source) {
+                    commands.entity(entity).try_insert(Velocity(Vec3::from(velocity)));
+                }
+            }
+            Action::EmitEvent(event) => {
+                game_events.write(GameEvent::new(registry.intern(&event)));
+            }
+            Action::SpawnPrefab { prefab, id, at, position, rotation, scale, params } => {
+                let mut position = Vec3::from(position);
+                if let Some(at) = at {
+                    let Some(anchor) = targets.resolve(&at, source) else { continue; };
+                    if let Ok((_, global)) = targets.transforms.get(anchor) {
+                        position += global.translation();
+                    }
+                }
+                pending_prefabs.0.push(PendingPrefab {
+                    handle: asset_server.load(prefab.clone()),
+                    instance: PrefabInstance {
+                        prefab,
+                        id,
+                        position: position.into(),
+                        rotation,
+                        scale,
+                        params,
+                    },
+                });
+            }
+            Action::SetTransform { target, position, rotation, scale } => {
+                let Some(entity) = targets.resolve(&target, source) else { continue; };
+                let Ok((mut transform, _)) = targets.transforms.get_mut(entity) else { continue; };
+                if let Some(position) = position {
+                    transform.translation = Vec3::from(position);
+                }
+                if let Some(rotation) = rotation {
+                    transform.rotation = rotation.to_quat();
+                }
+                if let Some(scale) = scale {
+                    transform.scale = scale.to_vec3();
+                }
+            }
+            Action::PlaySound(path) => play_sound(&mut commands, &asset_server, path),
+            Action::Quit => {
+                println!("Executing Action::Quit");
+                app_exit.write(AppExit::Success);
+            }
+            Action::SaveGame(slot) => {
+                commands.queue(move |world: &mut World| match save_game(world, &slot) {
+                    Ok(save) => println!("Saved game to slot '{}' in {}", slot, save.scene),
+                    Err(e) => warn!("SaveGame(\"{}\"): {}", slot, e),
+                });
+            }
+            Action::LoadGame(slot) => {
+                println!("Executing Action::LoadGame: {}", slot);
+                commands.queue(move |world: &mut World| {
+                    if let Err(e) = load_game(world, &slot) {
+                        warn!("LoadGame(\"{}\"): {}", slot, e);
+                    }
+                });
+            }
+            Action::Sequence(_)
+            | Action::Delay(..)
+            | Action::Parallel(_)
+            | Action::WaitForEvent(_)
+            | Action::RandomChoice(_) => {
+                registry.intern_action(&action);
+                let released = scheduler.start(action, source, &mut rng);
+                action_queue.prepend(released);
+            }
         }
     }
 }
+
+#[cfg(feature = "audio")]
+fn play_sound(commands: &mut Commands, asset_server: &AssetServer, path: String) {
+    commands.spawn((AudioPlayer::new(asset_server.load(path)), PlaybackSettings::DESPAWN));
+}
+
+#[cfg(not(feature = "audio"))]
+fn play_sound(_commands: &mut Commands, _asset_server: &AssetServer, path: String) {
+    warn!("PlaySound(\"{}\") ignored: built without the `audio` feature", path);
+}
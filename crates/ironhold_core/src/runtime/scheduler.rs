@@ -0,0 +1,169 @@
+use bevy::prelude::*;
+use std::collections::VecDeque;
+use crate::runtime::actions::*;
+use crate::runtime::events::EventId;
+use crate::runtime::rng::GameRng;
+use crate::runtime::tick::LogicTime;
+
+/// A composite action in progress.
+#[derive(Debug)]
+enum Task {
+    /// A plain action, released to the `ActionQueue` when reached.
+    Run(Action),
+    Sequence(VecDeque<Action>, Option<Box<Task>>),
+    Delay(f32, Box<Task>),
+    Parallel(Vec<Task>),
+    /// Only events raised after the first step count.
+    WaitForEvent { event: EventId, armed: bool },
+}
+
+impl Task {
+    /// A `RandomChoice` is decided here, so the picked action runs as if it
+    /// had been written in its place.
+    fn new(action: Action, rng: &mut GameRng) -> Self {
+        match action {
+            Action::Sequence(actions) => Task::Sequence(actions.into(), None),
+            Action::Delay(seconds, action) => Task::Delay(seconds, Box::new(Task::new(*action, rng))),
+            Action::Parallel(actions) => {
+                Task::Parallel(actions.into_iter().map(|action| Task::new(action, rng)).collect())
+            }
+            Action::WaitForEvent(event) => Task::WaitForEvent { event: EventId::of(&event), armed: false },
+            Action::RandomChoice(actions) => match rng.choose(&actions) {
+                Some(action) => Task::new(action.clone(), rng),
+                None => Task::Parallel(Vec::new()),
+            },
+            action => Task::Run(action),
+        }
+    }
+
+    /// Advances the task by `delta` seconds, releasing plain actions into
+    /// `released` in order. Returns the unused part of `delta` once the task
+    /// has finished, so that what follows it does not lose time.
+    fn step(
+        &mut self,
+        delta: f32,
+        events: &[EventId],
+        rng: &mut GameRng,
+        released: &mut Vec<Action>,
+    ) -> Option<f32> {
+        match self {
+            Task::Run(action) => {
+                released.push(action.clone());
+                Some(delta)
+            }
+            Task::Sequence(actions, current) => {
+                let mut delta = delta;
+                loop {
+                    if let Some(task) = current {
+                        delta = task.step(delta, events, rng, released)?;
+                        *current = None;
+                    }
+                    match actions.pop_front() {
+                        Some(action) => *current = Some(Box::new(Task::new(action, rng))),
+                        None => return Some(delta),
+                    }
+                }
+            }
+            Task::Delay(remaining, task) => {
+                if *remaining > delta {
+                    *remaining -= delta;
+                    return None;
+                }
+                let leftover = delta - *remaining;
+                *remaining = 0.0;
+                task.step(leftover, events, rng, released)
+            }
+            Task::Parallel(tasks) => {
+                let mut leftover = delta;
+                tasks.retain_mut(|task| match task.step(delta, events, rng, released) {
+                    Some(unused) => {
+                        leftover = leftover.min(unused);
+                        false
+                    }
+                    None => true,
+                });
+                tasks.is_empty().then_some(leftover)
+            }
+            Task::WaitForEvent { event, armed } => {
+                if *armed && events.contains(event) {
+                    return Some(0.0);
+                }
+                *armed = true;
+                None
+            }
+        }
+    }
+}
+
+/// Runs `Sequence`, `Delay`, `Parallel`, `WaitForEvent` and `RandomChoice`
+/// actions across frames. Tasks are not tied to a scene and keep running
+/// through scene loads, except those started by an entity, which stop once it
+/// is despawned.
+///
+/// Tasks advance in the order they were started, and the plain actions they
+/// release run in that order.
+#[derive(Resource, Default)]
+pub struct ActionScheduler {
+    tasks: Vec<(Task, Option<Entity>)>,
+    /// Events seen since the last step, for `WaitForEvent`.
+    events: Vec<EventId>,
+}
+
+impl ActionScheduler {
+    /// Starts a composite action on behalf of `source`. Whatever it can do
+    /// right away is returned, to run before anything else queued.
+    pub fn start(&mut self, action: Action, source: Option<Entity>, rng: &mut GameRng) -> Vec<QueuedAction> {
+        let mut task = Task::new(action, rng);
+        let mut released = Vec::new();
+        if task.step(0.0, &[], rng, &mut released).is_none() {
+            self.tasks.push((task, source));
+        }
+        scoped(released, source)
+    }
+
+    /// Records an event for tasks waiting on it.
+    pub fn notify(&mut self, event: EventId) {
+        self.events.push(event);
+    }
+
+    /// Advances every task by `delta` seconds and returns the actions they release.
+    pub fn step(&mut self, delta: f32, rng: &mut GameRng) -> Vec<QueuedAction> {
+        let events = std::mem::take(&mut self.events);
+        let mut queued = Vec::new();
+        self.tasks.retain_mut(|(task, source)| {
+            let mut released = Vec::new();
+            let finished = task.step(delta, &events, rng, &mut released).is_some();
+            queued.extend(scoped(released, *source));
+            !finished
+        });
+        queued
+    }
+
+    /// Drops the tasks whose source entity no longer exists, so they cannot
+    /// run against a despawned entity.
+    pub fn cancel_orphaned(&mut self, exists: impl Fn(Entity) -> bool) {
+        self.tasks.retain(|(_, source)| source.is_none_or(&exists));
+    }
+
+    /// Number of composite actions still running.
+    pub fn running(&self) -> usize {
+        self.tasks.len()
+    }
+}
+
+fn scoped(actions: Vec<Action>, source: Option<Entity>) -> Vec<QueuedAction> {
+    actions.into_iter().map(|action| QueuedAction { action, source }).collect()
+}
+
+/// Advances running composite actions and queues what they release.
+pub fn action_scheduler_system(
+    time: LogicTime,
+    mut scheduler: ResMut<ActionScheduler>,
+    mut action_queue: ResMut<ActionQueue>,
+    mut rng: ResMut<GameRng>,
+    entities: Query<()>,
+) {
+    scheduler.cancel_orphaned(|entity| entities.contains(entity));
+    let released = scheduler.step(time.delta_secs(), &mut rng);
+    action_queue.0.extend(released);
+}
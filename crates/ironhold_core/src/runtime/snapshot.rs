@@ -0,0 +1,474 @@
+use bevy::ecs::component::Mutable;
+use bevy::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::Hasher;
+use crate::schema::*;
+use crate::schema::migration::{Migration, SchemaError, VersionedAsset};
+use crate::runtime::behavior::Behavior;
+use crate::runtime::logic::GlobalLogicState;
+use crate::runtime::replay::StateHasher;
+use crate::runtime::rng::GameRng;
+use crate::runtime::scene_manager::active_scene;
+use crate::runtime::tick::TransformInterpolation;
+use crate::runtime::variables::Variables;
+use crate::capabilities::animation::AnimationController;
+use crate::capabilities::camera::OrbitCamera;
+use crate::capabilities::player::{CharacterController, JumpPhase, Locomotion};
+use crate::capabilities::velocity::Velocity;
+
+/// A component whose state is part of a `Snapshot`. Register it with
+/// `App::register_snapshot_component`.
+pub trait SnapshotComponent: Component<Mutability = Mutable> + Sized {
+    /// Key of the component in snapshots. Saved games refer to it, so it
+    /// must not change.
+    const NAME: &'static str;
+    /// Snapshots keep states as `ron::Value`s, which lose enum variant
+    /// names, so states are made of structs, tuples and options.
+    type State: Serialize + DeserializeOwned;
+
+    fn save(&self) -> Self::State;
+    fn restore(&mut self, state: Self::State);
+
+    /// A new component for an entity that has lost it since the snapshot, if
+    /// one can be made from the state alone.
+    fn from_state(_state: Self::State) -> Option<Self> {
+        None
+    }
+}
+
+#[derive(Clone, Copy)]
+struct SnapshotType {
+    name: &'static str,
+    save: fn(&EntityRef) -> Option<Result<ron::Value, SnapshotError>>,
+    restore: fn(&mut EntityWorldMut, Option<ron::Value>) -> Result<(), SnapshotError>,
+}
+
+/// The component types captured in snapshots. `GamePlugin` registers
+/// `Transform`, `CharacterController`, `AnimationController`, `Behavior`
+/// and `Velocity`.
+#[derive(Resource, Default, Clone)]
+pub struct SnapshotRegistry {
+    types: Vec<SnapshotType>,
+}
+
+impl SnapshotRegistry {
+    pub fn register<C: SnapshotComponent>(&mut self) {
+        if self.types.iter().any(|ty| ty.name == C::NAME) {
+            return;
+        }
+        self.types.push(SnapshotType {
+            name: C::NAME,
+            save: save_component::<C>,
+            restore: restore_component::<C>,
+        });
+    }
+
+    /// Names of the registered components, in registration order.
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.types.iter().map(|ty| ty.name)
+    }
+}
+
+pub trait SnapshotAppExt {
+    fn register_snapshot_component<C: SnapshotComponent>(&mut self) -> &mut Self;
+}
+
+impl SnapshotAppExt for App {
+    fn register_snapshot_component<C: SnapshotComponent>(&mut self) -> &mut Self {
+        self.init_resource::<SnapshotRegistry>();
+        self.world_mut().resource_mut::<SnapshotRegistry>().register::<C>();
+        self
+    }
+}
+
+fn save_component<C: SnapshotComponent>(entity: &EntityRef) -> Option<Result<ron::Value, SnapshotError>> {
+    // Interpolated entities are saved as of the last tick, not as shown.
+    let state = match entity.get::<TransformInterpolation>() {
+        Some(interpolation) if C::NAME == Transform::NAME => {
+            return Some(encode::<Transform>(&interpolation.current.save()));
+        }
+        _ => entity.get::<C>()?.save(),
+    };
+    Some(encode::<C>(&state))
+}
+
+fn encode<C: SnapshotComponent>(state: &C::State) -> Result<ron::Value, SnapshotError> {
+    ron::to_string(state)
+        .and_then(|document| Ok(ron::from_str::<ron::Value>(&document)?))
+        .map_err(|e| SnapshotError::Encode(C::NAME, e.to_string()))
+}
+
+fn restore_component<C: SnapshotComponent>(
+    entity: &mut EntityWorldMut,
+    value: Option<ron::Value>,
+) -> Result<(), SnapshotError> {
+    let Some(value) = value else {
+        entity.remove::<C>();
+        return Ok(());
+    };
+    let state: C::State = value
+        .into_rust()
+        .map_err(|e| SnapshotError::Decode(C::NAME, e.to_string()))?;
+    match entity.get_mut::<C>() {
+        Some(mut component) => component.restore(state),
+        None => match C::from_state(state) {
+            Some(component) => {
+                entity.insert(component);
+            }
+            None => return Err(SnapshotError::MissingComponent(C::NAME)),
+        },
+    }
+    Ok(())
+}
+
+/// Why a snapshot could not be captured or restored.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SnapshotError {
+    /// The snapshot was taken in another level; load it first.
+    LevelMismatch { snapshot: Option<String>, active: Option<String> },
+    /// No entity has this `EntityId`.
+    MissingEntity(String),
+    /// An entity lacks a component that cannot be recreated from its state.
+    MissingComponent(&'static str),
+    /// The snapshot has a component type that is not registered.
+    UnknownComponent(String),
+    Encode(&'static str, String),
+    Decode(&'static str, String),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::LevelMismatch { snapshot, active } => write!(
+                f,
+                "snapshot is of level {}, but {} is active",
+                snapshot.as_deref().unwrap_or("<none>"),
+                active.as_deref().unwrap_or("<none>")
+            ),
+            SnapshotError::MissingEntity(id) => write!(f, "no entity with id \"{}\"", id),
+            SnapshotError::MissingComponent(name) => write!(f, "an entity has no {} to restore", name),
+            SnapshotError::UnknownComponent(name) => write!(f, "component \"{}\" is not registered", name),
+            SnapshotError::Encode(name, message) => write!(f, "cannot save {}: {}", name, message),
+            SnapshotError::Decode(name, message) => write!(f, "cannot restore {}: {}", name, message),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+/// The gameplay state of the world: the active level, the global logic, the
+/// RNG, the variables and the registered components of every entity with an
+/// `EntityId`. Maps are sorted, so equal states serialize, and hash, equally.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub schema_version: u32,
+    /// Path of the active level.
+    pub level: Option<String>,
+    /// Current state of the global logic and seconds spent in it.
+    pub logic: Option<(String, f32)>,
+    pub rng: RngSnapshot,
+    pub variables: BTreeMap<String, Value>,
+    /// Component states by `EntityId`, then by component name.
+    pub entities: BTreeMap<String, BTreeMap<String, ron::Value>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RngSnapshot {
+    pub seed: u64,
+    pub state: u64,
+}
+
+impl VersionedAsset for Snapshot {
+    const KIND: &'static str = "Snapshot";
+    const CURRENT_VERSION: u32 = 1;
+    const MIGRATIONS: &'static [Migration] = &[];
+
+    fn parse(document: &str) -> Result<Self, SchemaError> {
+        Ok(ron::from_str(document)?)
+    }
+}
+
+impl Snapshot {
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+    }
+
+    /// FNV-1a of the snapshot's compact RON, the same on every platform.
+    pub fn checksum(&self) -> u64 {
+        let mut hasher = StateHasher::new();
+        // Plain data always serializes.
+        let document = ron::to_string(self).unwrap_or_default();
+        hasher.write(document.as_bytes());
+        hasher.finish()
+    }
+}
+
+/// Captures the gameplay state of `world`.
+pub fn capture_snapshot(world: &mut World) -> Result<Snapshot, SnapshotError> {
+    let registry = world.get_resource::<SnapshotRegistry>().cloned().unwrap_or_default();
+    let mut entities = BTreeMap::new();
+    let mut query = world.query::<(EntityRef, &EntityId)>();
+    for (entity, id) in query.iter(world) {
+        let mut components = BTreeMap::new();
+        for ty in &registry.types {
+            if let Some(value) = (ty.save)(&entity) {
+                components.insert(ty.name.to_string(), value?);
+            }
+        }
+        entities.insert(id.0.clone(), components);
+    }
+
+    let logic = world.get_resource::<GlobalLogicState>().and_then(|logic| {
+        logic.current().map(|current| (current.to_string(), logic.elapsed()))
+    });
+    let rng = world.get_resource::<GameRng>().map_or(RngSnapshot { seed: 0, state: 0 }, |rng| RngSnapshot {
+        seed: rng.seed(),
+        state: rng.state(),
+    });
+    let variables = world.get_resource::<Variables>().map_or_else(BTreeMap::new, |variables| {
+        variables.iter().map(|(name, value)| (name.to_string(), value.clone())).collect()
+    });
+
+    Ok(Snapshot {
+        schema_version: Snapshot::CURRENT_VERSION,
+        level: active_level(world),
+        logic,
+        rng,
+        variables,
+        entities,
+    })
+}
+
+/// Puts `world` back in the state of `snapshot`, which must be of the
+/// active level. Entities with an `EntityId` the snapshot does not have are
+/// despawned; registered components it does not have are removed.
+pub fn restore_snapshot(world: &mut World, snapshot: &Snapshot) -> Result<(), SnapshotError> {
+    let active = active_level(world);
+    if active != snapshot.level {
+        return Err(SnapshotError::LevelMismatch { snapshot: snapshot.level.clone(), active });
+    }
+    let registry = world.get_resource::<SnapshotRegistry>().cloned().unwrap_or_default();
+    if let Some(name) = snapshot
+        .entities
+        .values()
+        .flat_map(|components| components.keys())
+        .find(|name| !registry.types.iter().any(|ty| ty.name == name.as_str()))
+    {
+        return Err(SnapshotError::UnknownComponent(name.clone()));
+    }
+
+    let mut query = world.query::<(Entity, &EntityId)>();
+    let ids: HashMap<String, Entity> = query.iter(world).map(|(entity, id)| (id.0.clone(), entity)).collect();
+    if let Some(id) = snapshot.entities.keys().find(|id| !ids.contains_key(*id)) {
+        return Err(SnapshotError::MissingEntity(id.clone()));
+    }
+
+    for (id, entity) in &ids {
+        let Some(components) = snapshot.entities.get(id) else {
+            // Children go with their parent, so the entity may be gone already.
+            if let Ok(entity) = world.get_entity_mut(*entity) {
+                entity.despawn();
+            }
+            continue;
+        };
+        let Ok(mut entity) = world.get_entity_mut(*entity) else {
+            return Err(SnapshotError::MissingEntity(id.clone()));
+        };
+        for ty in &registry.types {
+            (ty.restore)(&mut entity, components.get(ty.name).cloned())?;
+        }
+        let transform = entity.get::<Transform>().copied();
+        if let (Some(transform), Some(mut interpolation)) = (transform, entity.get_mut::<TransformInterpolation>()) {
+            interpolation.snap_to(transform);
+        }
+    }
+
+    if let Some(mut logic) = world.get_resource_mut::<GlobalLogicState>() {
+        let (current, elapsed) = snapshot.logic.clone().map_or((None, 0.0), |(current, elapsed)| (Some(current), elapsed));
+        logic.restore(current, elapsed);
+    }
+    if let Some(mut rng) = world.get_resource_mut::<GameRng>() {
+        rng.reseed(snapshot.rng.seed);
+        rng.set_state(snapshot.rng.state);
+    }
+    if let Some(mut variables) = world.get_resource_mut::<Variables>() {
+        *variables = Variables::default();
+        for (name, value) in &snapshot.variables {
+            variables.set(name.clone(), value.clone());
+        }
+    }
+    Ok(())
+}
+
+fn active_level(world: &World) -> Option<String> {
+    let asset_server = world.get_resource::<AssetServer>()?;
+    active_scene(world.get_resource::<LevelHandle>(), asset_server)
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TransformState {
+    pub translation: (f32, f32, f32),
+    pub rotation: (f32, f32, f32, f32),
+    pub scale: (f32, f32, f32),
+}
+
+impl SnapshotComponent for Transform {
+    const NAME: &'static str = "Transform";
+    type State = TransformState;
+
+    fn save(&self) -> TransformState {
+        TransformState {
+            translation: self.translation.into(),
+            rotation: self.rotation.into(),
+            scale: self.scale.into(),
+        }
+    }
+
+    fn restore(&mut self, state: TransformState) {
+        *self = Self::from_state(state).unwrap_or_default();
+    }
+
+    fn from_state(state: TransformState) -> Option<Self> {
+        Some(Transform {
+            translation: state.translation.into(),
+            rotation: Quat::from_xyzw(state.rotation.0, state.rotation.1, state.rotation.2, state.rotation.3),
+            scale: state.scale.into(),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CharacterState {
+    pub is_running: bool,
+    pub is_moving: bool,
+    #[serde(default)]
+    pub velocity: (f32, f32, f32),
+    #[serde(default)]
+    pub takeoff_velocity: (f32, f32, f32),
+    /// Seconds in the air, while airborne.
+    #[serde(default)]
+    pub airborne: Option<f32>,
+    /// Seconds since landing, until the landing animation is over.
+    #[serde(default)]
+    pub landed: Option<f32>,
+    /// `Locomotion::name`.
+    #[serde(default)]
+    pub locomotion: String,
+    /// Seconds into a roll and its direction.
+    #[serde(default)]
+    pub roll: Option<(f32, (f32, f32, f32))>,
+}
+
+impl SnapshotComponent for CharacterController {
+    const NAME: &'static str = "CharacterController";
+    type State = CharacterState;
+
+    fn save(&self) -> CharacterState {
+        CharacterState {
+            is_running: self.is_running,
+            is_moving: self.is_moving,
+            velocity: self.velocity.into(),
+            takeoff_velocity: self.takeoff_velocity.into(),
+            airborne: match self.jump {
+                JumpPhase::Airborne(time) => Some(time),
+                _ => None,
+            },
+            landed: match self.jump {
+                JumpPhase::Landed(time) => Some(time),
+                _ => None,
+            },
+            locomotion: self.locomotion.name().to_string(),
+            roll: match self.locomotion {
+                Locomotion::Rolling { time, direction } => Some((time, direction.into())),
+                _ => None,
+            },
+        }
+    }
+
+    fn restore(&mut self, state: CharacterState) {
+        self.is_running = state.is_running;
+        self.is_moving = state.is_moving;
+        self.velocity = state.velocity.into();
+        self.takeoff_velocity = state.takeoff_velocity.into();
+        self.jump = match (state.airborne, state.landed) {
+            (Some(time), _) => JumpPhase::Airborne(time),
+            (None, Some(time)) => JumpPhase::Landed(time),
+            (None, None) => JumpPhase::Grounded,
+        };
+        let roll = state.roll.map(|(time, direction)| (time, direction.into()));
+        self.locomotion = Locomotion::from_name(&state.locomotion, roll).unwrap_or_default();
+    }
+}
+
+impl SnapshotComponent for AnimationController {
+    const NAME: &'static str = "AnimationController";
+    /// The clip playing.
+    type State = String;
+
+    fn save(&self) -> String {
+        self.current.clone()
+    }
+
+    fn restore(&mut self, current: String) {
+        self.current = current;
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BehaviorState {
+    pub current: Option<String>,
+    pub locals: BTreeMap<String, Value>,
+    pub elapsed: f32,
+}
+
+impl SnapshotComponent for Behavior {
+    const NAME: &'static str = "Behavior";
+    type State = BehaviorState;
+
+    fn save(&self) -> BehaviorState {
+        BehaviorState {
+            current: self.current().map(str::to_string),
+            locals: self.locals().iter().map(|(name, value)| (name.clone(), value.clone())).collect(),
+            elapsed: self.elapsed(),
+        }
+    }
+
+    fn restore(&mut self, state: BehaviorState) {
+        Behavior::restore(self, state.current, state.locals.into_iter().collect(), state.elapsed);
+    }
+}
+
+impl SnapshotComponent for Velocity {
+    const NAME: &'static str = "Velocity";
+    type State = (f32, f32, f32);
+
+    fn save(&self) -> (f32, f32, f32) {
+        self.0.into()
+    }
+
+    fn restore(&mut self, state: (f32, f32, f32)) {
+        self.0 = state.into();
+    }
+
+    fn from_state(state: (f32, f32, f32)) -> Option<Self> {
+        Some(Velocity(state.into()))
+    }
+}
+
+impl SnapshotComponent for OrbitCamera {
+    const NAME: &'static str = "OrbitCamera";
+    /// Yaw and pitch in radians.
+    type State = (f32, f32);
+
+    fn save(&self) -> (f32, f32) {
+        (self.yaw, self.pitch)
+    }
+
+    fn restore(&mut self, (yaw, pitch): (f32, f32)) {
+        self.yaw = yaw;
+        self.pitch = pitch;
+    }
+}
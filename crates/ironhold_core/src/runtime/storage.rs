@@ -0,0 +1,175 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Why a `PersistentStorage` read or write failed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StorageError {
+    /// The platform has nowhere to keep data, e.g. no config directory or
+    /// `localStorage` is disabled.
+    Unavailable,
+    Io(String),
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::Unavailable => write!(f, "no persistent storage available"),
+            StorageError::Io(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+/// Small named documents kept between runs, such as user settings.
+pub trait PersistentStorage: Send + Sync {
+    /// The document stored under `key`, or `None` if nothing was written yet.
+    fn read(&self, key: &str) -> Result<Option<String>, StorageError>;
+    fn write(&mut self, key: &str, contents: &str) -> Result<(), StorageError>;
+}
+
+/// The storage the game persists to. `GamePlugin` starts with a
+/// `MemoryStorage`; the runners install the platform's storage.
+#[derive(Resource)]
+pub struct StorageBackend(pub Box<dyn PersistentStorage>);
+
+impl Default for StorageBackend {
+    fn default() -> Self {
+        Self(Box::new(MemoryStorage::default()))
+    }
+}
+
+impl StorageBackend {
+    /// `FileStorage` under the platform config directory on native,
+    /// `LocalStorage` on web.
+    pub fn platform() -> Self {
+        #[cfg(target_arch = "wasm32")]
+        {
+            Self(Box::new(LocalStorage::new("ironhold")))
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            match FileStorage::config_dir("ironhold") {
+                Some(storage) => Self(Box::new(storage)),
+                None => {
+                    warn!("No config directory found, settings will not be saved");
+                    Self::default()
+                }
+            }
+        }
+    }
+}
+
+/// Keeps documents in memory only; used by tests and headless runs.
+#[derive(Default, Debug, Clone)]
+pub struct MemoryStorage {
+    pub documents: HashMap<String, String>,
+}
+
+impl PersistentStorage for MemoryStorage {
+    fn read(&self, key: &str) -> Result<Option<String>, StorageError> {
+        Ok(self.documents.get(key).cloned())
+    }
+
+    fn write(&mut self, key: &str, contents: &str) -> Result<(), StorageError> {
+        self.documents.insert(key.to_string(), contents.to_string());
+        Ok(())
+    }
+}
+
+/// Stores each document as `<root>/<key>.ron`.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone)]
+pub struct FileStorage {
+    pub root: std::path::PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileStorage {
+    pub fn new(root: impl Into<std::path::PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// A folder named `app` in the platform config directory: `%APPDATA%` on
+    /// Windows, `~/Library/Application Support` on macOS and
+    /// `$XDG_CONFIG_HOME` (or `~/.config`) elsewhere.
+    pub fn config_dir(app: &str) -> Option<Self> {
+        use std::env::var_os;
+        use std::path::PathBuf;
+
+        let base = if cfg!(target_os = "windows") {
+            var_os("APPDATA").map(PathBuf::from)
+        } else if cfg!(target_os = "macos") {
+            var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+        } else {
+            var_os("XDG_CONFIG_HOME")
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from)
+                .or_else(|| var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        };
+        base.map(|base| Self::new(base.join(app)))
+    }
+
+    fn path(&self, key: &str) -> std::path::PathBuf {
+        self.root.join(format!("{}.ron", key))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl PersistentStorage for FileStorage {
+    fn read(&self, key: &str) -> Result<Option<String>, StorageError> {
+        let path = self.path(key);
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(StorageError::Io(format!("{}: {}", path.display(), e))),
+        }
+    }
+
+    fn write(&mut self, key: &str, contents: &str) -> Result<(), StorageError> {
+        let path = self.path(key);
+        std::fs::create_dir_all(&self.root)
+            .and_then(|_| std::fs::write(&path, contents))
+            .map_err(|e| StorageError::Io(format!("{}: {}", path.display(), e)))
+    }
+}
+
+/// Stores each document in the browser's `localStorage` as `<prefix>.<key>`.
+#[cfg(target_arch = "wasm32")]
+#[derive(Debug, Clone)]
+pub struct LocalStorage {
+    pub prefix: String,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl LocalStorage {
+    pub fn new(prefix: &str) -> Self {
+        Self { prefix: prefix.to_string() }
+    }
+
+    fn storage() -> Result<web_sys::Storage, StorageError> {
+        web_sys::window()
+            .and_then(|window| window.local_storage().ok().flatten())
+            .ok_or(StorageError::Unavailable)
+    }
+
+    fn item(&self, key: &str) -> String {
+        format!("{}.{}", self.prefix, key)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl PersistentStorage for LocalStorage {
+    fn read(&self, key: &str) -> Result<Option<String>, StorageError> {
+        Self::storage()?
+            .get_item(&self.item(key))
+            .map_err(|e| StorageError::Io(format!("localStorage read failed: {:?}", e)))
+    }
+
+    fn write(&mut self, key: &str, contents: &str) -> Result<(), StorageError> {
+        Self::storage()?
+            .set_item(&self.item(key), contents)
+            .map_err(|e| StorageError::Io(format!("localStorage write failed: {:?}", e)))
+    }
+}
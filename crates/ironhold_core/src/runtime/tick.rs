@@ -0,0 +1,116 @@
+use bevy::ecs::schedule::ScheduleLabel;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use crate::schema::AppState;
+
+/// Gameplay ticks per second when `ProjectConfig::tick_rate` is not set.
+pub const DEFAULT_TICK_RATE: f64 = 60.0;
+
+/// Logic interpretation, behaviors and action execution. Runs once per
+/// gameplay tick in game, and every frame while time is paused or the game is
+/// not running, so the logic can still react to menus and unpause the game.
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GameLogic;
+
+pub fn run_game_logic(world: &mut World) {
+    world.run_schedule(GameLogic);
+}
+
+/// The time the logic's timers advance by in this run of `GameLogic`: the
+/// tick's delta in game, and none while scenes load or an error is shown.
+#[derive(SystemParam)]
+pub struct LogicTime<'w> {
+    time: Res<'w, Time>,
+    state: Res<'w, State<AppState>>,
+}
+
+impl LogicTime<'_> {
+    pub fn delta_secs(&self) -> f32 {
+        if *self.state.get() == AppState::InGame {
+            self.time.delta_secs()
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Interpolates the `Transform` of an entity moved by gameplay systems in
+/// `FixedUpdate`. Outside the fixed loop its `Transform` shows a blend of the
+/// last two ticks, so it moves smoothly at any frame rate; gameplay systems
+/// always see the simulated transform.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq)]
+pub struct TransformInterpolation {
+    /// The transform at the start of the last tick.
+    pub previous: Transform,
+    /// The transform at the end of the last tick.
+    pub current: Transform,
+    /// What `interpolate_transforms_system` last showed, to detect entities
+    /// moved outside the simulation.
+    rendered: Option<Transform>,
+}
+
+impl TransformInterpolation {
+    /// Jumps to `transform` without blending from where the entity was, e.g.
+    /// after restoring a snapshot.
+    pub fn snap_to(&mut self, transform: Transform) {
+        *self = Self { previous: transform, current: transform, rendered: None };
+    }
+
+    /// The blend of `previous` and `current` a fraction `t` of the way
+    /// through the next tick.
+    pub fn blend(&self, t: f32) -> Transform {
+        Transform {
+            translation: self.previous.translation.lerp(self.current.translation, t),
+            rotation: self.previous.rotation.slerp(self.current.rotation, t),
+            scale: self.previous.scale.lerp(self.current.scale, t),
+        }
+    }
+}
+
+/// Puts the simulated transforms back before the fixed loop runs. An entity
+/// whose transform changed since it was last shown, e.g. one just spawned,
+/// takes the new transform as its simulated one.
+pub fn restore_transforms_system(mut query: Query<(&mut Transform, &mut TransformInterpolation)>) {
+    for (mut transform, mut interpolation) in &mut query {
+        if interpolation.rendered == Some(*transform) {
+            let current = interpolation.current;
+            transform.set_if_neq(current);
+        } else {
+            interpolation.previous = *transform;
+            interpolation.current = *transform;
+        }
+    }
+}
+
+/// Drops time left over from before the game was entered, so the first tick
+/// lands at the same point of a recorded and a replayed run.
+pub fn reset_tick_phase_system(mut time: ResMut<Time<Fixed>>) {
+    let overstep = time.overstep();
+    time.discard_overstep(overstep);
+}
+
+pub fn begin_tick_transforms_system(mut query: Query<(&Transform, &mut TransformInterpolation)>) {
+    for (transform, mut interpolation) in &mut query {
+        interpolation.previous = *transform;
+    }
+}
+
+pub fn end_tick_transforms_system(mut query: Query<(&Transform, &mut TransformInterpolation)>) {
+    for (transform, mut interpolation) in &mut query {
+        interpolation.current = *transform;
+    }
+}
+
+/// Shows each interpolated entity between its last two ticks, by how far
+/// time has got towards the next tick.
+pub fn interpolate_transforms_system(
+    time: Res<Time<Fixed>>,
+    mut query: Query<(&mut Transform, &mut TransformInterpolation)>,
+) {
+    let t = time.overstep_fraction();
+    for (mut transform, mut interpolation) in &mut query {
+        let blended = interpolation.blend(t);
+        transform.set_if_neq(blended);
+        interpolation.rendered = Some(blended);
+    }
+}
@@ -0,0 +1,98 @@
+use bevy::prelude::*;
+use crate::schema::*;
+use crate::capabilities::rebind::RebindButton;
+
+/// A menu opened with `Action::OpenUi`. Its elements are spawned once the
+/// `UiMenu` asset has loaded.
+#[derive(Component, Debug, Clone)]
+pub struct OpenMenu {
+    pub path: String,
+    handle: Handle<UiMenu>,
+    built: bool,
+}
+
+impl OpenMenu {
+    pub fn new(path: String, handle: Handle<UiMenu>) -> Self {
+        Self {
+            path,
+            handle,
+            built: false,
+        }
+    }
+}
+
+/// Full-screen node the UI elements of a scene or menu are laid out in.
+pub fn ui_root() -> Node {
+    Node {
+        width: Val::Percent(100.0),
+        height: Val::Percent(100.0),
+        align_items: AlignItems::Center,
+        justify_content: JustifyContent::Center,
+        ..default()
+    }
+}
+
+pub fn spawn_ui_elements(parent: &mut ChildSpawnerCommands, elements: &[UiElement]) {
+    for element in elements {
+        match element {
+            UiElement::Button { text, action, visible } => {
+                let mut button = spawn_button(parent, text, 150.0);
+                button.insert(action.clone());
+                if let Some(condition) = visible {
+                    button.insert(UiVisibility(condition.clone()));
+                }
+            }
+            UiElement::Rebind { input, text } => {
+                let text = text.clone().unwrap_or_else(|| input.clone());
+                // The label is filled in by `rebind_label_system`.
+                spawn_button(parent, "", 300.0).insert(RebindButton::new(input.clone(), text));
+            }
+        }
+    }
+}
+
+fn spawn_button<'a>(parent: &'a mut ChildSpawnerCommands, text: &str, width: f32) -> EntityCommands<'a> {
+    let mut button = parent.spawn((
+        Button,
+        Node {
+            width: Val::Px(width),
+            height: Val::Px(65.0),
+            border: UiRect::all(Val::Px(5.0)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        BorderColor::from(Color::BLACK),
+        BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
+    ));
+    button.with_children(|parent| {
+        parent.spawn((
+            Text::new(text),
+            TextFont {
+                font_size: 33.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.9, 0.9, 0.9)),
+        ));
+    });
+    button
+}
+
+/// Spawns the elements of opened menus whose asset has loaded.
+pub fn ui_menu_system(
+    mut commands: Commands,
+    mut menus: Query<(Entity, &mut OpenMenu)>,
+    assets: Res<Assets<UiMenu>>,
+) {
+    for (entity, mut menu) in &mut menus {
+        if menu.built {
+            continue;
+        }
+        let Some(asset) = assets.get(&menu.handle) else {
+            continue;
+        };
+        println!("Opening menu: {}", menu.path);
+        commands.entity(entity).with_children(|parent| spawn_ui_elements(parent, &asset.elements));
+        menu.built = true;
+    }
+}
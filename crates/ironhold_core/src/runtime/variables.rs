@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+use crate::schema::value::Value;
+
+/// Project-wide game state: scores, collected keys, menu settings.
+///
+/// Declared with defaults in `ProjectConfig::variables` and
+/// `GameLevel::variables`, changed by `SetVar`, `AddVar` and `ToggleVar`.
+#[derive(Resource, Default, Debug, Clone, PartialEq)]
+pub struct Variables {
+    values: BTreeMap<String, Value>,
+}
+
+impl Variables {
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.values.get(name)
+    }
+
+    pub fn set(&mut self, name: impl Into<String>, value: Value) {
+        self.values.insert(name.into(), value);
+    }
+
+    /// Sets each variable that is not defined yet, keeping existing values.
+    pub fn declare(&mut self, defaults: &HashMap<String, Value>) {
+        for (name, value) in defaults {
+            self.values.entry(name.clone()).or_insert_with(|| value.clone());
+        }
+    }
+
+    /// Adds `amount` to a number or vector variable. An undefined variable
+    /// is set to `amount`.
+    pub fn add(&mut self, name: &str, amount: &Value) -> Result<(), String> {
+        let value = match self.values.get(name) {
+            Some(current) => current.try_add(amount).ok_or_else(|| {
+                format!("cannot add {} to variable \"{}\" ({})", amount, name, current)
+            })?,
+            None => amount.clone(),
+        };
+        self.values.insert(name.to_string(), value);
+        Ok(())
+    }
+
+    /// Flips a bool variable. An undefined variable becomes `true`.
+    pub fn toggle(&mut self, name: &str) -> Result<(), String> {
+        let value = match self.values.get(name) {
+            Some(Value::Bool(b)) => !b,
+            Some(other) => return Err(format!("variable \"{}\" is not a bool ({})", name, other)),
+            None => true,
+        };
+        self.values.insert(name.to_string(), Value::Bool(value));
+        Ok(())
+    }
+
+    /// All variables, sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.values.iter().map(|(name, value)| (name.as_str(), value))
+    }
+}
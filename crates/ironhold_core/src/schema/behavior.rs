@@ -0,0 +1,59 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+use crate::schema::logic::{LogicState, Transition};
+use crate::schema::value::Value;
+use crate::schema::migration::{unchanged, Migration, SchemaError, VersionedAsset};
+
+/// Fired on a trigger collider when a character enters it.
+pub const TRIGGER_ENTER_EVENT: &str = "trigger.enter";
+/// Fired on a trigger collider when a character leaves it.
+pub const TRIGGER_EXIT_EVENT: &str = "trigger.exit";
+/// Fired on the nearest behavior in range when a character presses its `interact` key.
+pub const INTERACT_EVENT: &str = "interact";
+/// Fired once when a state's `timer` runs out.
+pub const TIMER_EVENT: &str = "timer";
+/// Fired each time the entity's current animation clip completes.
+pub const ANIMATION_FINISHED_EVENT: &str = "animation.finished";
+
+/// A state machine run by each entity with a `Behavior` capability block.
+/// Every entity keeps its own current state and locals.
+///
+/// ```ron
+/// (
+///     schema_version: 1,
+///     initial_state: "waiting",
+///     locals: { "collected": false },
+///     states: [(name: "waiting"), (name: "taken", on_enter: [Despawn("self")])],
+///     transitions: [(from: "waiting", event: "trigger.enter", to: Some("taken"))],
+/// )
+/// ```
+#[derive(Deserialize, Asset, TypePath, Debug, Clone)]
+pub struct BehaviorMachine {
+    pub schema_version: u32,
+    pub initial_state: String,
+    /// Local variable defaults, overridable per entity.
+    #[serde(default)]
+    pub locals: HashMap<String, Value>,
+    pub states: Vec<LogicState>,
+    #[serde(default)]
+    pub transitions: Vec<Transition>,
+}
+
+impl VersionedAsset for BehaviorMachine {
+    const KIND: &'static str = "BehaviorMachine";
+    const CURRENT_VERSION: u32 = 1;
+    const MIGRATIONS: &'static [Migration] = &[
+        Migration { from: 0, apply: unchanged },
+    ];
+
+    fn parse(document: &str) -> Result<Self, SchemaError> {
+        Ok(ron::from_str(document)?)
+    }
+}
+
+impl BehaviorMachine {
+    pub fn state(&self, name: &str) -> Option<&LogicState> {
+        self.states.iter().find(|state| state.name == name)
+    }
+}
@@ -0,0 +1,241 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use crate::schema::player::{AnimationMap, CameraConfig, InputMap, MovementConfig};
+use crate::schema::transform::{to_transform, Rotation, Scale};
+use crate::schema::value::Value;
+use crate::schema::migration::{is_ident_byte, skip_char, skip_comment, skip_raw_string, skip_string, skip_whitespace};
+use bevy::prelude::Transform;
+
+/// A generic data-defined entity: an optional model plus capability blocks.
+#[derive(Deserialize, Debug, Clone)]
+pub struct EntityDef {
+    pub id: String,
+    /// Model to spawn, e.g. `"models/npc.glb#Scene0"`.
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub position: (f32, f32, f32),
+    #[serde(default)]
+    pub rotation: Rotation,
+    #[serde(default)]
+    pub scale: Scale,
+    #[serde(default)]
+    pub capabilities: Vec<CapabilityBlock>,
+}
+
+impl EntityDef {
+    pub fn transform(&self) -> Transform {
+        to_transform(self.position, self.rotation, self.scale)
+    }
+}
+
+/// One capability attached to an `EntityDef`. Each block is turned into its
+/// matching component when the entity is spawned.
+#[derive(Deserialize, Debug, Clone)]
+pub enum CapabilityBlock {
+    /// Character movement. Only entities with `inputs` read the player's
+    /// input; their keys are ignored when the project has an `InputProfile`.
+    CharacterController {
+        /// Speeds, ramps, modes and jumping, as in the player's `movement` block.
+        #[serde(default)]
+        movement: MovementConfig,
+        #[serde(default)]
+        inputs: Option<InputMap>,
+    },
+    /// Makes this entity a camera orbiting the entity with id `target`.
+    OrbitCamera {
+        target: String,
+        camera: CameraConfig,
+    },
+    /// Plays clips from this entity's model. Requires `model`.
+    AnimationController {
+        animations: AnimationMap,
+    },
+    PointLight {
+        #[serde(default = "default_light_color")]
+        color: (f32, f32, f32),
+        #[serde(default = "default_point_intensity")]
+        intensity: f32,
+        #[serde(default = "default_light_range")]
+        range: f32,
+        #[serde(default)]
+        shadows: bool,
+    },
+    SpotLight {
+        #[serde(default = "default_light_color")]
+        color: (f32, f32, f32),
+        #[serde(default = "default_point_intensity")]
+        intensity: f32,
+        #[serde(default = "default_light_range")]
+        range: f32,
+        /// Degrees.
+        #[serde(default)]
+        inner_angle: f32,
+        /// Degrees.
+        #[serde(default = "default_spot_outer_angle")]
+        outer_angle: f32,
+        #[serde(default)]
+        shadows: bool,
+    },
+    DirectionalLight {
+        #[serde(default = "default_light_color")]
+        color: (f32, f32, f32),
+        #[serde(default = "default_illuminance")]
+        illuminance: f32,
+        #[serde(default)]
+        shadows: bool,
+    },
+    Collider {
+        shape: ColliderShape,
+        /// Triggers report overlaps but do not block movement.
+        #[serde(default)]
+        trigger: bool,
+    },
+    /// Runs the `BehaviorMachine` at path `machine` on this entity.
+    Behavior {
+        machine: String,
+        /// Overrides the machine's local variable defaults.
+        #[serde(default)]
+        locals: HashMap<String, Value>,
+        /// How close a character must be to interact with this entity.
+        #[serde(default = "default_interact_radius")]
+        interact_radius: f32,
+    },
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum ColliderShape {
+    /// Half extents.
+    Box(f32, f32, f32),
+    Sphere(f32),
+    /// Radius and half height of the cylindrical part, along Y.
+    Capsule(f32, f32),
+}
+
+/// Migration step of the assets holding capability blocks from schema
+/// version 1 to 2: the fields of each `CharacterController(...)` other than
+/// `inputs` move into its `movement` block. Comments inside the block are
+/// dropped; lines after it keep their numbers.
+pub fn nest_character_movement(source: &str) -> Result<String, String> {
+    const NAME: &str = "CharacterController";
+    let bytes = source.as_bytes();
+    let mut out = String::with_capacity(source.len() + 32);
+    let mut copied = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => i = skip_string(bytes, i),
+            b'\'' => i = skip_char(bytes, i),
+            b'/' if matches!(bytes.get(i + 1), Some(b'/' | b'*')) => i = skip_comment(bytes, i),
+            c if is_ident_byte(c) => {
+                let start = i;
+                while i < bytes.len() && is_ident_byte(bytes[i]) {
+                    i += 1;
+                }
+                let ident = &source[start..i];
+                if ident == "r" && matches!(bytes.get(i), Some(b'"' | b'#')) {
+                    i = skip_raw_string(bytes, i - 1);
+                    continue;
+                }
+                let open = skip_whitespace(bytes, i);
+                if ident != NAME || bytes.get(open) != Some(&b'(') {
+                    continue;
+                }
+                let (close, fields) = split_fields(source, open)?;
+                let name = |field: &String| field.split(':').next().unwrap_or_default().trim().to_string();
+                if !fields.iter().any(|field| name(field) == "movement") {
+                    let (inputs, movement): (Vec<String>, Vec<String>) =
+                        fields.into_iter().partition(|field| name(field) == "inputs");
+                    let mut nested = Vec::new();
+                    if !movement.is_empty() {
+                        nested.push(format!("movement: ({})", movement.join(", ")));
+                    }
+                    nested.extend(inputs);
+                    let nested = nested.join(", ");
+                    // Keep the lines after the block where they were, for error positions.
+                    let lines = source[open..close].matches('\n').count().saturating_sub(nested.matches('\n').count());
+                    out.push_str(&source[copied..=open]);
+                    out.push_str(&nested);
+                    out.push_str(&"\n".repeat(lines));
+                    copied = close;
+                }
+                i = close;
+            }
+            _ => i += 1,
+        }
+    }
+    out.push_str(&source[copied..]);
+    Ok(out)
+}
+
+/// The fields of the parenthesized block opening at `open`, without
+/// comments, and the byte offset of its closing parenthesis.
+fn split_fields(source: &str, open: usize) -> Result<(usize, Vec<String>), String> {
+    let bytes = source.as_bytes();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut depth = 0;
+    let mut i = open + 1;
+    while i < bytes.len() {
+        let end = match bytes[i] {
+            b'"' => skip_string(bytes, i),
+            b'\'' => skip_char(bytes, i),
+            b'r' if matches!(bytes.get(i + 1), Some(b'"' | b'#')) && !is_ident_byte(bytes[i - 1]) => {
+                skip_raw_string(bytes, i)
+            }
+            b'/' if matches!(bytes.get(i + 1), Some(b'/' | b'*')) => {
+                i = skip_comment(bytes, i);
+                field.push(' ');
+                continue;
+            }
+            b'(' | b'[' | b'{' => {
+                depth += 1;
+                i + 1
+            }
+            b')' if depth == 0 => {
+                fields.push(field);
+                fields.retain(|field| !field.trim().is_empty());
+                let fields = fields.into_iter().map(|field| field.trim().to_string()).collect();
+                return Ok((i, fields));
+            }
+            b')' | b']' | b'}' => {
+                depth -= 1;
+                i + 1
+            }
+            b',' if depth == 0 => {
+                fields.push(std::mem::take(&mut field));
+                i += 1;
+                continue;
+            }
+            _ => i + source[i..].chars().next().map_or(1, char::len_utf8),
+        };
+        let end = end.min(bytes.len());
+        field.push_str(&source[i..end]);
+        i = end;
+    }
+    Err(format!("unclosed block at byte {}", open))
+}
+
+fn default_light_color() -> (f32, f32, f32) {
+    (1.0, 1.0, 1.0)
+}
+
+fn default_point_intensity() -> f32 {
+    1_000_000.0
+}
+
+fn default_light_range() -> f32 {
+    20.0
+}
+
+fn default_spot_outer_angle() -> f32 {
+    45.0
+}
+
+fn default_interact_radius() -> f32 {
+    1.5
+}
+
+fn default_illuminance() -> f32 {
+    10_000.0
+}
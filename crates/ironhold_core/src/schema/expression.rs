@@ -0,0 +1,384 @@
+use serde::{Deserialize, Deserializer};
+use std::cmp::Ordering;
+use std::fmt;
+use crate::schema::value::Value;
+
+/// A boolean expression over variables, written as a string and parsed when
+/// the asset loads:
+///
+/// ```text
+/// score >= 10 && !has_key
+/// (difficulty == "hard" or lives < 2) and not self.opened
+/// ```
+///
+/// Operands are literals (`true`, `3`, `2.5`, `"text"`, `vec3(0, 1, 0)`),
+/// `random()` (a number in `[0, 1)` from the game's seeded RNG) or variable
+/// names; names may contain dots, and `self.<name>` reads a behavior local.
+/// A bare operand is true unless it is `false`, zero, the empty string or
+/// undefined. Comparisons involving an undefined variable are false.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    source: String,
+    root: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Literal(Value),
+    Variable(String),
+    Random,
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(Box<Expr>, CompareOp, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// Why an expression failed to parse, with the 1-based column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpressionError {
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ExpressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at column {}", self.message, self.column)
+    }
+}
+
+impl std::error::Error for ExpressionError {}
+
+/// What an expression reads while it is evaluated.
+pub trait ExpressionContext {
+    /// Value of the variable `name`, or `None` if it is undefined.
+    fn lookup(&self, name: &str) -> Option<Value>;
+    /// The next number in `[0, 1)` for `random()`, or `None` if there is no
+    /// random source.
+    fn random(&mut self) -> Option<f64>;
+}
+
+impl Expression {
+    pub fn parse(source: &str) -> Result<Self, ExpressionError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens: &tokens, pos: 0, end: source.len() };
+        let root = parser.or()?;
+        if let Some((offset, token)) = parser.tokens.get(parser.pos) {
+            return Err(error(*offset, format!("unexpected {}", token)));
+        }
+        Ok(Expression {
+            source: source.to_string(),
+            root,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Names of the variables the expression reads, in order of appearance.
+    pub fn variables(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        self.root.collect_variables(&mut names);
+        names
+    }
+
+    /// Evaluates the expression, reading variables from `context`.
+    pub fn evaluate(&self, context: &mut dyn ExpressionContext) -> bool {
+        self.root.truthy(context)
+    }
+}
+
+impl<'de> Deserialize<'de> for Expression {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let source = String::deserialize(deserializer)?;
+        Expression::parse(&source)
+            .map_err(|e| serde::de::Error::custom(format!("invalid expression \"{}\": {}", source, e)))
+    }
+}
+
+impl Expr {
+    fn truthy(&self, context: &mut dyn ExpressionContext) -> bool {
+        self.value(context).is_some_and(|value| value.is_truthy())
+    }
+
+    fn value(&self, context: &mut dyn ExpressionContext) -> Option<Value> {
+        Some(match self {
+            Expr::Literal(value) => value.clone(),
+            Expr::Variable(name) => return context.lookup(name),
+            Expr::Random => Value::Float(context.random()?),
+            Expr::Not(inner) => Value::Bool(!inner.truthy(context)),
+            Expr::And(a, b) => Value::Bool(a.truthy(context) && b.truthy(context)),
+            Expr::Or(a, b) => Value::Bool(a.truthy(context) || b.truthy(context)),
+            Expr::Compare(a, op, b) => {
+                let result = match (a.value(context), b.value(context)) {
+                    (Some(a), Some(b)) => {
+                        let ordering = a.partial_cmp_value(&b);
+                        match op {
+                            CompareOp::Eq => ordering == Some(Ordering::Equal),
+                            CompareOp::Ne => ordering != Some(Ordering::Equal),
+                            CompareOp::Lt => ordering == Some(Ordering::Less),
+                            CompareOp::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+                            CompareOp::Gt => ordering == Some(Ordering::Greater),
+                            CompareOp::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+                        }
+                    }
+                    _ => false,
+                };
+                Value::Bool(result)
+            }
+        })
+    }
+
+    fn collect_variables<'a>(&'a self, names: &mut Vec<&'a str>) {
+        match self {
+            Expr::Literal(_) | Expr::Random => {}
+            Expr::Variable(name) => names.push(name),
+            Expr::Not(inner) => inner.collect_variables(names),
+            Expr::And(a, b) | Expr::Or(a, b) | Expr::Compare(a, _, b) => {
+                a.collect_variables(names);
+                b.collect_variables(names);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(Value),
+    Str(String),
+    Ident(String),
+    Symbol(&'static str),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Number(value) => write!(f, "number {}", value),
+            Token::Str(s) => write!(f, "string {:?}", s),
+            Token::Ident(name) => write!(f, "`{}`", name),
+            Token::Symbol(symbol) => write!(f, "`{}`", symbol),
+        }
+    }
+}
+
+const SYMBOLS: &[&str] = &["&&", "||", "==", "!=", "<=", ">=", "<", ">", "!", "(", ")", ","];
+
+fn error(offset: usize, message: String) -> ExpressionError {
+    ExpressionError { column: offset + 1, message }
+}
+
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, ExpressionError> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let c = bytes[i];
+        let start = i;
+        if c.is_ascii_whitespace() {
+            i += 1;
+        } else if c == b'"' {
+            let mut text = String::new();
+            let mut chars = source[i + 1..].char_indices();
+            loop {
+                match chars.next() {
+                    Some((end, '"')) => {
+                        i += end + 2;
+                        break;
+                    }
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, escaped)) => text.push(escaped),
+                        None => return Err(error(start, "unterminated string".to_string())),
+                    },
+                    Some((_, ch)) => text.push(ch),
+                    None => return Err(error(start, "unterminated string".to_string())),
+                }
+            }
+            tokens.push((start, Token::Str(text)));
+        } else if c.is_ascii_digit() || (c == b'-' && bytes.get(i + 1).is_some_and(u8::is_ascii_digit)) {
+            i += 1;
+            while i < bytes.len() && (bytes[i].is_ascii_digit() || bytes[i] == b'.') {
+                i += 1;
+            }
+            let text = &source[start..i];
+            let value = if text.contains('.') {
+                text.parse().map(Value::Float).ok()
+            } else {
+                text.parse().map(Value::Int).ok()
+            };
+            let Some(value) = value else {
+                return Err(error(start, format!("invalid number `{}`", text)));
+            };
+            tokens.push((start, Token::Number(value)));
+        } else if c.is_ascii_alphabetic() || c == b'_' {
+            while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_' || bytes[i] == b'.') {
+                i += 1;
+            }
+            tokens.push((start, Token::Ident(source[start..i].to_string())));
+        } else if let Some(symbol) = SYMBOLS.iter().find(|symbol| source[i..].starts_with(**symbol)) {
+            i += symbol.len();
+            tokens.push((start, Token::Symbol(symbol)));
+        } else {
+            let ch = source[i..].chars().next().unwrap_or_default();
+            return Err(error(start, format!("unexpected character `{}`", ch)));
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: &'a [(usize, Token)],
+    pos: usize,
+    /// Offset reported for errors at the end of the input.
+    end: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, token)| token)
+    }
+
+    fn offset(&self) -> usize {
+        self.tokens.get(self.pos).map(|(offset, _)| *offset).unwrap_or(self.end)
+    }
+
+    /// Consumes the next token if it is one of `symbols` or keyword `words`.
+    fn eat(&mut self, symbols: &[&str], words: &[&str]) -> bool {
+        let matched = match self.peek() {
+            Some(Token::Symbol(symbol)) => symbols.contains(symbol),
+            Some(Token::Ident(word)) => words.contains(&word.as_str()),
+            _ => false,
+        };
+        if matched {
+            self.pos += 1;
+        }
+        matched
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), ExpressionError> {
+        if self.eat(&[symbol], &[]) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("`{}`", symbol)))
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> ExpressionError {
+        let found = match self.peek() {
+            Some(token) => token.to_string(),
+            None => "end of expression".to_string(),
+        };
+        error(self.offset(), format!("expected {}, found {}", expected, found))
+    }
+
+    fn or(&mut self) -> Result<Expr, ExpressionError> {
+        let mut expr = self.and()?;
+        while self.eat(&["||"], &["or"]) {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, ExpressionError> {
+        let mut expr = self.not()?;
+        while self.eat(&["&&"], &["and"]) {
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr, ExpressionError> {
+        if self.eat(&["!"], &["not"]) {
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, ExpressionError> {
+        let left = self.operand()?;
+        let op = match self.peek() {
+            Some(Token::Symbol("==")) => CompareOp::Eq,
+            Some(Token::Symbol("!=")) => CompareOp::Ne,
+            Some(Token::Symbol("<")) => CompareOp::Lt,
+            Some(Token::Symbol("<=")) => CompareOp::Le,
+            Some(Token::Symbol(">")) => CompareOp::Gt,
+            Some(Token::Symbol(">=")) => CompareOp::Ge,
+            _ => return Ok(left),
+        };
+        self.pos += 1;
+        let right = self.operand()?;
+        Ok(Expr::Compare(Box::new(left), op, Box::new(right)))
+    }
+
+    fn operand(&mut self) -> Result<Expr, ExpressionError> {
+        let Some(token) = self.peek().cloned() else {
+            return Err(self.unexpected("a value"));
+        };
+        match token {
+            Token::Number(value) => {
+                self.pos += 1;
+                Ok(Expr::Literal(value))
+            }
+            Token::Str(text) => {
+                self.pos += 1;
+                Ok(Expr::Literal(Value::String(text)))
+            }
+            Token::Symbol("(") => {
+                self.pos += 1;
+                let inner = self.or()?;
+                self.expect(")")?;
+                Ok(inner)
+            }
+            Token::Ident(name) => {
+                self.pos += 1;
+                match name.as_str() {
+                    "true" => Ok(Expr::Literal(Value::Bool(true))),
+                    "false" => Ok(Expr::Literal(Value::Bool(false))),
+                    "vec3" => self.vec3(),
+                    "random" => {
+                        self.expect("(")?;
+                        self.expect(")")?;
+                        Ok(Expr::Random)
+                    }
+                    "and" | "or" | "not" => {
+                        self.pos -= 1;
+                        Err(self.unexpected("a value"))
+                    }
+                    _ => Ok(Expr::Variable(name)),
+                }
+            }
+            Token::Symbol(_) => Err(self.unexpected("a value")),
+        }
+    }
+
+    /// The `(x, y, z)` after `vec3`.
+    fn vec3(&mut self) -> Result<Expr, ExpressionError> {
+        self.expect("(")?;
+        let mut components = [0.0; 3];
+        for (index, component) in components.iter_mut().enumerate() {
+            if index > 0 {
+                self.expect(",")?;
+            }
+            match self.peek() {
+                Some(Token::Number(value)) => {
+                    *component = value.as_number().unwrap_or_default() as f32;
+                    self.pos += 1;
+                }
+                _ => return Err(self.unexpected("a number")),
+            }
+        }
+        self.expect(")")?;
+        let [x, y, z] = components;
+        Ok(Expr::Literal(Value::Vec3(x, y, z)))
+    }
+}
@@ -0,0 +1,452 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use crate::schema::player::InputMap;
+use crate::schema::keys::deserialize_key;
+use crate::schema::migration::{unchanged, Migration, SchemaError, VersionedAsset};
+
+/// `Axis2d`: moves the player; `y` is forward, `x` strafes right.
+pub const MOVE_ACTION: &str = "move";
+/// `Axis`: turns the player; positive turns right.
+pub const TURN_ACTION: &str = "turn";
+pub const JUMP_ACTION: &str = "jump";
+/// Toggles between walking and running.
+pub const RUN_ACTION: &str = "run";
+pub const INTERACT_ACTION: &str = "interact";
+/// Crouches, as a toggle or while held (`CharacterController::crouch_mode`).
+pub const CROUCH_ACTION: &str = "crouch";
+pub const ROLL_ACTION: &str = "roll";
+/// Starts and stops the character's emote.
+pub const EMOTE_ACTION: &str = "emote";
+/// `Axis2d`: orbits the camera around its target.
+pub const CAMERA_ORBIT_ACTION: &str = "camera_orbit";
+/// `Axis`: zooms the camera; positive zooms in.
+pub const CAMERA_ZOOM_ACTION: &str = "camera_zoom";
+/// While held, orbiting the camera also turns the character it follows.
+pub const CAMERA_STEER_ACTION: &str = "camera_steer";
+
+/// Project-level input profile, referenced from `ProjectConfig::input`:
+/// named actions and the keys, mouse and gamepad inputs bound to them.
+#[derive(Deserialize, Asset, TypePath, Debug, Clone, PartialEq)]
+pub struct InputProfile {
+    pub schema_version: u32,
+    /// Stick and trigger values below this are treated as zero.
+    #[serde(default = "default_deadzone")]
+    pub deadzone: f32,
+    /// In `Axis2d` actions that also read the mouse, a fully pushed stick
+    /// counts as this many pixels of mouse motion per frame, so it turns the
+    /// camera at a steady rate per second.
+    #[serde(default = "default_stick_pixels")]
+    pub stick_pixels: f32,
+    pub actions: BTreeMap<String, InputAction>,
+}
+
+fn default_deadzone() -> f32 {
+    0.15
+}
+
+fn default_stick_pixels() -> f32 {
+    6.0
+}
+
+impl VersionedAsset for InputProfile {
+    const KIND: &'static str = "InputProfile";
+    const CURRENT_VERSION: u32 = 1;
+    const MIGRATIONS: &'static [Migration] = &[
+        Migration { from: 0, apply: unchanged },
+    ];
+
+    fn parse(document: &str) -> Result<Self, SchemaError> {
+        Ok(ron::from_str(document)?)
+    }
+}
+
+/// An action and the inputs bound to it. When several bindings are active,
+/// the one with the largest value wins.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub enum InputAction {
+    Button(Vec<ButtonBinding>),
+    /// A value, e.g. `-1.0..=1.0` for a stick or key pair.
+    Axis(Vec<AxisBinding>),
+    Axis2d(Vec<Axis2dBinding>),
+}
+
+impl InputAction {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            InputAction::Button(_) => "Button",
+            InputAction::Axis(_) => "Axis",
+            InputAction::Axis2d(_) => "Axis2d",
+        }
+    }
+
+    /// Whether the action has a `MouseMotion` or `MouseDrag` binding.
+    pub fn reads_mouse(&self) -> bool {
+        match self {
+            InputAction::Axis2d(bindings) => bindings
+                .iter()
+                .any(|binding| matches!(binding, Axis2dBinding::MouseMotion | Axis2dBinding::MouseDrag(_))),
+            _ => false,
+        }
+    }
+
+    /// Every input the action names, as `(kind, name, known)`, e.g.
+    /// `("key", "KeyW", true)`.
+    pub fn named_inputs(&self) -> Vec<(&'static str, &str, bool)> {
+        let mut buttons = Vec::new();
+        let mut inputs = Vec::new();
+        match self {
+            InputAction::Button(bindings) => buttons.extend(bindings),
+            InputAction::Axis(bindings) => {
+                for binding in bindings {
+                    match binding {
+                        AxisBinding::Buttons { negative, positive } => buttons.extend([negative, positive]),
+                        AxisBinding::GamepadAxis(name) => {
+                            inputs.push(("gamepad axis", name.as_str(), parse_gamepad_axis(name).is_some()));
+                        }
+                        AxisBinding::MouseWheel => {}
+                    }
+                }
+            }
+            InputAction::Axis2d(bindings) => {
+                for binding in bindings {
+                    match binding {
+                        Axis2dBinding::Buttons { up, down, left, right } => buttons.extend([up, down, left, right]),
+                        Axis2dBinding::MouseDrag(name) => {
+                            inputs.push(("mouse button", name.as_str(), parse_mouse_button(name).is_some()));
+                        }
+                        Axis2dBinding::GamepadStick(_) | Axis2dBinding::MouseMotion => {}
+                    }
+                }
+            }
+        }
+        let mut all: Vec<_> = buttons
+            .into_iter()
+            .map(|binding| {
+                let (name, known) = binding.check();
+                (binding.kind(), name, known)
+            })
+            .collect();
+        all.extend(inputs);
+        all
+    }
+}
+
+/// A key (`Key("Space")`), mouse button (`Mouse("Left")`) or gamepad
+/// button (`Gamepad("South")`). Unknown key names are rejected when the
+/// profile is parsed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ButtonBinding {
+    Key(#[serde(deserialize_with = "deserialize_key")] String),
+    Mouse(String),
+    Gamepad(String),
+}
+
+impl ButtonBinding {
+    /// The binding's name and whether it names a known input.
+    pub fn check(&self) -> (&str, bool) {
+        match self {
+            ButtonBinding::Key(name) => (name, InputMap::parse_key(name).is_some()),
+            ButtonBinding::Mouse(name) => (name, parse_mouse_button(name).is_some()),
+            ButtonBinding::Gamepad(name) => (name, parse_gamepad_button(name).is_some()),
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ButtonBinding::Key(_) => "key",
+            ButtonBinding::Mouse(_) => "mouse button",
+            ButtonBinding::Gamepad(_) => "gamepad button",
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub enum AxisBinding {
+    /// `-1.0` while `negative` is held, `1.0` while `positive` is.
+    Buttons { negative: ButtonBinding, positive: ButtonBinding },
+    /// A stick or trigger axis: `GamepadAxis("LeftStickX")`.
+    GamepadAxis(String),
+    /// Scroll lines this frame; scrolling up is positive.
+    MouseWheel,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub enum Axis2dBinding {
+    Buttons { up: ButtonBinding, down: ButtonBinding, left: ButtonBinding, right: ButtonBinding },
+    /// `GamepadStick(Left)`; pushing the stick up is positive `y`. Scaled by
+    /// `InputProfile::stick_pixels` in actions that also read the mouse.
+    GamepadStick(Stick),
+    /// Mouse movement in pixels this frame.
+    MouseMotion,
+    /// Mouse movement while a mouse button is held: `MouseDrag("Right")`.
+    MouseDrag(String),
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stick {
+    Left,
+    Right,
+}
+
+/// Bindings the player changed in game, keyed by input name, applied on top
+/// of the project's bindings. See `InputProfile::rebind` for input names.
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct InputOverrides {
+    #[serde(default)]
+    pub bindings: BTreeMap<String, ButtonBinding>,
+}
+
+impl InputOverrides {
+    /// Name the overrides are persisted under.
+    pub const STORAGE_KEY: &'static str = "input_overrides";
+
+    /// Sets the keys of a character's `InputMap`; other bindings are skipped.
+    pub fn apply_to_map(&self, inputs: &mut InputMap) {
+        for (input, binding) in &self.bindings {
+            if let ButtonBinding::Key(key) = binding {
+                inputs.set_key(input, key);
+            }
+        }
+    }
+
+    pub fn apply_to_profile(&self, profile: &mut InputProfile) {
+        for (input, binding) in &self.bindings {
+            profile.rebind(input, binding.clone());
+        }
+    }
+}
+
+impl Default for InputProfile {
+    fn default() -> Self {
+        Self::from_input_map(&InputMap::default())
+    }
+}
+
+impl InputProfile {
+    /// The default bindings with the keys of a character's `InputMap`, used
+    /// when the project has no input profile.
+    pub fn from_input_map(inputs: &InputMap) -> Self {
+        let key = |name: &str| ButtonBinding::Key(name.to_string());
+        let gamepad = |name: &str| ButtonBinding::Gamepad(name.to_string());
+        let actions = [
+            (MOVE_ACTION, InputAction::Axis2d(vec![
+                Axis2dBinding::Buttons {
+                    up: key(&inputs.forward),
+                    down: key(&inputs.backward),
+                    left: key(&inputs.strafe_left),
+                    right: key(&inputs.strafe_right),
+                },
+                Axis2dBinding::GamepadStick(Stick::Left),
+            ])),
+            (TURN_ACTION, InputAction::Axis(vec![
+                AxisBinding::Buttons { negative: key(&inputs.left), positive: key(&inputs.right) },
+                AxisBinding::Buttons { negative: gamepad("LeftTrigger"), positive: gamepad("RightTrigger") },
+            ])),
+            (JUMP_ACTION, InputAction::Button(vec![key(&inputs.jump), gamepad("South")])),
+            (RUN_ACTION, InputAction::Button(vec![key(&inputs.run), gamepad("LeftThumb")])),
+            (INTERACT_ACTION, InputAction::Button(vec![key(&inputs.interact), gamepad("West")])),
+            (CROUCH_ACTION, InputAction::Button(vec![key(&inputs.crouch), gamepad("RightThumb")])),
+            (ROLL_ACTION, InputAction::Button(vec![key(&inputs.roll), gamepad("East")])),
+            (EMOTE_ACTION, InputAction::Button(vec![key(&inputs.emote), gamepad("DPadLeft")])),
+            (CAMERA_ORBIT_ACTION, InputAction::Axis2d(vec![
+                Axis2dBinding::MouseDrag("Left".to_string()),
+                Axis2dBinding::MouseDrag("Right".to_string()),
+                Axis2dBinding::GamepadStick(Stick::Right),
+            ])),
+            (CAMERA_STEER_ACTION, InputAction::Button(vec![ButtonBinding::Mouse("Right".to_string())])),
+            (CAMERA_ZOOM_ACTION, InputAction::Axis(vec![
+                AxisBinding::MouseWheel,
+                AxisBinding::Buttons { negative: gamepad("DPadDown"), positive: gamepad("DPadUp") },
+            ])),
+        ];
+        Self {
+            schema_version: Self::CURRENT_VERSION,
+            deadzone: default_deadzone(),
+            stick_pixels: default_stick_pixels(),
+            actions: actions.into_iter().map(|(name, action)| (name.to_string(), action)).collect(),
+        }
+    }
+
+    /// Replaces the binding of `input` that has the same kind as `binding`
+    /// (key, mouse or gamepad button). `input` is either an `InputMap` input,
+    /// which names one direction of `move` (`forward`, `backward`,
+    /// `strafe_left`, `strafe_right`) or `turn` (`left`, `right`), or the name
+    /// of an action. A `Button` action without a binding of that kind gains
+    /// one. Returns whether anything was bound.
+    pub fn rebind(&mut self, input: &str, binding: ButtonBinding) -> bool {
+        let (action, slot) = input_slot(input);
+        let Some(action) = self.actions.get_mut(action) else {
+            return false;
+        };
+        if let Some(existing) = action.slots_mut(slot).into_iter().find(|existing| existing.kind() == binding.kind()) {
+            *existing = binding;
+            return true;
+        }
+        match action {
+            InputAction::Button(bindings) => {
+                bindings.push(binding);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// The bindings `rebind` can replace for `input`.
+    pub fn input_bindings(&self, input: &str) -> Vec<&ButtonBinding> {
+        let (action, slot) = input_slot(input);
+        self.actions.get(action).map(|action| action.slots(slot)).unwrap_or_default()
+    }
+
+    /// The inputs, as named for `rebind`, that `binding` is bound to.
+    pub fn inputs_bound_to(&self, binding: &ButtonBinding) -> Vec<String> {
+        let mut inputs = Vec::new();
+        for (name, action) in &self.actions {
+            let slots = match action {
+                InputAction::Button(_) => 1,
+                InputAction::Axis(_) => 2,
+                InputAction::Axis2d(_) => 4,
+            };
+            for slot in 0..slots {
+                if action.slots(slot).contains(&binding) {
+                    inputs.push(slot_input(name, slot).to_string());
+                }
+            }
+        }
+        inputs.dedup();
+        inputs
+    }
+}
+
+/// The action an input belongs to and, for `Axis` and `Axis2d` actions, the
+/// position of its button in `Buttons` bindings.
+fn input_slot(input: &str) -> (&str, usize) {
+    match input {
+        "forward" => (MOVE_ACTION, 0),
+        "backward" => (MOVE_ACTION, 1),
+        "strafe_left" => (MOVE_ACTION, 2),
+        "strafe_right" => (MOVE_ACTION, 3),
+        "left" => (TURN_ACTION, 0),
+        "right" => (TURN_ACTION, 1),
+        action => (action, 0),
+    }
+}
+
+/// The input `input_slot` maps to `(action, slot)`.
+fn slot_input(action: &str, slot: usize) -> &str {
+    match (action, slot) {
+        (MOVE_ACTION, 0) => "forward",
+        (MOVE_ACTION, 1) => "backward",
+        (MOVE_ACTION, 2) => "strafe_left",
+        (MOVE_ACTION, 3) => "strafe_right",
+        (TURN_ACTION, 0) => "left",
+        (TURN_ACTION, 1) => "right",
+        (action, _) => action,
+    }
+}
+
+impl InputAction {
+    fn slots(&self, slot: usize) -> Vec<&ButtonBinding> {
+        match self {
+            InputAction::Button(bindings) => bindings.iter().collect(),
+            InputAction::Axis(bindings) => bindings
+                .iter()
+                .filter_map(|binding| match binding {
+                    AxisBinding::Buttons { negative, positive } => [negative, positive].into_iter().nth(slot),
+                    _ => None,
+                })
+                .collect(),
+            InputAction::Axis2d(bindings) => bindings
+                .iter()
+                .filter_map(|binding| match binding {
+                    Axis2dBinding::Buttons { up, down, left, right } => [up, down, left, right].into_iter().nth(slot),
+                    _ => None,
+                })
+                .collect(),
+        }
+    }
+
+    fn slots_mut(&mut self, slot: usize) -> Vec<&mut ButtonBinding> {
+        match self {
+            InputAction::Button(bindings) => bindings.iter_mut().collect(),
+            InputAction::Axis(bindings) => bindings
+                .iter_mut()
+                .filter_map(|binding| match binding {
+                    AxisBinding::Buttons { negative, positive } => [negative, positive].into_iter().nth(slot),
+                    _ => None,
+                })
+                .collect(),
+            InputAction::Axis2d(bindings) => bindings
+                .iter_mut()
+                .filter_map(|binding| match binding {
+                    Axis2dBinding::Buttons { up, down, left, right } => [up, down, left, right].into_iter().nth(slot),
+                    _ => None,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The kind each action the engine reads must have.
+pub const ENGINE_ACTIONS: [(&str, &str); 11] = [
+    (MOVE_ACTION, "Axis2d"),
+    (TURN_ACTION, "Axis"),
+    (JUMP_ACTION, "Button"),
+    (RUN_ACTION, "Button"),
+    (INTERACT_ACTION, "Button"),
+    (CROUCH_ACTION, "Button"),
+    (ROLL_ACTION, "Button"),
+    (EMOTE_ACTION, "Button"),
+    (CAMERA_ORBIT_ACTION, "Axis2d"),
+    (CAMERA_ZOOM_ACTION, "Axis"),
+    (CAMERA_STEER_ACTION, "Button"),
+];
+
+pub fn parse_mouse_button(s: &str) -> Option<MouseButton> {
+    match s {
+        "Left" => Some(MouseButton::Left),
+        "Right" => Some(MouseButton::Right),
+        "Middle" => Some(MouseButton::Middle),
+        "Back" => Some(MouseButton::Back),
+        "Forward" => Some(MouseButton::Forward),
+        _ => None,
+    }
+}
+
+pub fn parse_gamepad_button(s: &str) -> Option<GamepadButton> {
+    let button = match s {
+        "South" => GamepadButton::South,
+        "East" => GamepadButton::East,
+        "North" => GamepadButton::North,
+        "West" => GamepadButton::West,
+        "C" => GamepadButton::C,
+        "Z" => GamepadButton::Z,
+        "LeftTrigger" => GamepadButton::LeftTrigger,
+        "LeftTrigger2" => GamepadButton::LeftTrigger2,
+        "RightTrigger" => GamepadButton::RightTrigger,
+        "RightTrigger2" => GamepadButton::RightTrigger2,
+        "Select" => GamepadButton::Select,
+        "Start" => GamepadButton::Start,
+        "Mode" => GamepadButton::Mode,
+        "LeftThumb" => GamepadButton::LeftThumb,
+        "RightThumb" => GamepadButton::RightThumb,
+        "DPadUp" => GamepadButton::DPadUp,
+        "DPadDown" => GamepadButton::DPadDown,
+        "DPadLeft" => GamepadButton::DPadLeft,
+        "DPadRight" => GamepadButton::DPadRight,
+        _ => return None,
+    };
+    Some(button)
+}
+
+pub fn parse_gamepad_axis(s: &str) -> Option<GamepadAxis> {
+    match s {
+        "LeftStickX" => Some(GamepadAxis::LeftStickX),
+        "LeftStickY" => Some(GamepadAxis::LeftStickY),
+        "LeftZ" => Some(GamepadAxis::LeftZ),
+        "RightStickX" => Some(GamepadAxis::RightStickX),
+        "RightStickY" => Some(GamepadAxis::RightStickY),
+        "RightZ" => Some(GamepadAxis::RightZ),
+        _ => None,
+    }
+}
@@ -0,0 +1,355 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Deserializer};
+use std::fmt;
+
+/// Every `KeyCode`, by its canonical name: the name of the variant.
+pub const KEY_CODES: &[(&str, KeyCode)] = &[
+    ("Backquote", KeyCode::Backquote),
+    ("Backslash", KeyCode::Backslash),
+    ("BracketLeft", KeyCode::BracketLeft),
+    ("BracketRight", KeyCode::BracketRight),
+    ("Comma", KeyCode::Comma),
+    ("Digit0", KeyCode::Digit0),
+    ("Digit1", KeyCode::Digit1),
+    ("Digit2", KeyCode::Digit2),
+    ("Digit3", KeyCode::Digit3),
+    ("Digit4", KeyCode::Digit4),
+    ("Digit5", KeyCode::Digit5),
+    ("Digit6", KeyCode::Digit6),
+    ("Digit7", KeyCode::Digit7),
+    ("Digit8", KeyCode::Digit8),
+    ("Digit9", KeyCode::Digit9),
+    ("Equal", KeyCode::Equal),
+    ("IntlBackslash", KeyCode::IntlBackslash),
+    ("IntlRo", KeyCode::IntlRo),
+    ("IntlYen", KeyCode::IntlYen),
+    ("KeyA", KeyCode::KeyA),
+    ("KeyB", KeyCode::KeyB),
+    ("KeyC", KeyCode::KeyC),
+    ("KeyD", KeyCode::KeyD),
+    ("KeyE", KeyCode::KeyE),
+    ("KeyF", KeyCode::KeyF),
+    ("KeyG", KeyCode::KeyG),
+    ("KeyH", KeyCode::KeyH),
+    ("KeyI", KeyCode::KeyI),
+    ("KeyJ", KeyCode::KeyJ),
+    ("KeyK", KeyCode::KeyK),
+    ("KeyL", KeyCode::KeyL),
+    ("KeyM", KeyCode::KeyM),
+    ("KeyN", KeyCode::KeyN),
+    ("KeyO", KeyCode::KeyO),
+    ("KeyP", KeyCode::KeyP),
+    ("KeyQ", KeyCode::KeyQ),
+    ("KeyR", KeyCode::KeyR),
+    ("KeyS", KeyCode::KeyS),
+    ("KeyT", KeyCode::KeyT),
+    ("KeyU", KeyCode::KeyU),
+    ("KeyV", KeyCode::KeyV),
+    ("KeyW", KeyCode::KeyW),
+    ("KeyX", KeyCode::KeyX),
+    ("KeyY", KeyCode::KeyY),
+    ("KeyZ", KeyCode::KeyZ),
+    ("Minus", KeyCode::Minus),
+    ("Period", KeyCode::Period),
+    ("Quote", KeyCode::Quote),
+    ("Semicolon", KeyCode::Semicolon),
+    ("Slash", KeyCode::Slash),
+    ("AltLeft", KeyCode::AltLeft),
+    ("AltRight", KeyCode::AltRight),
+    ("Backspace", KeyCode::Backspace),
+    ("CapsLock", KeyCode::CapsLock),
+    ("ContextMenu", KeyCode::ContextMenu),
+    ("ControlLeft", KeyCode::ControlLeft),
+    ("ControlRight", KeyCode::ControlRight),
+    ("Enter", KeyCode::Enter),
+    ("SuperLeft", KeyCode::SuperLeft),
+    ("SuperRight", KeyCode::SuperRight),
+    ("ShiftLeft", KeyCode::ShiftLeft),
+    ("ShiftRight", KeyCode::ShiftRight),
+    ("Space", KeyCode::Space),
+    ("Tab", KeyCode::Tab),
+    ("Convert", KeyCode::Convert),
+    ("KanaMode", KeyCode::KanaMode),
+    ("Lang1", KeyCode::Lang1),
+    ("Lang2", KeyCode::Lang2),
+    ("Lang3", KeyCode::Lang3),
+    ("Lang4", KeyCode::Lang4),
+    ("Lang5", KeyCode::Lang5),
+    ("NonConvert", KeyCode::NonConvert),
+    ("Delete", KeyCode::Delete),
+    ("End", KeyCode::End),
+    ("Help", KeyCode::Help),
+    ("Home", KeyCode::Home),
+    ("Insert", KeyCode::Insert),
+    ("PageDown", KeyCode::PageDown),
+    ("PageUp", KeyCode::PageUp),
+    ("ArrowDown", KeyCode::ArrowDown),
+    ("ArrowLeft", KeyCode::ArrowLeft),
+    ("ArrowRight", KeyCode::ArrowRight),
+    ("ArrowUp", KeyCode::ArrowUp),
+    ("NumLock", KeyCode::NumLock),
+    ("Numpad0", KeyCode::Numpad0),
+    ("Numpad1", KeyCode::Numpad1),
+    ("Numpad2", KeyCode::Numpad2),
+    ("Numpad3", KeyCode::Numpad3),
+    ("Numpad4", KeyCode::Numpad4),
+    ("Numpad5", KeyCode::Numpad5),
+    ("Numpad6", KeyCode::Numpad6),
+    ("Numpad7", KeyCode::Numpad7),
+    ("Numpad8", KeyCode::Numpad8),
+    ("Numpad9", KeyCode::Numpad9),
+    ("NumpadAdd", KeyCode::NumpadAdd),
+    ("NumpadBackspace", KeyCode::NumpadBackspace),
+    ("NumpadClear", KeyCode::NumpadClear),
+    ("NumpadClearEntry", KeyCode::NumpadClearEntry),
+    ("NumpadComma", KeyCode::NumpadComma),
+    ("NumpadDecimal", KeyCode::NumpadDecimal),
+    ("NumpadDivide", KeyCode::NumpadDivide),
+    ("NumpadEnter", KeyCode::NumpadEnter),
+    ("NumpadEqual", KeyCode::NumpadEqual),
+    ("NumpadHash", KeyCode::NumpadHash),
+    ("NumpadMemoryAdd", KeyCode::NumpadMemoryAdd),
+    ("NumpadMemoryClear", KeyCode::NumpadMemoryClear),
+    ("NumpadMemoryRecall", KeyCode::NumpadMemoryRecall),
+    ("NumpadMemoryStore", KeyCode::NumpadMemoryStore),
+    ("NumpadMemorySubtract", KeyCode::NumpadMemorySubtract),
+    ("NumpadMultiply", KeyCode::NumpadMultiply),
+    ("NumpadParenLeft", KeyCode::NumpadParenLeft),
+    ("NumpadParenRight", KeyCode::NumpadParenRight),
+    ("NumpadStar", KeyCode::NumpadStar),
+    ("NumpadSubtract", KeyCode::NumpadSubtract),
+    ("Escape", KeyCode::Escape),
+    ("Fn", KeyCode::Fn),
+    ("FnLock", KeyCode::FnLock),
+    ("PrintScreen", KeyCode::PrintScreen),
+    ("ScrollLock", KeyCode::ScrollLock),
+    ("Pause", KeyCode::Pause),
+    ("BrowserBack", KeyCode::BrowserBack),
+    ("BrowserFavorites", KeyCode::BrowserFavorites),
+    ("BrowserForward", KeyCode::BrowserForward),
+    ("BrowserHome", KeyCode::BrowserHome),
+    ("BrowserRefresh", KeyCode::BrowserRefresh),
+    ("BrowserSearch", KeyCode::BrowserSearch),
+    ("BrowserStop", KeyCode::BrowserStop),
+    ("Eject", KeyCode::Eject),
+    ("LaunchApp1", KeyCode::LaunchApp1),
+    ("LaunchApp2", KeyCode::LaunchApp2),
+    ("LaunchMail", KeyCode::LaunchMail),
+    ("MediaPlayPause", KeyCode::MediaPlayPause),
+    ("MediaSelect", KeyCode::MediaSelect),
+    ("MediaStop", KeyCode::MediaStop),
+    ("MediaTrackNext", KeyCode::MediaTrackNext),
+    ("MediaTrackPrevious", KeyCode::MediaTrackPrevious),
+    ("Power", KeyCode::Power),
+    ("Sleep", KeyCode::Sleep),
+    ("AudioVolumeDown", KeyCode::AudioVolumeDown),
+    ("AudioVolumeMute", KeyCode::AudioVolumeMute),
+    ("AudioVolumeUp", KeyCode::AudioVolumeUp),
+    ("WakeUp", KeyCode::WakeUp),
+    ("Meta", KeyCode::Meta),
+    ("Hyper", KeyCode::Hyper),
+    ("Turbo", KeyCode::Turbo),
+    ("Abort", KeyCode::Abort),
+    ("Resume", KeyCode::Resume),
+    ("Suspend", KeyCode::Suspend),
+    ("Again", KeyCode::Again),
+    ("Copy", KeyCode::Copy),
+    ("Cut", KeyCode::Cut),
+    ("Find", KeyCode::Find),
+    ("Open", KeyCode::Open),
+    ("Paste", KeyCode::Paste),
+    ("Props", KeyCode::Props),
+    ("Select", KeyCode::Select),
+    ("Undo", KeyCode::Undo),
+    ("Hiragana", KeyCode::Hiragana),
+    ("Katakana", KeyCode::Katakana),
+    ("F1", KeyCode::F1),
+    ("F2", KeyCode::F2),
+    ("F3", KeyCode::F3),
+    ("F4", KeyCode::F4),
+    ("F5", KeyCode::F5),
+    ("F6", KeyCode::F6),
+    ("F7", KeyCode::F7),
+    ("F8", KeyCode::F8),
+    ("F9", KeyCode::F9),
+    ("F10", KeyCode::F10),
+    ("F11", KeyCode::F11),
+    ("F12", KeyCode::F12),
+    ("F13", KeyCode::F13),
+    ("F14", KeyCode::F14),
+    ("F15", KeyCode::F15),
+    ("F16", KeyCode::F16),
+    ("F17", KeyCode::F17),
+    ("F18", KeyCode::F18),
+    ("F19", KeyCode::F19),
+    ("F20", KeyCode::F20),
+    ("F21", KeyCode::F21),
+    ("F22", KeyCode::F22),
+    ("F23", KeyCode::F23),
+    ("F24", KeyCode::F24),
+    ("F25", KeyCode::F25),
+    ("F26", KeyCode::F26),
+    ("F27", KeyCode::F27),
+    ("F28", KeyCode::F28),
+    ("F29", KeyCode::F29),
+    ("F30", KeyCode::F30),
+    ("F31", KeyCode::F31),
+    ("F32", KeyCode::F32),
+    ("F33", KeyCode::F33),
+    ("F34", KeyCode::F34),
+    ("F35", KeyCode::F35),
+];
+
+/// Other names keys can be written as: letters, digits and punctuation on
+/// their own, and common short names.
+pub const KEY_ALIASES: &[(&str, KeyCode)] = &[
+    ("A", KeyCode::KeyA),
+    ("B", KeyCode::KeyB),
+    ("C", KeyCode::KeyC),
+    ("D", KeyCode::KeyD),
+    ("E", KeyCode::KeyE),
+    ("F", KeyCode::KeyF),
+    ("G", KeyCode::KeyG),
+    ("H", KeyCode::KeyH),
+    ("I", KeyCode::KeyI),
+    ("J", KeyCode::KeyJ),
+    ("K", KeyCode::KeyK),
+    ("L", KeyCode::KeyL),
+    ("M", KeyCode::KeyM),
+    ("N", KeyCode::KeyN),
+    ("O", KeyCode::KeyO),
+    ("P", KeyCode::KeyP),
+    ("Q", KeyCode::KeyQ),
+    ("R", KeyCode::KeyR),
+    ("S", KeyCode::KeyS),
+    ("T", KeyCode::KeyT),
+    ("U", KeyCode::KeyU),
+    ("V", KeyCode::KeyV),
+    ("W", KeyCode::KeyW),
+    ("X", KeyCode::KeyX),
+    ("Y", KeyCode::KeyY),
+    ("Z", KeyCode::KeyZ),
+    ("0", KeyCode::Digit0),
+    ("1", KeyCode::Digit1),
+    ("2", KeyCode::Digit2),
+    ("3", KeyCode::Digit3),
+    ("4", KeyCode::Digit4),
+    ("5", KeyCode::Digit5),
+    ("6", KeyCode::Digit6),
+    ("7", KeyCode::Digit7),
+    ("8", KeyCode::Digit8),
+    ("9", KeyCode::Digit9),
+    ("Num0", KeyCode::Numpad0),
+    ("Num1", KeyCode::Numpad1),
+    ("Num2", KeyCode::Numpad2),
+    ("Num3", KeyCode::Numpad3),
+    ("Num4", KeyCode::Numpad4),
+    ("Num5", KeyCode::Numpad5),
+    ("Num6", KeyCode::Numpad6),
+    ("Num7", KeyCode::Numpad7),
+    ("Num8", KeyCode::Numpad8),
+    ("Num9", KeyCode::Numpad9),
+    ("Up", KeyCode::ArrowUp),
+    ("Down", KeyCode::ArrowDown),
+    ("Left", KeyCode::ArrowLeft),
+    ("Right", KeyCode::ArrowRight),
+    ("Esc", KeyCode::Escape),
+    ("Return", KeyCode::Enter),
+    ("Shift", KeyCode::ShiftLeft),
+    ("Ctrl", KeyCode::ControlLeft),
+    ("Control", KeyCode::ControlLeft),
+    ("Alt", KeyCode::AltLeft),
+    ("Super", KeyCode::SuperLeft),
+    ("LShift", KeyCode::ShiftLeft),
+    ("RShift", KeyCode::ShiftRight),
+    ("LCtrl", KeyCode::ControlLeft),
+    ("RCtrl", KeyCode::ControlRight),
+    ("LAlt", KeyCode::AltLeft),
+    ("RAlt", KeyCode::AltRight),
+    ("Del", KeyCode::Delete),
+    ("Ins", KeyCode::Insert),
+    ("PgUp", KeyCode::PageUp),
+    ("PgDn", KeyCode::PageDown),
+    ("`", KeyCode::Backquote),
+    ("-", KeyCode::Minus),
+    ("=", KeyCode::Equal),
+    ("[", KeyCode::BracketLeft),
+    ("]", KeyCode::BracketRight),
+    ("\\", KeyCode::Backslash),
+    (";", KeyCode::Semicolon),
+    ("'", KeyCode::Quote),
+    (",", KeyCode::Comma),
+    (".", KeyCode::Period),
+    ("/", KeyCode::Slash),
+];
+
+/// A key name that is neither a `KeyCode` nor an alias.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownKey {
+    pub name: String,
+    /// The canonical name of the closest key, when one is close enough.
+    pub suggestion: Option<&'static str>,
+}
+
+impl fmt::Display for UnknownKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown key \"{}\"", self.name)?;
+        if let Some(suggestion) = self.suggestion {
+            write!(f, ", did you mean \"{}\"?", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for UnknownKey {}
+
+/// Parses a key by canonical name or alias. Names are not case sensitive.
+pub fn parse_key(name: &str) -> Result<KeyCode, UnknownKey> {
+    KEY_CODES
+        .iter()
+        .chain(KEY_ALIASES)
+        .find(|(key_name, _)| key_name.eq_ignore_ascii_case(name))
+        .map(|(_, key)| *key)
+        .ok_or_else(|| UnknownKey { name: name.to_string(), suggestion: suggest_key(name) })
+}
+
+/// The canonical name of `key`, e.g. `"KeyW"`.
+pub fn key_name(key: KeyCode) -> Option<&'static str> {
+    KEY_CODES.iter().find(|(_, code)| *code == key).map(|(name, _)| *name)
+}
+
+/// The canonical name of the key whose name or alias is closest to `name`.
+fn suggest_key(name: &str) -> Option<&'static str> {
+    let name = name.to_ascii_lowercase();
+    let max_distance = (name.chars().count() / 2).max(1);
+    KEY_CODES
+        .iter()
+        .chain(KEY_ALIASES)
+        .map(|(candidate, key)| (edit_distance(&name, &candidate.to_ascii_lowercase()), *key))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .and_then(|(_, key)| key_name(key))
+}
+
+/// Levenshtein distance between two strings.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous + usize::from(ca != *cb);
+            previous = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(previous + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// `deserialize_with` for key names: rejects names `parse_key` does not know.
+pub fn deserialize_key<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    parse_key(&name).map_err(serde::de::Error::custom)?;
+    Ok(name)
+}
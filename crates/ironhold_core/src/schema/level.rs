@@ -1,22 +1,137 @@
+use bevy::asset::LoadContext;
 use bevy::prelude::*;
 use serde::Deserialize;
+use std::collections::HashMap;
 use crate::schema::player::PlayerConfig;
 use crate::schema::ui::UiElement;
+use crate::schema::entity::{nest_character_movement, EntityDef};
+use crate::schema::prefab::{Prefab, PrefabInstance};
+use crate::schema::value::Value;
+use crate::schema::transform::{to_transform, Rotation, Scale};
+use crate::schema::migration::{unchanged, Migration, SchemaError, VersionedAsset};
 
-#[derive(Deserialize, Asset, TypePath, Debug, Clone)]
+#[derive(Deserialize, Asset, TypePath, Debug, Clone, Default)]
 pub struct GameLevel {
+    pub schema_version: u32,
+    /// Scene files merged into this one, e.g. `"scenes/shared/lighting.ron"`.
+    #[serde(default)]
+    pub includes: Vec<String>,
     #[serde(default)]
     pub models: Vec<ModelInfo>,
     #[serde(default)]
     pub ui: Vec<UiElement>,
     #[serde(default)]
     pub player: Option<PlayerConfig>,
+    #[serde(default)]
+    pub entities: Vec<EntityDef>,
+    #[serde(default)]
+    pub prefabs: Vec<PrefabInstance>,
+    /// Project variables declared when the level is spawned. Variables that
+    /// already have a value keep it.
+    #[serde(default)]
+    pub variables: HashMap<String, Value>,
+    /// Height of the infinite ground plane characters stand on; `None` leaves
+    /// only solid colliders to stand on.
+    #[serde(default = "default_ground_plane")]
+    pub ground_plane: Option<f32>,
+    /// Keeps included scenes and prefabs loaded while this level is.
+    #[serde(skip)]
+    pub dependencies: Vec<UntypedHandle>,
+}
+
+fn default_ground_plane() -> Option<f32> {
+    Some(0.0)
+}
+
+impl GameLevel {
+    /// Returns this level (loaded from `path`) with its `includes` merged in,
+    /// depth first, ahead of its own content. A level's `player` and
+    /// `variables` replace ones it included. Includes that `resolve` cannot
+    /// find, or that form a cycle, are skipped.
+    pub fn flattened(&self, path: &str, resolve: &dyn Fn(&str) -> Option<GameLevel>) -> GameLevel {
+        let mut out = GameLevel {
+            schema_version: self.schema_version,
+            ground_plane: self.ground_plane,
+            ..default()
+        };
+        let mut visiting = vec![path.to_string()];
+        merge_level(self, &mut out, resolve, &mut visiting);
+        out
+    }
+}
+
+fn merge_level(
+    level: &GameLevel,
+    out: &mut GameLevel,
+    resolve: &dyn Fn(&str) -> Option<GameLevel>,
+    visiting: &mut Vec<String>,
+) {
+    for path in &level.includes {
+        if visiting.contains(path) {
+            warn!("Scene include cycle through '{}', skipping", path);
+            continue;
+        }
+        let Some(included) = resolve(path) else {
+            warn!("Included scene '{}' is not loaded, skipping", path);
+            continue;
+        };
+        visiting.push(path.clone());
+        merge_level(&included, out, resolve, visiting);
+        visiting.pop();
+    }
+
+    out.models.extend(level.models.iter().cloned());
+    out.ui.extend(level.ui.iter().cloned());
+    out.entities.extend(level.entities.iter().cloned());
+    out.prefabs.extend(level.prefabs.iter().cloned());
+    out.variables.extend(level.variables.iter().map(|(k, v)| (k.clone(), v.clone())));
+    if level.player.is_some() {
+        out.player = level.player.clone();
+    }
 }
 
-#[derive(Deserialize, Debug, Clone)]
+impl VersionedAsset for GameLevel {
+    const KIND: &'static str = "GameLevel";
+    const CURRENT_VERSION: u32 = 2;
+    const MIGRATIONS: &'static [Migration] = &[
+        Migration { from: 0, apply: unchanged },
+        // v1 -> v2: `CharacterController` movement fields move into `movement`.
+        Migration { from: 1, apply: nest_character_movement },
+    ];
+
+    fn parse(document: &str) -> Result<Self, SchemaError> {
+        Ok(ron::from_str(document)?)
+    }
+
+    fn load_dependencies(&mut self, load_context: &mut LoadContext) {
+        for path in &self.includes {
+            let handle = load_context.load::<GameLevel>(path.clone());
+            self.dependencies.push(handle.untyped());
+        }
+        for instance in &self.prefabs {
+            let handle = load_context.load::<Prefab>(instance.prefab.clone());
+            self.dependencies.push(handle.untyped());
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
 pub struct ModelInfo {
+    /// Stable identifier, attached to the spawned entity as `Name` and `EntityId`.
+    #[serde(default)]
+    pub id: Option<String>,
     pub path: String,
     pub position: (f32, f32, f32),
+    #[serde(default)]
+    pub rotation: Rotation,
+    #[serde(default)]
+    pub scale: Scale,
+}
+
+impl ModelInfo {
+    pub fn transform(&self) -> Transform {
+        to_transform(self.position, self.rotation, self.scale)
+    }
 }
 
 #[derive(Resource)]
@@ -24,3 +139,8 @@ pub struct LevelHandle(pub Handle<GameLevel>);
 
 #[derive(Component)]
 pub struct LevelEntity;
+
+/// Stable, data-defined identifier of a spawned entity, used by logic and
+/// actions to look the entity up.
+#[derive(Component, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EntityId(pub String);
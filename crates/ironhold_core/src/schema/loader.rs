@@ -0,0 +1,79 @@
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::prelude::*;
+use std::marker::PhantomData;
+use crate::schema::migration::{from_versioned_bytes, SchemaError, VersionedAsset};
+
+/// Registers a RON loader for `A` that runs the schema migration pipeline
+/// before deserializing.
+pub struct VersionedRonAssetPlugin<A> {
+    extensions: Vec<&'static str>,
+    _marker: PhantomData<fn() -> A>,
+}
+
+impl<A> VersionedRonAssetPlugin<A> {
+    pub fn new(extensions: &[&'static str]) -> Self {
+        Self {
+            extensions: extensions.to_vec(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<A: VersionedAsset + Asset> Plugin for VersionedRonAssetPlugin<A> {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<A>()
+            .register_asset_loader(VersionedRonLoader::<A> {
+                extensions: self.extensions.clone(),
+                _marker: PhantomData,
+            });
+    }
+}
+
+pub struct VersionedRonLoader<A> {
+    extensions: Vec<&'static str>,
+    _marker: PhantomData<fn() -> A>,
+}
+
+#[derive(Debug)]
+pub enum VersionedRonLoaderError {
+    Io(std::io::Error),
+    Schema(SchemaError),
+}
+
+impl std::fmt::Display for VersionedRonLoaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VersionedRonLoaderError::Io(e) => write!(f, "could not read file: {}", e),
+            VersionedRonLoaderError::Schema(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for VersionedRonLoaderError {}
+
+impl<A: VersionedAsset + Asset> AssetLoader for VersionedRonLoader<A> {
+    type Asset = A;
+    type Settings = ();
+    type Error = VersionedRonLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(VersionedRonLoaderError::Io)?;
+        let mut asset = from_versioned_bytes::<A>(&bytes).map_err(VersionedRonLoaderError::Schema)?;
+        asset.load_dependencies(load_context);
+        Ok(asset)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &self.extensions
+    }
+}
@@ -0,0 +1,90 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+use crate::runtime::actions::Action;
+use crate::runtime::events::EventName;
+use crate::schema::value::Value;
+use crate::schema::expression::Expression;
+use crate::schema::migration::{unchanged, Migration, SchemaError, VersionedAsset};
+
+/// `Transition::from` value matching every state.
+pub const ANY_STATE: &str = "*";
+
+/// Project-level state machine, referenced from `ProjectConfig::logic`.
+#[derive(Deserialize, Asset, TypePath, Debug, Clone)]
+pub struct GlobalLogic {
+    pub schema_version: u32,
+    pub initial_state: String,
+    pub states: Vec<LogicState>,
+    #[serde(default)]
+    pub transitions: Vec<Transition>,
+}
+
+impl VersionedAsset for GlobalLogic {
+    const KIND: &'static str = "GlobalLogic";
+    const CURRENT_VERSION: u32 = 1;
+    const MIGRATIONS: &'static [Migration] = &[
+        Migration { from: 0, apply: unchanged },
+    ];
+
+    fn parse(document: &str) -> Result<Self, SchemaError> {
+        Ok(ron::from_str(document)?)
+    }
+}
+
+impl GlobalLogic {
+    pub fn state(&self, name: &str) -> Option<&LogicState> {
+        self.states.iter().find(|state| state.name == name)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LogicState {
+    pub name: String,
+    #[serde(default)]
+    pub on_enter: Vec<Action>,
+    #[serde(default)]
+    pub on_exit: Vec<Action>,
+    /// Seconds after entering the state at which a `"timer"` event fires.
+    #[serde(default)]
+    pub timer: Option<f32>,
+}
+
+/// Fires when `event` arrives in state `from` and `guard` holds. The first
+/// matching transition wins.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Transition {
+    /// State name, or `"*"` for any state.
+    pub from: String,
+    /// Event name, e.g. `"ui.start"` or `"key.Escape"`.
+    pub event: EventName,
+    #[serde(default)]
+    pub guard: Option<Condition>,
+    /// Target state. Without one, only `actions` run and the state is kept.
+    #[serde(default)]
+    pub to: Option<String>,
+    #[serde(default)]
+    pub actions: Vec<Action>,
+}
+
+/// Transition guard.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub enum Condition {
+    /// The active scene is `path`.
+    SceneIs(String),
+    /// The behavior's local variable `name` equals `value`.
+    LocalIs(String, Value),
+    /// The event was caused by the entity with this id, e.g. the character
+    /// entering a trigger.
+    OtherIs(String),
+    /// An expression over variables, e.g. `Expr("score >= 10 && !has_key")`.
+    Expr(Expression),
+    /// Holds with this probability, rolled with the game's seeded RNG each
+    /// time it is checked: `Random(0.25)`.
+    Random(f32),
+    Not(Box<Condition>),
+    All(Vec<Condition>),
+    Any(Vec<Condition>),
+}
+
+#[derive(Resource)]
+pub struct GlobalLogicHandle(pub Handle<GlobalLogic>);
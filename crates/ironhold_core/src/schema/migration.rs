@@ -0,0 +1,334 @@
+use bevy::asset::LoadContext;
+use std::fmt;
+
+/// Version assumed for documents written before `schema_version` existed.
+pub const LEGACY_SCHEMA_VERSION: u32 = 0;
+
+/// A single upgrade step: rewrites a RON document from `from` to `from + 1`.
+///
+/// Steps only transform the content; the loader updates `schema_version`
+/// itself once a step succeeds.
+pub struct Migration {
+    pub from: u32,
+    pub apply: fn(&str) -> Result<String, String>,
+}
+
+/// A RON asset that carries a `schema_version` and knows how to upgrade
+/// older documents to its current shape.
+pub trait VersionedAsset: Sized {
+    /// Human readable asset kind used in error messages.
+    const KIND: &'static str;
+    /// The version this build of the engine deserializes.
+    const CURRENT_VERSION: u32;
+    /// Migration registry, one step per version below `CURRENT_VERSION`.
+    /// Assets older than `schema_version` read version 0 documents with an
+    /// `unchanged` step to 1.
+    const MIGRATIONS: &'static [Migration];
+
+    /// Deserializes a document already migrated to `CURRENT_VERSION`.
+    fn parse(document: &str) -> Result<Self, SchemaError>;
+
+    /// Starts loading the assets this one refers to, so that it only counts
+    /// as loaded with dependencies once they are.
+    fn load_dependencies(&mut self, _load_context: &mut LoadContext) {}
+}
+
+#[derive(Debug)]
+pub enum SchemaError {
+    Utf8(std::str::Utf8Error),
+    Parse(ron::error::SpannedError),
+    InvalidVersion(String),
+    UnsupportedVersion {
+        kind: &'static str,
+        found: u32,
+        current: u32,
+    },
+    MissingMigration {
+        kind: &'static str,
+        from: u32,
+    },
+    Migration {
+        kind: &'static str,
+        from: u32,
+        message: String,
+    },
+    Parameter(String),
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaError::Utf8(e) => write!(f, "file is not valid UTF-8: {}", e),
+            SchemaError::Parse(e) => write!(f, "RON parse error: {}", e),
+            SchemaError::InvalidVersion(found) => {
+                write!(f, "schema_version must be a non-negative integer, found {}", found)
+            }
+            SchemaError::UnsupportedVersion { kind, found, current } => write!(
+                f,
+                "{} has schema_version {} but this engine only supports up to {}; update the engine or downgrade the file",
+                kind, found, current
+            ),
+            SchemaError::MissingMigration { kind, from } => {
+                write!(f, "no migration registered for {} schema_version {}", kind, from)
+            }
+            SchemaError::Migration { kind, from, message } => write!(
+                f,
+                "failed to migrate {} from schema_version {}: {}",
+                kind, from, message
+            ),
+            SchemaError::Parameter(message) => write!(f, "prefab parameter error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+impl SchemaError {
+    /// Line and column (1-based) of the offending RON, for parse errors.
+    pub fn position(&self) -> Option<(usize, usize)> {
+        match self {
+            SchemaError::Parse(e) => Some((e.span.start.line, e.span.start.col)),
+            _ => None,
+        }
+    }
+
+    /// The error text without the position prefix that RON adds.
+    pub fn message(&self) -> String {
+        match self {
+            SchemaError::Parse(e) => e.code.to_string(),
+            other => other.to_string(),
+        }
+    }
+}
+
+impl From<ron::error::SpannedError> for SchemaError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        SchemaError::Parse(e)
+    }
+}
+
+/// Reads `schema_version` from a RON document without deserializing the rest.
+/// Documents without the field are treated as `LEGACY_SCHEMA_VERSION`.
+pub fn read_schema_version(source: &str) -> Result<u32, SchemaError> {
+    let value: ron::Value = ron::from_str(source)?;
+    let ron::Value::Map(map) = value else {
+        return Ok(LEGACY_SCHEMA_VERSION);
+    };
+    match map.get(&ron::Value::String("schema_version".to_string())) {
+        None => Ok(LEGACY_SCHEMA_VERSION),
+        Some(value) => value.clone().into_rust::<u32>().map_err(|_| {
+            SchemaError::InvalidVersion(ron::to_string(value).unwrap_or_else(|_| format!("{:?}", value)))
+        }),
+    }
+}
+
+/// Upgrades a RON document step by step until it matches `A::CURRENT_VERSION`.
+pub fn migrate_document<A: VersionedAsset>(source: &str) -> Result<String, SchemaError> {
+    let mut version = read_schema_version(source)?;
+    if version > A::CURRENT_VERSION {
+        return Err(SchemaError::UnsupportedVersion {
+            kind: A::KIND,
+            found: version,
+            current: A::CURRENT_VERSION,
+        });
+    }
+
+    let mut document = source.to_string();
+    while version < A::CURRENT_VERSION {
+        let step = A::MIGRATIONS
+            .iter()
+            .find(|m| m.from == version)
+            .ok_or(SchemaError::MissingMigration { kind: A::KIND, from: version })?;
+        let migrated = (step.apply)(&document).map_err(|message| SchemaError::Migration {
+            kind: A::KIND,
+            from: version,
+            message,
+        })?;
+        version += 1;
+        document = set_schema_version(&migrated, version).map_err(|message| SchemaError::Migration {
+            kind: A::KIND,
+            from: version - 1,
+            message,
+        })?;
+    }
+    Ok(document)
+}
+
+/// Migrates and deserializes a versioned RON document.
+pub fn from_versioned_str<A: VersionedAsset>(source: &str) -> Result<A, SchemaError> {
+    let document = migrate_document::<A>(source)?;
+    A::parse(&document)
+}
+
+/// Same as `from_versioned_str`, for raw file contents.
+pub fn from_versioned_bytes<A: VersionedAsset>(bytes: &[u8]) -> Result<A, SchemaError> {
+    let source = std::str::from_utf8(bytes).map_err(SchemaError::Utf8)?;
+    from_versioned_str(source)
+}
+
+/// Migration step that leaves the document untouched. Used when a version bump
+/// only adds optional fields.
+pub fn unchanged(source: &str) -> Result<String, String> {
+    Ok(source.to_string())
+}
+
+/// Sets (or inserts) the top-level `schema_version` field of a RON struct.
+pub fn set_schema_version(source: &str, version: u32) -> Result<String, String> {
+    let scan = scan_top_level(source)?;
+    let mut out = String::with_capacity(source.len() + 24);
+    match scan.schema_version {
+        Some((start, end)) => {
+            out.push_str(&source[..start]);
+            out.push_str(&version.to_string());
+            out.push_str(&source[end..]);
+        }
+        None => {
+            let insert_at = scan.open_paren + 1;
+            out.push_str(&source[..insert_at]);
+            out.push_str(&format!("\n    schema_version: {},", version));
+            out.push_str(&source[insert_at..]);
+        }
+    }
+    Ok(out)
+}
+
+struct TopLevelScan {
+    /// Byte offset of the `(` opening the top-level struct.
+    open_paren: usize,
+    /// Byte range of the `schema_version` value, if the field exists.
+    schema_version: Option<(usize, usize)>,
+}
+
+/// Minimal RON scanner: finds the top-level struct and its `schema_version`
+/// value while skipping comments, attributes and string literals.
+fn scan_top_level(source: &str) -> Result<TopLevelScan, String> {
+    let bytes = source.as_bytes();
+    let mut i = 0;
+    let mut depth = 0usize;
+    let mut open_paren = None;
+    let mut schema_version = None;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i += 2;
+                while i + 1 < bytes.len() && !(bytes[i] == b'*' && bytes[i + 1] == b'/') {
+                    i += 1;
+                }
+                i += 2;
+            }
+            b'"' => i = skip_string(bytes, i),
+            b'r' if matches!(bytes.get(i + 1), Some(b'"') | Some(b'#'))
+                && (i == 0 || !is_ident_byte(bytes[i - 1])) =>
+            {
+                i = skip_raw_string(bytes, i);
+            }
+            b'\'' => i = skip_char(bytes, i),
+            b'#' if depth == 0 => {
+                // Extension attributes such as `#![enable(implicit_some)]`.
+                while i < bytes.len() && bytes[i] != b']' {
+                    i += 1;
+                }
+                i += 1;
+            }
+            b'(' | b'[' | b'{' => {
+                if depth == 0 && bytes[i] == b'(' && open_paren.is_none() {
+                    open_paren = Some(i);
+                }
+                depth += 1;
+                i += 1;
+            }
+            b')' | b']' | b'}' => {
+                depth = depth.saturating_sub(1);
+                i += 1;
+            }
+            c if depth == 1 && is_ident_byte(c) && (i == 0 || !is_ident_byte(bytes[i - 1])) => {
+                let start = i;
+                while i < bytes.len() && is_ident_byte(bytes[i]) {
+                    i += 1;
+                }
+                if &source[start..i] == "schema_version" && schema_version.is_none() {
+                    let mut j = skip_whitespace(bytes, i);
+                    if bytes.get(j) == Some(&b':') {
+                        j = skip_whitespace(bytes, j + 1);
+                        let value_start = j;
+                        while j < bytes.len() && (bytes[j].is_ascii_alphanumeric() || bytes[j] == b'_' || bytes[j] == b'-') {
+                            j += 1;
+                        }
+                        schema_version = Some((value_start, j));
+                        i = j;
+                    }
+                }
+            }
+            _ => i += 1,
+        }
+    }
+
+    let open_paren = open_paren.ok_or_else(|| "document is not a RON struct".to_string())?;
+    Ok(TopLevelScan { open_paren, schema_version })
+}
+
+pub(crate) fn is_ident_byte(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_'
+}
+
+pub(crate) fn skip_whitespace(bytes: &[u8], mut i: usize) -> usize {
+    while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+        i += 1;
+    }
+    i
+}
+
+pub(crate) fn skip_string(bytes: &[u8], mut i: usize) -> usize {
+    i += 1;
+    while i < bytes.len() && bytes[i] != b'"' {
+        if bytes[i] == b'\\' {
+            i += 1;
+        }
+        i += 1;
+    }
+    i + 1
+}
+
+pub(crate) fn skip_char(bytes: &[u8], mut i: usize) -> usize {
+    i += 1;
+    while i < bytes.len() && bytes[i] != b'\'' {
+        if bytes[i] == b'\\' {
+            i += 1;
+        }
+        i += 1;
+    }
+    i + 1
+}
+
+/// End of the comment starting at `i`: the newline ending a `//` comment,
+/// or just past the `*/` of a block comment.
+pub(crate) fn skip_comment(bytes: &[u8], i: usize) -> usize {
+    if bytes.get(i + 1) == Some(&b'/') {
+        bytes[i..].iter().position(|&b| b == b'\n').map_or(bytes.len(), |offset| i + offset)
+    } else {
+        bytes[i + 2..].windows(2).position(|window| window == b"*/").map_or(bytes.len(), |offset| i + offset + 4)
+    }
+}
+
+pub(crate) fn skip_raw_string(bytes: &[u8], mut i: usize) -> usize {
+    i += 1;
+    let mut hashes = 0;
+    while bytes.get(i) == Some(&b'#') {
+        hashes += 1;
+        i += 1;
+    }
+    i += 1;
+    while i < bytes.len() {
+        if bytes[i] == b'"' && bytes[i + 1..].iter().take(hashes).filter(|&&b| b == b'#').count() == hashes {
+            return i + 1 + hashes;
+        }
+        i += 1;
+    }
+    i
+}
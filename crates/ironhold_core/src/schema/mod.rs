@@ -1,9 +1,33 @@
 pub mod project;
 pub mod level;
 pub mod player;
+pub mod input;
+pub mod recording;
+pub mod keys;
 pub mod ui;
+pub mod transform;
+pub mod entity;
+pub mod prefab;
+pub mod logic;
+pub mod behavior;
+pub mod value;
+pub mod expression;
+pub mod migration;
+pub mod loader;
 
 pub use project::*;
 pub use level::*;
 pub use player::*;
+pub use input::*;
+pub use recording::*;
+pub use keys::*;
 pub use ui::*;
+pub use transform::*;
+pub use entity::*;
+pub use prefab::*;
+pub use logic::*;
+pub use behavior::*;
+pub use value::*;
+pub use expression::*;
+pub use migration::*;
+pub use loader::*;
@@ -1,5 +1,6 @@
 use bevy::prelude::*;
 use serde::Deserialize;
+use crate::schema::keys::{self, deserialize_key};
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct PlayerConfig {
@@ -8,6 +9,133 @@ pub struct PlayerConfig {
     pub camera: CameraConfig,
     pub inputs: InputMap,
     pub animations: AnimationMap,
+    #[serde(default)]
+    pub movement: MovementConfig,
+}
+
+/// How a character moves: the player's `movement` block, and the fields of
+/// a `CharacterController` capability. Every field is optional.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct MovementConfig {
+    pub walk_speed: f32,
+    pub run_speed: f32,
+    pub crouch_speed: f32,
+    /// Speed of a roll, in its direction whatever the input.
+    pub roll_speed: f32,
+    pub roll_seconds: f32,
+    /// Turn rate, in radians per second.
+    pub rot_speed: f32,
+    /// How the character speeds up to its walk, run or crouch speed.
+    pub acceleration: SpeedRamp,
+    /// How the character slows down when the input is released.
+    pub deceleration: SpeedRamp,
+    /// Whether `run` toggles running or runs while held.
+    pub run_mode: ButtonMode,
+    /// Whether `crouch` toggles crouching or crouches while held.
+    pub crouch_mode: ButtonMode,
+    /// Speed multiplier when moving sideways.
+    pub strafe_multiplier: f32,
+    /// Speed multiplier when moving backwards.
+    pub backward_multiplier: f32,
+    /// Whether input moves the character along its own axes or the camera's.
+    pub steering: Steering,
+    /// Upward speed a jump starts with.
+    pub jump_speed: f32,
+    pub gravity: f32,
+    /// Share of the input's movement that applies in the air, 0 to 1.
+    pub air_control: f32,
+}
+
+impl Default for MovementConfig {
+    fn default() -> Self {
+        Self {
+            walk_speed: 3.0,
+            run_speed: 6.0,
+            crouch_speed: 1.5,
+            roll_speed: 7.0,
+            roll_seconds: 0.6,
+            rot_speed: 3.0,
+            acceleration: SpeedRamp::default(),
+            deceleration: SpeedRamp::default(),
+            run_mode: ButtonMode::Toggle,
+            crouch_mode: ButtonMode::Toggle,
+            strafe_multiplier: 1.0,
+            backward_multiplier: 1.0,
+            steering: Steering::Tank,
+            jump_speed: 5.0,
+            gravity: 9.81,
+            air_control: 0.3,
+        }
+    }
+}
+
+/// How movement input steers a character.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Steering {
+    /// `move` goes along the character's own axes and `turn` rotates it.
+    #[default]
+    Tank,
+    /// `move` and `turn` go along the axes of the `OrbitCamera` following the
+    /// character, which turns toward where it moves at `rot_speed`. Speed
+    /// multipliers and camera steering do not apply.
+    CameraRelative,
+}
+
+/// A change of speed between standing still and `run_speed`, taking
+/// `seconds` along `curve`. The default, zero seconds, changes speed at once.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct SpeedRamp {
+    #[serde(default)]
+    pub seconds: f32,
+    #[serde(default)]
+    pub curve: Easing,
+}
+
+impl SpeedRamp {
+    /// How far along the ramp a change is `dt` seconds after being at
+    /// `progress`, both as the share of the speed range covered (0 to 1).
+    pub fn advance(&self, progress: f32, dt: f32) -> f32 {
+        if self.seconds <= 0.0 {
+            return 1.0;
+        }
+        let time = self.curve.inverse(progress.clamp(0.0, 1.0));
+        self.curve.apply((time + dt / self.seconds).min(1.0))
+    }
+}
+
+/// Shape of a ramp: the share of the change covered at each share of its time.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Easing {
+    #[default]
+    Linear,
+    /// Starts slowly.
+    EaseIn,
+    /// Ends slowly.
+    EaseOut,
+    /// Starts and ends slowly.
+    EaseInOut,
+}
+
+impl Easing {
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+
+    /// The time at which the curve reaches `value`.
+    pub fn inverse(self, value: f32) -> f32 {
+        match self {
+            Easing::Linear => value,
+            Easing::EaseIn => value.sqrt(),
+            Easing::EaseOut => 1.0 - (1.0 - value).sqrt(),
+            Easing::EaseInOut => 0.5 - ((1.0 - 2.0 * value).asin() / 3.0).sin(),
+        }
+    }
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -20,24 +148,96 @@ pub struct CameraConfig {
     pub max_radius: f32,
 }
 
+/// Keys of the player's inputs, by `KeyCode` name or alias (see `keys`).
+/// Unknown key names are rejected when the scene is parsed. The keys only
+/// apply to projects without an `InputProfile`; a profile replaces them.
 #[derive(Deserialize, Debug, Clone)]
 pub struct InputMap {
+    #[serde(deserialize_with = "deserialize_key")]
     pub forward: String,
+    #[serde(deserialize_with = "deserialize_key")]
     pub backward: String,
+    #[serde(deserialize_with = "deserialize_key")]
     pub left: String,
+    #[serde(deserialize_with = "deserialize_key")]
     pub right: String,
+    #[serde(deserialize_with = "deserialize_key")]
     pub strafe_left: String,
+    #[serde(deserialize_with = "deserialize_key")]
     pub strafe_right: String,
+    #[serde(deserialize_with = "deserialize_key")]
     pub jump: String,
-    #[serde(default = "default_run_key")]
+    #[serde(default = "default_run_key", deserialize_with = "deserialize_key")]
     pub run: String,
+    #[serde(default = "default_interact_key", deserialize_with = "deserialize_key")]
+    pub interact: String,
+    #[serde(default = "default_crouch_key", deserialize_with = "deserialize_key")]
+    pub crouch: String,
+    #[serde(default = "default_roll_key", deserialize_with = "deserialize_key")]
+    pub roll: String,
+    /// Starts and stops the `dance` emote.
+    #[serde(default = "default_emote_key", deserialize_with = "deserialize_key")]
+    pub emote: String,
 }
 
 fn default_run_key() -> String {
     "ShiftLeft".to_string()
 }
 
+fn default_interact_key() -> String {
+    "KeyF".to_string()
+}
+
+fn default_crouch_key() -> String {
+    "KeyC".to_string()
+}
+
+fn default_roll_key() -> String {
+    "KeyR".to_string()
+}
+
+fn default_emote_key() -> String {
+    "KeyG".to_string()
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        Self {
+            forward: "KeyW".to_string(),
+            backward: "KeyS".to_string(),
+            left: "KeyA".to_string(),
+            right: "KeyD".to_string(),
+            strafe_left: "KeyQ".to_string(),
+            strafe_right: "KeyE".to_string(),
+            jump: "Space".to_string(),
+            run: default_run_key(),
+            interact: default_interact_key(),
+            crouch: default_crouch_key(),
+            roll: default_roll_key(),
+            emote: default_emote_key(),
+        }
+    }
+}
+
 impl InputMap {
+    /// All bindings as `(input name, key name)` pairs.
+    pub fn bindings(&self) -> [(&'static str, &str); 12] {
+        [
+            ("forward", &self.forward),
+            ("backward", &self.backward),
+            ("left", &self.left),
+            ("right", &self.right),
+            ("strafe_left", &self.strafe_left),
+            ("strafe_right", &self.strafe_right),
+            ("jump", &self.jump),
+            ("run", &self.run),
+            ("interact", &self.interact),
+            ("crouch", &self.crouch),
+            ("roll", &self.roll),
+            ("emote", &self.emote),
+        ]
+    }
+
     pub fn key(&self, name: &str) -> Option<KeyCode> {
         let s = match name {
             "forward" => &self.forward,
@@ -48,25 +248,51 @@ impl InputMap {
             "strafe_right" => &self.strafe_right,
             "jump" => &self.jump,
             "run" => &self.run,
+            "interact" => &self.interact,
+            "crouch" => &self.crouch,
+            "roll" => &self.roll,
+            "emote" => &self.emote,
             _ => return None,
         };
         Self::parse_key(s)
     }
     
-    pub fn parse_key(s: &str) -> Option<KeyCode> {
-        match s {
-            "KeyW" | "W" => Some(KeyCode::KeyW),
-            "KeyA" | "A" => Some(KeyCode::KeyA),
-            "KeyS" | "S" => Some(KeyCode::KeyS),
-            "KeyD" | "D" => Some(KeyCode::KeyD),
-            "KeyQ" | "Q" => Some(KeyCode::KeyQ),
-            "KeyE" | "E" => Some(KeyCode::KeyE),
-            "Space" => Some(KeyCode::Space),
-            "ShiftLeft" => Some(KeyCode::ShiftLeft),
-            "ShiftRight" => Some(KeyCode::ShiftRight),
-            _ => None,
+    /// Binds input `name` to `key`. Returns `false` for unknown inputs and keys.
+    pub fn set_key(&mut self, name: &str, key: &str) -> bool {
+        if keys::parse_key(key).is_err() {
+            return false;
         }
+        let field = match name {
+            "forward" => &mut self.forward,
+            "backward" => &mut self.backward,
+            "left" => &mut self.left,
+            "right" => &mut self.right,
+            "strafe_left" => &mut self.strafe_left,
+            "strafe_right" => &mut self.strafe_right,
+            "jump" => &mut self.jump,
+            "run" => &mut self.run,
+            "interact" => &mut self.interact,
+            "crouch" => &mut self.crouch,
+            "roll" => &mut self.roll,
+            "emote" => &mut self.emote,
+            _ => return false,
+        };
+        *field = key.to_string();
+        true
     }
+
+    pub fn parse_key(s: &str) -> Option<KeyCode> {
+        keys::parse_key(s).ok()
+    }
+}
+
+/// How a held input works: `Toggle` switches on each press, `Hold` is on
+/// while the input is held.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ButtonMode {
+    #[default]
+    Toggle,
+    Hold,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -0,0 +1,235 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+use crate::schema::entity::{nest_character_movement, CapabilityBlock, EntityDef};
+use crate::schema::transform::{Rotation, Scale};
+use crate::schema::migration::{
+    is_ident_byte, skip_char, skip_comment, skip_raw_string, skip_string, skip_whitespace, unchanged,
+    Migration, SchemaError, VersionedAsset,
+};
+
+/// A reusable entity template: a model, capability blocks and children.
+///
+/// Values in the template can be written as `Param("name")` placeholders,
+/// declared with a default in `params` and overridden per instance:
+///
+/// ```ron
+/// (
+///     schema_version: 2,
+///     params: { "speed": 3.0 },
+///     model: Some("models/npc.glb#Scene0"),
+///     capabilities: [CharacterController(movement: (walk_speed: Param("speed")))],
+/// )
+/// ```
+#[derive(Asset, TypePath, Debug, Clone)]
+pub struct Prefab {
+    /// Parameter defaults.
+    pub params: HashMap<String, ParamValue>,
+    /// The template with default parameters applied.
+    pub template: PrefabTemplate,
+    /// Migrated source, kept to re-instantiate with overridden parameters.
+    source: String,
+}
+
+impl VersionedAsset for Prefab {
+    const KIND: &'static str = "Prefab";
+    const CURRENT_VERSION: u32 = 2;
+    const MIGRATIONS: &'static [Migration] = &[
+        Migration { from: 0, apply: unchanged },
+        // v1 -> v2: `CharacterController` movement fields move into `movement`.
+        Migration { from: 1, apply: nest_character_movement },
+    ];
+
+    fn parse(document: &str) -> Result<Self, SchemaError> {
+        let header: PrefabHeader = ron::from_str(document)?;
+        let template = instantiate_template(document, &header.params)?;
+        Ok(Prefab {
+            params: header.params,
+            template,
+            source: document.to_string(),
+        })
+    }
+}
+
+impl Prefab {
+    /// Resolves `instance` into its root entity and children. The root gets
+    /// `id`; children get `"<id>.<child id>"`.
+    pub fn instantiate(&self, instance: &PrefabInstance, id: &str) -> Result<(EntityDef, Vec<EntityDef>), SchemaError> {
+        let template = if instance.params.is_empty() {
+            self.template.clone()
+        } else {
+            for name in instance.params.keys() {
+                if !self.params.contains_key(name) {
+                    return Err(SchemaError::Parameter(format!("unknown parameter \"{}\"", name)));
+                }
+            }
+            let mut params = self.params.clone();
+            params.extend(instance.params.iter().map(|(k, v)| (k.clone(), v.clone())));
+            instantiate_template(&self.source, &params)?
+        };
+
+        let root = EntityDef {
+            id: id.to_string(),
+            model: template.model,
+            position: instance.position,
+            rotation: instance.rotation.unwrap_or(template.rotation),
+            scale: instance.scale.unwrap_or(template.scale),
+            capabilities: template.capabilities,
+        };
+        let children = template
+            .children
+            .into_iter()
+            .map(|child| EntityDef {
+                id: format!("{}.{}", id, child.id),
+                ..child
+            })
+            .collect();
+        Ok((root, children))
+    }
+}
+
+#[derive(Deserialize)]
+struct PrefabHeader {
+    #[serde(default)]
+    params: HashMap<String, ParamValue>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct PrefabTemplate {
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub rotation: Rotation,
+    #[serde(default)]
+    pub scale: Scale,
+    #[serde(default)]
+    pub capabilities: Vec<CapabilityBlock>,
+    /// Spawned as children of the root, positioned relative to it.
+    #[serde(default)]
+    pub children: Vec<EntityDef>,
+}
+
+/// A prefab parameter value.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum ParamValue {
+    Bool(bool),
+    Number(f64),
+    Text(String),
+    Vec3(f32, f32, f32),
+}
+
+impl ParamValue {
+    /// The value as a RON literal.
+    pub fn to_ron(&self) -> String {
+        match self {
+            ParamValue::Bool(b) => b.to_string(),
+            ParamValue::Number(n) => format!("{:?}", n),
+            ParamValue::Text(s) => format!("{:?}", s),
+            ParamValue::Vec3(x, y, z) => format!("({:?}, {:?}, {:?})", x, y, z),
+        }
+    }
+}
+
+/// Places a prefab in a scene.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct PrefabInstance {
+    /// Path of the prefab file, e.g. `"prefabs/player.ron"`.
+    pub prefab: String,
+    /// Defaults to the prefab file name followed by the instance index.
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub position: (f32, f32, f32),
+    /// Overrides the prefab's rotation.
+    #[serde(default)]
+    pub rotation: Option<Rotation>,
+    /// Overrides the prefab's scale.
+    #[serde(default)]
+    pub scale: Option<Scale>,
+    #[serde(default)]
+    pub params: HashMap<String, ParamValue>,
+}
+
+impl PrefabInstance {
+    /// `id`, or `"<prefab file stem>_<index>"` if unset.
+    pub fn id_or_default(&self, index: usize) -> String {
+        self.id.clone().unwrap_or_else(|| {
+            let stem = Path::new(&self.prefab)
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or("prefab");
+            format!("{}_{}", stem, index)
+        })
+    }
+}
+
+fn instantiate_template(source: &str, params: &HashMap<String, ParamValue>) -> Result<PrefabTemplate, SchemaError> {
+    let document = substitute(source, &|name| params.get(name).map(ParamValue::to_ron))?;
+    Ok(ron::from_str(&document)?)
+}
+
+/// Replaces `Param("name")` placeholders outside literals and comments with
+/// the literal returned by `lookup`.
+fn substitute(source: &str, lookup: &dyn Fn(&str) -> Option<String>) -> Result<String, SchemaError> {
+    let bytes = source.as_bytes();
+    let mut out = String::with_capacity(source.len());
+    let mut copied = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => i = skip_string(bytes, i),
+            b'\'' => i = skip_char(bytes, i),
+            b'/' if matches!(bytes.get(i + 1), Some(b'/' | b'*')) => i = skip_comment(bytes, i),
+            c if is_ident_byte(c) => {
+                let start = i;
+                while i < bytes.len() && is_ident_byte(bytes[i]) {
+                    i += 1;
+                }
+                let ident = &source[start..i];
+                if ident == "r" && matches!(bytes.get(i), Some(b'"' | b'#')) {
+                    i = skip_raw_string(bytes, i - 1);
+                    continue;
+                }
+                if ident != "Param" {
+                    continue;
+                }
+                let Some((name, end)) = placeholder_name(source, i) else {
+                    continue;
+                };
+                let Some(value) = lookup(name) else {
+                    return Err(SchemaError::Parameter(format!("parameter \"{}\" is not declared in `params`", name)));
+                };
+                out.push_str(&source[copied..start]);
+                out.push_str(&value);
+                copied = end;
+                i = end;
+            }
+            _ => i += 1,
+        }
+    }
+
+    out.push_str(&source[copied..]);
+    Ok(out)
+}
+
+/// Parses `("name")` at `i`, returning the name and the end offset.
+fn placeholder_name(source: &str, i: usize) -> Option<(&str, usize)> {
+    let bytes = source.as_bytes();
+    let open = skip_whitespace(bytes, i);
+    if bytes.get(open) != Some(&b'(') {
+        return None;
+    }
+    let quote = skip_whitespace(bytes, open + 1);
+    if bytes.get(quote) != Some(&b'"') {
+        return None;
+    }
+    let name_end = quote + 1 + source[quote + 1..].find('"')?;
+    let close = skip_whitespace(bytes, name_end + 1);
+    if bytes.get(close) != Some(&b')') {
+        return None;
+    }
+    Some((&source[quote + 1..name_end], close + 1))
+}
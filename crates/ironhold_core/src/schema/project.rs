@@ -1,5 +1,8 @@
 use bevy::prelude::*;
 use serde::Deserialize;
+use std::collections::HashMap;
+use crate::schema::value::Value;
+use crate::schema::migration::{unchanged, Migration, SchemaError, VersionedAsset};
 
 #[derive(Clone, Eq, PartialEq, Debug, Hash, Default, States)]
 pub enum AppState {
@@ -8,11 +11,49 @@ pub enum AppState {
     LoadingProject,
     LoadingScene,
     InGame,
+    Error,
 }
 
 #[derive(Deserialize, Asset, TypePath, Debug, Clone)]
 pub struct ProjectConfig {
+    pub schema_version: u32,
     pub initial_scene: String,
+    /// Re-spawn the active scene when its file changes on disk (native only).
+    #[serde(default)]
+    pub hot_reload: bool,
+    /// `GlobalLogic` file driving the project flow, e.g. `"logic/flow.ron"`.
+    #[serde(default)]
+    pub logic: Option<String>,
+    /// `InputProfile` file binding the project's actions, e.g. `"input/default.ron"`.
+    #[serde(default)]
+    pub input: Option<String>,
+    /// Project variables and their defaults, e.g. `{ "score": 0 }`.
+    #[serde(default)]
+    pub variables: HashMap<String, Value>,
+    /// Gameplay ticks per second. Movement, logic and actions advance in fixed
+    /// ticks of `1 / tick_rate` seconds, whatever the frame rate.
+    #[serde(default = "default_tick_rate")]
+    pub tick_rate: f64,
+    /// Seed of the game's random numbers. Without one every run is seeded
+    /// differently; replays always use the recorded seed.
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+fn default_tick_rate() -> f64 {
+    crate::runtime::tick::DEFAULT_TICK_RATE
+}
+
+impl VersionedAsset for ProjectConfig {
+    const KIND: &'static str = "ProjectConfig";
+    const CURRENT_VERSION: u32 = 1;
+    const MIGRATIONS: &'static [Migration] = &[
+        Migration { from: 0, apply: unchanged },
+    ];
+
+    fn parse(document: &str) -> Result<Self, SchemaError> {
+        Ok(ron::from_str(document)?)
+    }
 }
 
 #[derive(Resource)]
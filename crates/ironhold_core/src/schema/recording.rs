@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use crate::schema::migration::{Migration, SchemaError, VersionedAsset};
+
+/// Input actions captured frame by frame with `--record`, fed back in place
+/// of live input by `--replay`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InputRecording {
+    pub schema_version: u32,
+    /// Project file the recording was made with, relative to the assets folder.
+    pub project: String,
+    /// The project's `initial_scene` when recording started.
+    pub initial_scene: String,
+    /// Seed of the game's random numbers.
+    #[serde(default)]
+    pub seed: u64,
+    pub frames: Vec<RecordedFrame>,
+}
+
+impl InputRecording {
+    pub fn new(project: String, initial_scene: String, seed: u64) -> Self {
+        Self {
+            schema_version: Self::CURRENT_VERSION,
+            project,
+            initial_scene,
+            seed,
+            frames: Vec::new(),
+        }
+    }
+
+    /// Pretty RON with one frame per line.
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default().depth_limit(2))
+    }
+}
+
+impl VersionedAsset for InputRecording {
+    const KIND: &'static str = "InputRecording";
+    const CURRENT_VERSION: u32 = 1;
+    const MIGRATIONS: &'static [Migration] = &[];
+
+    fn parse(document: &str) -> Result<Self, SchemaError> {
+        Ok(ron::from_str(document)?)
+    }
+}
+
+/// One in-game frame of a recording.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct RecordedFrame {
+    /// Seconds the frame advanced the game by.
+    pub delta: f64,
+    /// Actions with a non-zero value as `(x, y)`; `y` is only used by `Axis2d`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub actions: BTreeMap<String, (f32, f32)>,
+}
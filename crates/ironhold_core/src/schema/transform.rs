@@ -0,0 +1,60 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+/// Rotation as written in RON: `Euler(x, y, z)` in degrees (applied in
+/// Y, X, Z order) or a raw quaternion `Quat(x, y, z, w)`.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Rotation {
+    Euler(f32, f32, f32),
+    Quat(f32, f32, f32, f32),
+}
+
+impl Default for Rotation {
+    fn default() -> Self {
+        Rotation::Euler(0.0, 0.0, 0.0)
+    }
+}
+
+impl Rotation {
+    pub fn to_quat(self) -> Quat {
+        match self {
+            Rotation::Euler(x, y, z) => Quat::from_euler(
+                EulerRot::YXZ,
+                y.to_radians(),
+                x.to_radians(),
+                z.to_radians(),
+            ),
+            Rotation::Quat(x, y, z, w) => Quat::from_xyzw(x, y, z, w).normalize(),
+        }
+    }
+}
+
+/// Scale as written in RON: `Uniform(2.0)` or `PerAxis(1.0, 2.0, 1.0)`.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Scale {
+    Uniform(f32),
+    PerAxis(f32, f32, f32),
+}
+
+impl Default for Scale {
+    fn default() -> Self {
+        Scale::Uniform(1.0)
+    }
+}
+
+impl Scale {
+    pub fn to_vec3(self) -> Vec3 {
+        match self {
+            Scale::Uniform(s) => Vec3::splat(s),
+            Scale::PerAxis(x, y, z) => Vec3::new(x, y, z),
+        }
+    }
+}
+
+pub fn to_transform(position: (f32, f32, f32), rotation: Rotation, scale: Scale) -> Transform {
+    Transform {
+        translation: Vec3::from(position),
+        rotation: rotation.to_quat(),
+        scale: scale.to_vec3(),
+    }
+}
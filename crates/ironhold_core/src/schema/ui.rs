@@ -1,15 +1,69 @@
 use bevy::prelude::*;
 use serde::Deserialize;
+use crate::runtime::actions::Action;
+use crate::schema::logic::Condition;
+use crate::schema::migration::{unchanged, Migration, SchemaError, VersionedAsset};
 
 #[derive(Deserialize, Debug, Clone)]
 pub enum UiElement {
     Button {
         text: String,
         action: UiAction,
+        /// Shown only while the condition holds, e.g. `Some(Expr("has_key"))`.
+        #[serde(default)]
+        visible: Option<Condition>,
+    },
+    /// Shows what `input` is bound to. Clicking it binds the next key, mouse
+    /// or gamepad button pressed instead; Escape cancels. `input` is an
+    /// `InputMap` input or an action, see `InputProfile::rebind`.
+    Rebind {
+        input: String,
+        /// Label shown before the binding; defaults to the input name.
+        #[serde(default)]
+        text: Option<String>,
     },
 }
 
+/// Hides the UI node while its condition is false.
+#[derive(Component, Debug, Clone)]
+pub struct UiVisibility(pub Condition);
+
 #[derive(Deserialize, Debug, Clone, Component)]
 pub enum UiAction {
     LoadScene(String),
+    /// Sends a named event to the global logic, e.g. `Emit("ui.start")`.
+    Emit(String),
+    /// Runs actions directly, e.g. `Run([PlaySound("sounds/click.ogg"), CloseUi("menus/options.ron")])`.
+    Run(Vec<Action>),
+}
+
+impl UiAction {
+    /// Actions the button queues when pressed. `Emit` buttons queue none and
+    /// send their event instead.
+    pub fn actions(&self) -> Vec<Action> {
+        match self {
+            UiAction::Emit(_) => Vec::new(),
+            UiAction::LoadScene(path) => vec![Action::LoadScene(path.clone())],
+            UiAction::Run(actions) => actions.clone(),
+        }
+    }
+}
+
+/// UI elements shown over the current scene by `Action::OpenUi`.
+#[derive(Deserialize, Asset, TypePath, Debug, Clone)]
+pub struct UiMenu {
+    pub schema_version: u32,
+    pub elements: Vec<UiElement>,
+}
+
+impl VersionedAsset for UiMenu {
+    const KIND: &'static str = "UiMenu";
+    const CURRENT_VERSION: u32 = 1;
+    const MIGRATIONS: &'static [Migration] = &[
+        Migration { from: 0, apply: unchanged },
+    ];
+
+    fn parse(document: &str) -> Result<Self, SchemaError> {
+        Ok(ron::from_str(document)?)
+    }
 }
@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+
+/// A typed value held by a variable, e.g. a behavior's locals.
+///
+/// Written as a plain RON literal: `true`, `3`, `2.5`, `"text"` or `(0.0, 1.0, 0.0)`.
+/// Integers without a decimal point are `Int`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum Value {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Vec3(f32, f32, f32),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Int(i) => write!(f, "{}", i),
+            Value::Float(x) => write!(f, "{:?}", x),
+            Value::String(s) => write!(f, "{:?}", s),
+            Value::Vec3(x, y, z) => write!(f, "({:?}, {:?}, {:?})", x, y, z),
+        }
+    }
+}
+
+impl Value {
+    /// Ints and floats as `f64`.
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            Value::Int(i) => Some(*i as f64),
+            Value::Float(x) => Some(*x),
+            _ => None,
+        }
+    }
+
+    /// `false`, zero and the empty string are false; everything else is true.
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::Bool(b) => *b,
+            Value::Int(i) => *i != 0,
+            Value::Float(x) => *x != 0.0,
+            Value::String(s) => !s.is_empty(),
+            Value::Vec3(..) => true,
+        }
+    }
+
+    /// Numbers compare by value and strings alphabetically. Other values,
+    /// and values of different types, are only ordered when equal.
+    pub fn partial_cmp_value(&self, other: &Value) -> Option<Ordering> {
+        match (self.as_number(), other.as_number()) {
+            (Some(a), Some(b)) => a.partial_cmp(&b),
+            _ => match (self, other) {
+                (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+                _ if self == other => Some(Ordering::Equal),
+                _ => None,
+            },
+        }
+    }
+
+    /// `self + other` for two numbers (an int unless either is a float) or
+    /// two vectors.
+    pub fn try_add(&self, other: &Value) -> Option<Value> {
+        match (self, other) {
+            (Value::Int(a), Value::Int(b)) => Some(Value::Int(a.wrapping_add(*b))),
+            (Value::Vec3(x, y, z), Value::Vec3(dx, dy, dz)) => Some(Value::Vec3(x + dx, y + dy, z + dz)),
+            _ => Some(Value::Float(self.as_number()? + other.as_number()?)),
+        }
+    }
+}
@@ -0,0 +1,677 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use crate::schema::*;
+use crate::runtime::actions::Action;
+use crate::runtime::save::check_slot;
+use crate::runtime::entities::{PLAYER_CAMERA_ENTITY_ID, PLAYER_ENTITY_ID};
+
+/// A single problem found while validating a project.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub file: PathBuf,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.file.display())?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+            if let Some(column) = self.column {
+                write!(f, ":{}", column)?;
+            }
+        }
+        write!(f, ": error: {}", self.message)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    /// Scene paths (relative to the assets folder) that were visited.
+    pub scenes: Vec<String>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl ValidationReport {
+    pub fn has_errors(&self) -> bool {
+        !self.diagnostics.is_empty()
+    }
+}
+
+/// Validates a project offline: loads `project_file` and its global logic,
+/// follows `initial_scene`, every `LoadScene` target and scene include, and
+/// checks referenced assets, prefabs, behaviors and key bindings.
+/// Asset paths are resolved against `assets_root`.
+pub fn validate_project(project_file: &Path, assets_root: &Path) -> ValidationReport {
+    let mut report = ValidationReport::default();
+
+    let Some(source) = read_file(project_file, &mut report) else {
+        return report;
+    };
+    let project = match from_versioned_str::<ProjectConfig>(&source) {
+        Ok(project) => project,
+        Err(e) => {
+            report.diagnostics.push(schema_diagnostic(project_file, &e));
+            return report;
+        }
+    };
+
+    let mut locator = Locator::new(&source);
+    let mut queue = VecDeque::new();
+    let mut visited = HashSet::new();
+    let mut prefabs = HashMap::new();
+    let mut behaviors = HashSet::new();
+    let mut events = EventUses::default();
+    if project.tick_rate <= 0.0 {
+        report.diagnostics.push(Diagnostic {
+            file: project_file.to_path_buf(),
+            line: None,
+            column: None,
+            message: format!("tick_rate must be positive, got {}", project.tick_rate),
+        });
+    }
+    check_scene_reference(&project.initial_scene, project_file, &mut locator, assets_root, &mut report, &mut queue);
+    if let Some(logic) = &project.logic {
+        let (line, column) = locator.find(logic);
+        let logic_file = assets_root.join(logic);
+        if logic_file.is_file() {
+            validate_logic(&logic_file, assets_root, &mut report, &mut queue, &mut events);
+        } else {
+            report.diagnostics.push(Diagnostic {
+                file: project_file.to_path_buf(),
+                line,
+                column,
+                message: format!("logic \"{}\" not found under {}", logic, assets_root.display()),
+            });
+        }
+    }
+
+    if let Some(input) = &project.input {
+        let (line, column) = locator.find(input);
+        let input_file = assets_root.join(input);
+        if input_file.is_file() {
+            validate_input_profile(&input_file, &mut report);
+        } else {
+            report.diagnostics.push(Diagnostic {
+                file: project_file.to_path_buf(),
+                line,
+                column,
+                message: format!("input profile \"{}\" not found under {}", input, assets_root.display()),
+            });
+        }
+    }
+
+    while let Some(scene) = queue.pop_front() {
+        if !visited.insert(scene.clone()) {
+            continue;
+        }
+        report.scenes.push(scene.clone());
+        validate_scene(&scene, assets_root, &mut report, &mut queue, &mut prefabs, &mut behaviors, &mut events);
+    }
+    events.check(&mut report);
+
+    report
+}
+
+/// Event names raised by buttons and `EmitEvent`, and those the global logic
+/// and `WaitForEvent` listen for. They are compared once the whole project
+/// has been visited.
+#[derive(Default)]
+struct EventUses {
+    listened: HashSet<String>,
+    raised: Vec<(String, Diagnostic)>,
+}
+
+impl EventUses {
+    fn raise(&mut self, name: &str, file: &Path, locator: &mut Locator) {
+        let (line, column) = locator.find(name);
+        self.raised.push((
+            name.to_string(),
+            Diagnostic {
+                file: file.to_path_buf(),
+                line,
+                column,
+                message: format!("unknown event \"{}\": no logic transition or WaitForEvent listens for it", name),
+            },
+        ));
+    }
+
+    fn check(self, report: &mut ValidationReport) {
+        for (name, diagnostic) in self.raised {
+            if !self.listened.contains(&name) {
+                report.diagnostics.push(diagnostic);
+            }
+        }
+    }
+}
+
+fn validate_scene(
+    scene: &str,
+    assets_root: &Path,
+    report: &mut ValidationReport,
+    queue: &mut VecDeque<String>,
+    prefabs: &mut HashMap<String, Option<Prefab>>,
+    behaviors: &mut HashSet<String>,
+    events: &mut EventUses,
+) {
+    let file = assets_root.join(scene);
+    let Some(source) = read_file(&file, report) else {
+        return;
+    };
+    let level = match from_versioned_str::<GameLevel>(&source) {
+        Ok(level) => level,
+        Err(e) => {
+            report.diagnostics.push(schema_diagnostic(&file, &e));
+            return;
+        }
+    };
+
+    let mut locator = Locator::new(&source);
+
+    for include in &level.includes {
+        check_scene_reference(include, &file, &mut locator, assets_root, report, queue);
+    }
+
+    // Ids and camera targets are checked across the scene and its includes.
+    // This file's own prefab instances come last in the flattened level.
+    let flat = level.flattened(scene, &|path| {
+        let source = std::fs::read_to_string(assets_root.join(path)).ok()?;
+        from_versioned_str::<GameLevel>(&source).ok()
+    });
+    let own_prefabs = flat.prefabs.len() - level.prefabs.len();
+
+    let mut prefab_defs = Vec::new();
+    let mut own_prefab_defs = Vec::new();
+    for (index, instance) in flat.prefabs.iter().enumerate() {
+        let Some(prefab) = load_prefab(&instance.prefab, &file, &mut locator, assets_root, report, prefabs) else {
+            continue;
+        };
+        match prefab.instantiate(instance, &instance.id_or_default(index)) {
+            Ok((root, children)) => {
+                let defs = if index >= own_prefabs { &mut own_prefab_defs } else { &mut prefab_defs };
+                defs.push(root);
+                defs.extend(children);
+            }
+            Err(e) if index >= own_prefabs => {
+                let (line, column) = locator.find(&instance.prefab);
+                report.diagnostics.push(Diagnostic {
+                    file: file.clone(),
+                    line,
+                    column,
+                    message: format!("prefab \"{}\": {}", instance.prefab, e.message()),
+                });
+            }
+            Err(_) => {}
+        }
+    }
+
+    let mut ids = HashSet::new();
+    if flat.player.is_some() {
+        ids.insert(PLAYER_ENTITY_ID);
+        ids.insert(PLAYER_CAMERA_ENTITY_ID);
+    }
+    for model in &flat.models {
+        if let Some(id) = &model.id {
+            check_unique_id(id, &mut ids, &file, &mut locator, report);
+        }
+    }
+    for def in flat.entities.iter().chain(&prefab_defs).chain(&own_prefab_defs) {
+        check_unique_id(&def.id, &mut ids, &file, &mut locator, report);
+    }
+
+    for model in &level.models {
+        check_asset(&model.path, &file, &mut locator, assets_root, report);
+    }
+    for entity in &level.entities {
+        check_entity(entity, &file, &mut locator, assets_root, report);
+    }
+    for def in level.entities.iter().chain(&own_prefab_defs) {
+        check_camera_target(def, &ids, &file, &mut locator, report);
+    }
+    for def in flat.entities.iter().chain(&prefab_defs).chain(&own_prefab_defs) {
+        for block in &def.capabilities {
+            if let CapabilityBlock::Behavior { machine, .. } = block {
+                validate_behavior(machine, assets_root, report, queue, behaviors, events);
+            }
+        }
+    }
+
+    for element in &level.ui {
+        match element {
+            UiElement::Button { action, .. } => match action {
+                UiAction::LoadScene(target) => {
+                    check_scene_reference(target, &file, &mut locator, assets_root, report, queue);
+                }
+                UiAction::Emit(event) => events.raise(event, &file, &mut locator),
+                UiAction::Run(actions) => {
+                    for action in actions {
+                        check_action(action, &file, &mut locator, assets_root, report, queue, events);
+                    }
+                }
+            },
+            UiElement::Rebind { .. } => {}
+        }
+    }
+
+    if let Some(player) = &level.player {
+        check_asset(&player.model_path, &file, &mut locator, assets_root, report);
+    }
+}
+
+/// Loads and checks a prefab the first time it is referenced. Problems inside
+/// the prefab are reported against the prefab file, once.
+fn load_prefab(
+    path: &str,
+    file: &Path,
+    locator: &mut Locator,
+    assets_root: &Path,
+    report: &mut ValidationReport,
+    prefabs: &mut HashMap<String, Option<Prefab>>,
+) -> Option<Prefab> {
+    if let Some(prefab) = prefabs.get(path) {
+        return prefab.clone();
+    }
+
+    let prefab_file = assets_root.join(path);
+    let prefab = if prefab_file.is_file() {
+        read_file(&prefab_file, report).and_then(|source| match from_versioned_str::<Prefab>(&source) {
+            Ok(prefab) => {
+                let mut prefab_locator = Locator::new(&source);
+                if let Ok((root, children)) = prefab.instantiate(&PrefabInstance::default(), path) {
+                    for def in std::iter::once(&root).chain(&children) {
+                        check_entity(def, &prefab_file, &mut prefab_locator, assets_root, report);
+                    }
+                }
+                Some(prefab)
+            }
+            Err(e) => {
+                report.diagnostics.push(schema_diagnostic(&prefab_file, &e));
+                None
+            }
+        })
+    } else {
+        let (line, column) = locator.find(path);
+        report.diagnostics.push(Diagnostic {
+            file: file.to_path_buf(),
+            line,
+            column,
+            message: format!("prefab \"{}\" not found under {}", path, assets_root.display()),
+        });
+        None
+    };
+
+    prefabs.insert(path.to_string(), prefab.clone());
+    prefab
+}
+
+/// Checks an entity's model and capabilities on their own.
+fn check_entity(
+    entity: &EntityDef,
+    file: &Path,
+    locator: &mut Locator,
+    assets_root: &Path,
+    report: &mut ValidationReport,
+) {
+    if let Some(model) = &entity.model {
+        check_asset(model, file, locator, assets_root, report);
+    }
+    for block in &entity.capabilities {
+        match block {
+            CapabilityBlock::Behavior { machine, .. } => {
+                check_asset(machine, file, locator, assets_root, report);
+            }
+            CapabilityBlock::AnimationController { .. } if entity.model.is_none() => {
+                let (line, column) = locator.find(&entity.id);
+                report.diagnostics.push(Diagnostic {
+                    file: file.to_path_buf(),
+                    line,
+                    column,
+                    message: format!(
+                        "entity \"{}\": AnimationController requires a model",
+                        entity.id
+                    ),
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+fn check_camera_target(
+    entity: &EntityDef,
+    ids: &HashSet<&str>,
+    file: &Path,
+    locator: &mut Locator,
+    report: &mut ValidationReport,
+) {
+    for block in &entity.capabilities {
+        if let CapabilityBlock::OrbitCamera { target, .. } = block {
+            if !ids.contains(target.as_str()) {
+                let (line, column) = locator.find(target);
+                report.diagnostics.push(Diagnostic {
+                    file: file.to_path_buf(),
+                    line,
+                    column,
+                    message: format!(
+                        "entity \"{}\": OrbitCamera target \"{}\" does not exist",
+                        entity.id, target
+                    ),
+                });
+            }
+        }
+    }
+}
+
+fn check_unique_id<'a>(
+    id: &'a str,
+    ids: &mut HashSet<&'a str>,
+    file: &Path,
+    locator: &mut Locator,
+    report: &mut ValidationReport,
+) {
+    let (line, column) = locator.find(id);
+    if !ids.insert(id) {
+        report.diagnostics.push(Diagnostic {
+            file: file.to_path_buf(),
+            line,
+            column,
+            message: format!("duplicate entity id \"{}\"", id),
+        });
+    }
+}
+
+/// Checks that every binding names a known input, and that the actions the
+/// engine reads have the right kind.
+fn validate_input_profile(file: &Path, report: &mut ValidationReport) {
+    let Some(source) = read_file(file, report) else {
+        return;
+    };
+    let profile = match from_versioned_str::<InputProfile>(&source) {
+        Ok(profile) => profile,
+        Err(e) => {
+            report.diagnostics.push(schema_diagnostic(file, &e));
+            return;
+        }
+    };
+
+    let mut locator = Locator::new(&source);
+    for (name, action) in &profile.actions {
+        let (line, column) = locator.find(name);
+        if let Some((_, kind)) = ENGINE_ACTIONS.iter().find(|(engine, _)| engine == name) {
+            if action.kind() != *kind {
+                report.diagnostics.push(Diagnostic {
+                    file: file.to_path_buf(),
+                    line,
+                    column,
+                    message: format!("action \"{}\" must be {}, found {}", name, kind, action.kind()),
+                });
+            }
+        }
+
+        for (kind, input, known) in action.named_inputs() {
+            if known {
+                continue;
+            }
+            let (line, column) = locator.find(input);
+            report.diagnostics.push(Diagnostic {
+                file: file.to_path_buf(),
+                line,
+                column,
+                message: format!("unknown {} \"{}\" bound to action \"{}\"", kind, input, name),
+            });
+        }
+    }
+}
+
+/// Checks state names in the global logic and queues the scenes it loads.
+fn validate_logic(
+    file: &Path,
+    assets_root: &Path,
+    report: &mut ValidationReport,
+    queue: &mut VecDeque<String>,
+    events: &mut EventUses,
+) {
+    let Some(source) = read_file(file, report) else {
+        return;
+    };
+    let logic = match from_versioned_str::<GlobalLogic>(&source) {
+        Ok(logic) => logic,
+        Err(e) => {
+            report.diagnostics.push(schema_diagnostic(file, &e));
+            return;
+        }
+    };
+
+    let mut locator = Locator::new(&source);
+    events.listened.extend(logic.transitions.iter().map(|transition| transition.event.to_string()));
+    check_machine(&logic.initial_state, &logic.states, &logic.transitions, file, &mut locator, assets_root, report, queue, events);
+}
+
+/// Checks a behavior machine the first time it is referenced.
+fn validate_behavior(
+    path: &str,
+    assets_root: &Path,
+    report: &mut ValidationReport,
+    queue: &mut VecDeque<String>,
+    behaviors: &mut HashSet<String>,
+    events: &mut EventUses,
+) {
+    let file = assets_root.join(path);
+    if !behaviors.insert(path.to_string()) || !file.is_file() {
+        return;
+    }
+    let Some(source) = read_file(&file, report) else {
+        return;
+    };
+    let machine = match from_versioned_str::<BehaviorMachine>(&source) {
+        Ok(machine) => machine,
+        Err(e) => {
+            report.diagnostics.push(schema_diagnostic(&file, &e));
+            return;
+        }
+    };
+
+    let mut locator = Locator::new(&source);
+    check_machine(&machine.initial_state, &machine.states, &machine.transitions, &file, &mut locator, assets_root, report, queue, events);
+}
+
+/// Checks the state names of a state machine and queues the scenes its
+/// actions load.
+#[allow(clippy::too_many_arguments)]
+fn check_machine(
+    initial_state: &str,
+    states: &[LogicState],
+    transitions: &[Transition],
+    file: &Path,
+    locator: &mut Locator,
+    assets_root: &Path,
+    report: &mut ValidationReport,
+    queue: &mut VecDeque<String>,
+    events: &mut EventUses,
+) {
+    let mut names = HashSet::new();
+    for state in states {
+        if !names.insert(state.name.as_str()) {
+            let (line, column) = locator.find(&state.name);
+            report.diagnostics.push(Diagnostic {
+                file: file.to_path_buf(),
+                line,
+                column,
+                message: format!("duplicate state \"{}\"", state.name),
+            });
+        }
+    }
+
+    let check_state = |name: &str, locator: &mut Locator, report: &mut ValidationReport| {
+        let (line, column) = locator.find(name);
+        if !names.contains(name) {
+            report.diagnostics.push(Diagnostic {
+                file: file.to_path_buf(),
+                line,
+                column,
+                message: format!("unknown state \"{}\"", name),
+            });
+        }
+    };
+    check_state(initial_state, locator, report);
+    for transition in transitions {
+        if transition.from != ANY_STATE {
+            check_state(&transition.from, locator, report);
+        }
+        if let Some(to) = &transition.to {
+            check_state(to, locator, report);
+        }
+    }
+
+    let actions = states
+        .iter()
+        .flat_map(|state| state.on_enter.iter().chain(&state.on_exit))
+        .chain(transitions.iter().flat_map(|transition| &transition.actions));
+    for action in actions {
+        check_action(action, file, locator, assets_root, report, queue, events);
+    }
+}
+
+/// Queues the scene an action loads, checks the assets it refers to and
+/// records the events it uses.
+fn check_action(
+    action: &Action,
+    file: &Path,
+    locator: &mut Locator,
+    assets_root: &Path,
+    report: &mut ValidationReport,
+    queue: &mut VecDeque<String>,
+    events: &mut EventUses,
+) {
+    match action {
+        Action::LoadScene(scene) => check_scene_reference(scene, file, locator, assets_root, report, queue),
+        Action::OpenUi(path) | Action::PlaySound(path) | Action::SpawnPrefab { prefab: path, .. } => {
+            check_asset(path, file, locator, assets_root, report);
+        }
+        Action::Sequence(actions) | Action::Parallel(actions) | Action::RandomChoice(actions) => {
+            for action in actions {
+                check_action(action, file, locator, assets_root, report, queue, events);
+            }
+        }
+        Action::Delay(_, action) => check_action(action, file, locator, assets_root, report, queue, events),
+        Action::EmitEvent(event) => events.raise(event, file, locator),
+        Action::WaitForEvent(event) => {
+            events.listened.insert(event.clone());
+        }
+        Action::SaveGame(slot) | Action::LoadGame(slot) => {
+            if let Err(e) = check_slot(slot) {
+                let (line, column) = locator.find(slot);
+                report.diagnostics.push(Diagnostic {
+                    file: file.to_path_buf(),
+                    line,
+                    column,
+                    message: e.to_string(),
+                });
+            }
+        }
+        _ => {}
+    }
+}
+
+fn check_scene_reference(
+    scene: &str,
+    file: &Path,
+    locator: &mut Locator,
+    assets_root: &Path,
+    report: &mut ValidationReport,
+    queue: &mut VecDeque<String>,
+) {
+    let (line, column) = locator.find(scene);
+    if assets_root.join(scene).is_file() {
+        queue.push_back(scene.to_string());
+    } else {
+        report.diagnostics.push(Diagnostic {
+            file: file.to_path_buf(),
+            line,
+            column,
+            message: format!("scene \"{}\" not found under {}", scene, assets_root.display()),
+        });
+    }
+}
+
+fn check_asset(
+    path: &str,
+    file: &Path,
+    locator: &mut Locator,
+    assets_root: &Path,
+    report: &mut ValidationReport,
+) {
+    let (line, column) = locator.find(path);
+    // Strip sub-asset labels such as `#Scene0`.
+    let file_part = path.split('#').next().unwrap_or("");
+    if !assets_root.join(file_part).is_file() {
+        report.diagnostics.push(Diagnostic {
+            file: file.to_path_buf(),
+            line,
+            column,
+            message: format!("asset \"{}\" not found under {}", file_part, assets_root.display()),
+        });
+    }
+}
+
+fn read_file(file: &Path, report: &mut ValidationReport) -> Option<String> {
+    match std::fs::read_to_string(file) {
+        Ok(source) => Some(source),
+        Err(e) => {
+            report.diagnostics.push(Diagnostic {
+                file: file.to_path_buf(),
+                line: None,
+                column: None,
+                message: format!("could not read file: {}", e),
+            });
+            None
+        }
+    }
+}
+
+fn schema_diagnostic(file: &Path, error: &SchemaError) -> Diagnostic {
+    let position = error.position();
+    Diagnostic {
+        file: file.to_path_buf(),
+        line: position.map(|(line, _)| line),
+        column: position.map(|(_, column)| column),
+        message: error.message(),
+    }
+}
+
+/// Finds the source position of quoted string literals. Repeated lookups of
+/// the same string return successive occurrences.
+struct Locator<'a> {
+    source: &'a str,
+    cursors: HashMap<String, usize>,
+}
+
+impl<'a> Locator<'a> {
+    fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            cursors: HashMap::new(),
+        }
+    }
+
+    fn find(&mut self, literal: &str) -> (Option<usize>, Option<usize>) {
+        let needle = format!("\"{}\"", literal);
+        let cursor = self.cursors.entry(literal.to_string()).or_insert(0);
+        let found = self.source[*cursor..]
+            .find(&needle)
+            .map(|offset| *cursor + offset)
+            .or_else(|| self.source.find(&needle));
+        let Some(offset) = found else {
+            return (None, None);
+        };
+        *cursor = offset + needle.len();
+
+        let before = &self.source[..offset];
+        let line = before.matches('\n').count() + 1;
+        let column = offset - before.rfind('\n').map(|i| i + 1).unwrap_or(0) + 1;
+        (Some(line), Some(column))
+    }
+}
@@ -0,0 +1,61 @@
+use ironhold_core::runtime::Action;
+use ironhold_core::schema::{from_versioned_str, ParamValue, Rotation, Scale, UiAction, UiElement, UiMenu};
+use std::collections::HashMap;
+
+#[test]
+fn test_actions_parse() {
+    let actions: Vec<Action> = ron::from_str(r#"[
+        OpenUi("ui/pause.ron"),
+        CloseUi("ui/pause.ron"),
+        PlayAnimation("self", "Wave"),
+        SetVelocity("platform", (0.0, 1.0, 0.0)),
+        EmitEvent("boss.defeated"),
+        PlaySound("sounds/click.ogg"),
+        Quit,
+        SetTransform(target: "door", rotation: Some(Euler(0.0, 90.0, 0.0))),
+        SpawnPrefab(prefab: "prefabs/coin.ron", at: Some("self"), position: (0.0, 1.0, 0.0), params: { "value": 5 }),
+    ]"#).expect("Failed to deserialize actions");
+
+    assert_eq!(actions[2], Action::PlayAnimation("self".to_string(), "Wave".to_string()));
+    assert_eq!(actions[3], Action::SetVelocity("platform".to_string(), (0.0, 1.0, 0.0)));
+    assert_eq!(actions[6], Action::Quit);
+    assert_eq!(
+        actions[7],
+        Action::SetTransform {
+            target: "door".to_string(),
+            position: None,
+            rotation: Some(Rotation::Euler(0.0, 90.0, 0.0)),
+            scale: None,
+        }
+    );
+    assert_eq!(
+        actions[8],
+        Action::SpawnPrefab {
+            prefab: "prefabs/coin.ron".to_string(),
+            id: None,
+            at: Some("self".to_string()),
+            position: (0.0, 1.0, 0.0),
+            rotation: None,
+            scale: None,
+            params: HashMap::from([("value".to_string(), ParamValue::Number(5.0))]),
+        }
+    );
+
+    let scaled: Action = ron::from_str(r#"SetTransform(target: "crate", scale: Some(Uniform(2.0)))"#).unwrap();
+    assert!(matches!(scaled, Action::SetTransform { scale: Some(Scale::Uniform(s)), .. } if s == 2.0));
+}
+
+#[test]
+fn test_ui_menu_parses() {
+    let menu: UiMenu = from_versioned_str(r#"(
+        schema_version: 1,
+        elements: [
+            Button(text: "Resume", action: Run([SetPaused(false), CloseUi("ui/pause.ron")])),
+            Button(text: "Quit", action: Run([Quit])),
+        ],
+    )"#).expect("Failed to deserialize UiMenu");
+    let UiElement::Button { action: UiAction::Run(actions), .. } = &menu.elements[0] else {
+        panic!("expected a Run button");
+    };
+    assert_eq!(actions, &[Action::SetPaused(false), Action::CloseUi("ui/pause.ron".to_string())]);
+}
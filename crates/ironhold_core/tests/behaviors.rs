@@ -0,0 +1,130 @@
+use bevy::prelude::*;
+use ironhold_core::capabilities::{CharacterController, Collider};
+use ironhold_core::runtime::{trigger_system, Action, ActionQueue, Behavior, BehaviorMessage, ConditionContext, EventId, QueuedAction};
+use ironhold_core::schema::{from_versioned_str, BehaviorMachine, ColliderShape, Value};
+use std::collections::HashMap;
+
+const DOOR: &str = r#"
+    (
+        schema_version: 1,
+        initial_state: "closed",
+        locals: { "locked": true, "uses": 0 },
+        states: [
+            (name: "closed"),
+            (name: "open", on_enter: [SendEvent("door_light", "on")], timer: Some(2.0)),
+        ],
+        transitions: [
+            (from: "closed", event: "interact", guard: Some(LocalIs("locked", false)), to: Some("open")),
+            (from: "closed", event: "trigger.enter", guard: Some(OtherIs("janitor")), to: Some("open")),
+            (from: "open", event: "timer", to: Some("closed"), actions: [SetLocal("uses", 1)]),
+        ],
+    )
+"#;
+
+fn sources(queue: &ActionQueue) -> Vec<Option<Entity>> {
+    queue.0.iter().map(|queued| queued.source).collect()
+}
+
+#[test]
+fn test_behavior_locals_and_guards() {
+    let machine: BehaviorMachine = from_versioned_str(DOOR).expect("Failed to deserialize BehaviorMachine");
+    assert_eq!(machine.locals["uses"], Value::Int(0));
+
+    let entity = Entity::PLACEHOLDER;
+    let mut context = ConditionContext::default();
+    let mut queue = ActionQueue::default();
+    let mut locked = Behavior::new(Handle::default(), HashMap::new(), 1.5);
+    locked.start(entity, &machine, &mut queue);
+    assert_eq!(locked.current(), Some("closed"));
+    assert!(!locked.handle_event(entity, &machine, EventId::of("interact"), Some("player"), &mut context, &mut queue));
+    assert!(!locked.handle_event(entity, &machine, EventId::of("trigger.enter"), Some("player"), &mut context, &mut queue));
+    assert!(locked.handle_event(entity, &machine, EventId::of("trigger.enter"), Some("janitor"), &mut context, &mut queue));
+    assert_eq!(locked.current(), Some("open"));
+
+    // Per-entity overrides replace the machine's defaults.
+    let overrides = HashMap::from([("locked".to_string(), Value::Bool(false))]);
+    let mut unlocked = Behavior::new(Handle::default(), overrides, 1.5);
+    unlocked.start(entity, &machine, &mut queue);
+    assert_eq!(unlocked.local("locked"), Some(&Value::Bool(false)));
+    assert_eq!(unlocked.local("uses"), Some(&Value::Int(0)));
+    assert!(unlocked.handle_event(entity, &machine, EventId::of("interact"), Some("player"), &mut context, &mut queue));
+
+    // Actions run on behalf of the behavior's entity.
+    assert_eq!(
+        queue.0.back(),
+        Some(&QueuedAction {
+            action: Action::SendEvent("door_light".to_string(), "on".to_string()),
+            source: Some(entity),
+        })
+    );
+    assert_eq!(sources(&queue), [Some(entity), Some(entity)]);
+}
+
+#[test]
+fn test_state_timer_fires_once() {
+    let machine: BehaviorMachine = from_versioned_str(DOOR).unwrap();
+    let entity = Entity::PLACEHOLDER;
+    let mut context = ConditionContext::default();
+    let mut queue = ActionQueue::default();
+    let mut door = Behavior::new(Handle::default(), HashMap::new(), 1.5);
+    door.start(entity, &machine, &mut queue);
+    door.handle_event(entity, &machine, EventId::of("trigger.enter"), Some("janitor"), &mut context, &mut queue);
+
+    door.tick(entity, &machine, 1.5, &mut context, &mut queue);
+    assert_eq!(door.current(), Some("open"));
+    door.tick(entity, &machine, 1.0, &mut context, &mut queue);
+    assert_eq!(door.current(), Some("closed"));
+    assert_eq!(queue.actions().last(), Some(&Action::SetLocal("uses".to_string(), Value::Int(1))));
+
+    // "closed" has no timer.
+    let queued = queue.0.len();
+    door.tick(entity, &machine, 10.0, &mut context, &mut queue);
+    assert_eq!(door.current(), Some("closed"));
+    assert_eq!(queue.0.len(), queued);
+}
+
+#[test]
+fn test_collider_contains() {
+    let sphere = Collider { shape: ColliderShape::Sphere(1.0), is_trigger: true };
+    assert!(sphere.contains(Vec3::new(0.5, 0.5, 0.0)));
+    assert!(!sphere.contains(Vec3::new(1.0, 1.0, 0.0)));
+
+    let cuboid = Collider { shape: ColliderShape::Box(1.0, 2.0, 0.5), is_trigger: true };
+    assert!(cuboid.contains(Vec3::new(-1.0, 1.9, 0.5)));
+    assert!(!cuboid.contains(Vec3::new(0.0, 0.0, 0.6)));
+
+    let capsule = Collider { shape: ColliderShape::Capsule(0.5, 1.0), is_trigger: true };
+    assert!(capsule.contains(Vec3::new(0.0, 1.4, 0.0)));
+    assert!(capsule.contains(Vec3::new(0.4, -1.0, 0.0)));
+    assert!(!capsule.contains(Vec3::new(0.0, 1.6, 0.0)));
+}
+
+#[test]
+fn test_sample_pickup_parses() {
+    let machine: BehaviorMachine = from_versioned_str(include_str!("../../../assets/behaviors/pickup.ron"))
+        .expect("Failed to deserialize assets/behaviors/pickup.ron");
+    assert_eq!(machine.state("taken").unwrap().on_enter.last(), Some(&Action::Despawn("self".to_string())));
+}
+
+#[test]
+fn test_triggers_use_the_simulated_transforms() {
+    let mut app = App::new();
+    app.add_message::<BehaviorMessage>().add_systems(Update, trigger_system);
+    // `GlobalTransform` is left as spawned, as before propagation runs.
+    let trigger = app.world_mut().spawn((
+        Transform::from_xyz(5.0, 0.0, 0.0),
+        GlobalTransform::IDENTITY,
+        Collider { shape: ColliderShape::Sphere(1.0), is_trigger: true },
+    )).id();
+    let character = app.world_mut().spawn((Transform::IDENTITY, GlobalTransform::IDENTITY, CharacterController::default())).id();
+    let events = |app: &mut App| -> Vec<EventId> {
+        app.world_mut().resource_mut::<Messages<BehaviorMessage>>().drain().map(|message| message.event).collect()
+    };
+
+    app.update();
+    assert!(events(&mut app).is_empty());
+    app.world_mut().get_mut::<Transform>(character).unwrap().translation.x = 5.0;
+    app.update();
+    assert_eq!(events(&mut app), [EventId::of("trigger.enter")]);
+    assert_eq!(app.world().get::<GlobalTransform>(trigger), Some(&GlobalTransform::IDENTITY));
+}
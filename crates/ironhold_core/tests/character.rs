@@ -0,0 +1,376 @@
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use std::time::Duration;
+use ironhold_core::capabilities::{
+    character_animation_system, player_movement_system, AnimationController, CharacterController, Collider, GroundPlane,
+    JumpPhase, Locomotion, OrbitCamera,
+};
+use ironhold_core::runtime::{end_input_tick_system, InputActions, InputSources};
+use ironhold_core::schema::{
+    AnimationMap, ButtonMode, CameraConfig, ColliderShape, Easing, InputMap, InputProfile, SpeedRamp, Steering,
+};
+
+fn animations() -> AnimationMap {
+    let clip = |name: &str| name.to_string();
+    AnimationMap {
+        idle: clip("idle"),
+        walk: clip("walk"),
+        run: clip("run"),
+        jump_enter: clip("jump_enter"),
+        jump_loop: clip("jump_loop"),
+        jump_exit: clip("jump_exit"),
+        death: clip("death"),
+        dance: clip("dance"),
+        crouch_idle: clip("crouch_idle"),
+        crouch_forward: clip("crouch_forward"),
+        roll: clip("roll"),
+    }
+}
+
+/// An app that runs one 60 Hz tick of character movement per update.
+fn movement_app(ground_plane: Option<f32>) -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_nanos(16_666_667)))
+        .insert_resource(Time::<Fixed>::from_hz(60.0))
+        .insert_resource(InputActions::new(InputProfile::from_input_map(&InputMap::default())))
+        .insert_resource(GroundPlane(ground_plane))
+        .add_systems(FixedUpdate, player_movement_system)
+        .add_systems(FixedLast, end_input_tick_system)
+        .add_systems(Update, character_animation_system);
+    app
+}
+
+fn spawn_player(app: &mut App, position: Vec3) -> Entity {
+    let animations = animations();
+    app.world_mut()
+        .spawn((
+            Transform::from_translation(position),
+            CharacterController { inputs: Some(InputMap::default()), ..default() },
+            AnimationController {
+                current: animations.idle.clone(),
+                animations,
+                last_played: String::new(),
+                gltf_path: String::new(),
+                gltf_handle: Handle::default(),
+                node_indices: default(),
+                graph_initialized: false,
+                completions: 0,
+            },
+        ))
+        .id()
+}
+
+/// Runs a frame with `pressed` held.
+fn frame(app: &mut App, pressed: &[KeyCode]) {
+    let mut keys = ButtonInput::default();
+    for key in pressed {
+        keys.press(*key);
+    }
+    app.world_mut().resource_mut::<InputActions>().update(&InputSources { keys: Some(&keys), ..default() });
+    app.update();
+}
+
+fn clip(app: &App, entity: Entity) -> &str {
+    &app.world().get::<AnimationController>(entity).unwrap().current
+}
+
+fn locomotion(app: &App, entity: Entity) -> Locomotion {
+    app.world().get::<CharacterController>(entity).unwrap().locomotion
+}
+
+fn velocity(app: &App, entity: Entity) -> Vec3 {
+    app.world().get::<CharacterController>(entity).unwrap().velocity
+}
+
+fn player(app: &App, entity: Entity) -> (Vec3, JumpPhase) {
+    let entity = app.world().entity(entity);
+    (entity.get::<Transform>().unwrap().translation, entity.get::<CharacterController>().unwrap().jump)
+}
+
+#[test]
+fn test_jumps_rise_fall_and_land_through_the_jump_clips() {
+    let mut app = movement_app(Some(0.0));
+    let entity = spawn_player(&mut app, Vec3::ZERO);
+    frame(&mut app, &[]);
+    frame(&mut app, &[KeyCode::Space]);
+
+    let mut clips = vec!["idle".to_string()];
+    let mut apex: f32 = 0.0;
+    for _ in 0..90 {
+        frame(&mut app, &[]);
+        apex = apex.max(player(&app, entity).0.y);
+        let current = &app.world().get::<AnimationController>(entity).unwrap().current;
+        if clips.last() != Some(current) {
+            clips.push(current.clone());
+        }
+    }
+
+    // v² / 2g with the default jump speed and gravity.
+    assert!((apex - 5.0 * 5.0 / (2.0 * 9.81)).abs() < 0.1, "apex {}", apex);
+    assert_eq!(player(&app, entity), (Vec3::ZERO, JumpPhase::Grounded));
+    assert_eq!(clips, ["idle", "jump_enter", "jump_loop", "jump_exit", "idle"]);
+}
+
+#[test]
+fn test_characters_stand_on_solid_colliders_and_fall_off_them() {
+    let mut app = movement_app(Some(0.0));
+    let block = Transform::from_xyz(0.0, 0.5, 0.0);
+    app.world_mut().spawn((
+        block,
+        GlobalTransform::from(block),
+        Collider { shape: ColliderShape::Box(1.0, 0.5, 1.0), is_trigger: false },
+    ));
+    // Triggers are not solid.
+    let zone = Transform::from_xyz(0.0, 2.0, -3.0);
+    app.world_mut().spawn((
+        zone,
+        GlobalTransform::from(zone),
+        Collider { shape: ColliderShape::Box(1.0, 0.5, 1.0), is_trigger: true },
+    ));
+    let entity = spawn_player(&mut app, Vec3::new(0.0, 3.0, 0.0));
+
+    for _ in 0..60 {
+        frame(&mut app, &[]);
+    }
+    assert_eq!(player(&app, entity), (Vec3::new(0.0, 1.0, 0.0), JumpPhase::Grounded));
+
+    // Walk forward (-Z) off the block, past the trigger, down to the ground plane.
+    for _ in 0..60 {
+        frame(&mut app, &[KeyCode::KeyW]);
+    }
+    for _ in 0..30 {
+        frame(&mut app, &[]);
+    }
+    let (position, jump) = player(&app, entity);
+    assert_eq!(position.y, 0.0);
+    assert!(position.z < -2.5, "{}", position);
+    assert_eq!(jump, JumpPhase::Grounded);
+}
+
+#[test]
+fn test_characters_land_on_colliders_spawned_this_frame() {
+    let mut app = movement_app(None);
+    // Not yet propagated: `GlobalTransform` still says the origin.
+    app.world_mut().spawn((
+        Transform::from_xyz(10.0, 0.5, 0.0),
+        GlobalTransform::IDENTITY,
+        Collider { shape: ColliderShape::Box(1.0, 0.5, 1.0), is_trigger: false },
+    ));
+    let entity = spawn_player(&mut app, Vec3::new(10.0, 3.0, 0.0));
+    for _ in 0..60 {
+        frame(&mut app, &[]);
+    }
+    assert_eq!(player(&app, entity), (Vec3::new(10.0, 1.0, 0.0), JumpPhase::Grounded));
+}
+
+#[test]
+fn test_air_control_limits_steering_in_the_air() {
+    let distance = |air_control: f32| {
+        let mut app = movement_app(Some(0.0));
+        let entity = spawn_player(&mut app, Vec3::ZERO);
+        app.world_mut().get_mut::<CharacterController>(entity).unwrap().movement.air_control = air_control;
+        frame(&mut app, &[]);
+        // Jump from standing, then hold forward until just before landing.
+        frame(&mut app, &[KeyCode::Space]);
+        for _ in 0..55 {
+            frame(&mut app, &[KeyCode::KeyW]);
+        }
+        -player(&app, entity).0.z
+    };
+
+    assert_eq!(distance(0.0), 0.0);
+    let full = distance(1.0);
+    assert!((distance(0.5) - full * 0.5).abs() < 1e-3);
+    assert!((full - 55.0 / 60.0 * 3.0).abs() < 0.1, "{}", full);
+}
+
+#[test]
+fn test_crouching_toggles_or_holds_and_slows_the_character() {
+    let mut app = movement_app(Some(0.0));
+    let entity = spawn_player(&mut app, Vec3::ZERO);
+    frame(&mut app, &[]);
+    frame(&mut app, &[KeyCode::KeyC]);
+    assert_eq!((locomotion(&app, entity), clip(&app, entity)), (Locomotion::Crouching, "crouch_idle"));
+    for _ in 0..60 {
+        frame(&mut app, &[KeyCode::KeyW]);
+    }
+    assert_eq!((locomotion(&app, entity), clip(&app, entity)), (Locomotion::Crouching, "crouch_forward"));
+    assert!((player(&app, entity).0.z + 1.5).abs() < 1e-3, "{}", player(&app, entity).0);
+    frame(&mut app, &[KeyCode::KeyC]);
+    assert_eq!(locomotion(&app, entity), Locomotion::Standing);
+
+    app.world_mut().get_mut::<CharacterController>(entity).unwrap().movement.crouch_mode = ButtonMode::Hold;
+    frame(&mut app, &[KeyCode::KeyC]);
+    frame(&mut app, &[KeyCode::KeyC]);
+    assert_eq!(locomotion(&app, entity), Locomotion::Crouching);
+    frame(&mut app, &[]);
+    assert_eq!(locomotion(&app, entity), Locomotion::Standing);
+}
+
+#[test]
+fn test_rolls_burst_forward_without_turning() {
+    let mut app = movement_app(Some(0.0));
+    let entity = spawn_player(&mut app, Vec3::ZERO);
+    frame(&mut app, &[]);
+    frame(&mut app, &[KeyCode::KeyR]);
+    assert_eq!(clip(&app, entity), "roll");
+    // Turning and jumping are ignored mid-roll.
+    for _ in 0..35 {
+        frame(&mut app, &[KeyCode::KeyD, KeyCode::Space]);
+    }
+    let transform = *app.world().get::<Transform>(entity).unwrap();
+    assert_eq!(transform.rotation, Quat::IDENTITY);
+    assert!((transform.translation.z + 7.0 * 0.6).abs() < 1e-3, "{}", transform.translation);
+    assert_eq!(player(&app, entity).1, JumpPhase::Grounded);
+
+    frame(&mut app, &[]);
+    assert_eq!((locomotion(&app, entity), clip(&app, entity)), (Locomotion::Standing, "idle"));
+}
+
+#[test]
+fn test_emotes_and_death_lock_movement() {
+    let mut app = movement_app(Some(0.0));
+    let entity = spawn_player(&mut app, Vec3::ZERO);
+    frame(&mut app, &[]);
+    frame(&mut app, &[KeyCode::KeyG]);
+    for _ in 0..10 {
+        frame(&mut app, &[KeyCode::KeyW, KeyCode::KeyD]);
+    }
+    assert_eq!((locomotion(&app, entity), clip(&app, entity)), (Locomotion::Emoting, "dance"));
+    assert_eq!(*app.world().get::<Transform>(entity).unwrap(), Transform::IDENTITY);
+    frame(&mut app, &[KeyCode::KeyG]);
+    frame(&mut app, &[KeyCode::KeyW]);
+    assert_eq!((locomotion(&app, entity), clip(&app, entity)), (Locomotion::Standing, "walk"));
+
+    app.world_mut().get_mut::<CharacterController>(entity).unwrap().locomotion = Locomotion::Dead;
+    let position = player(&app, entity).0;
+    for _ in 0..10 {
+        frame(&mut app, &[KeyCode::KeyW, KeyCode::KeyR, KeyCode::KeyG]);
+    }
+    assert_eq!((locomotion(&app, entity), clip(&app, entity)), (Locomotion::Dead, "death"));
+    assert_eq!(player(&app, entity).0, position);
+}
+
+#[test]
+fn test_speed_ramps_along_the_acceleration_and_deceleration_curves() {
+    let mut app = movement_app(Some(0.0));
+    let entity = spawn_player(&mut app, Vec3::ZERO);
+    {
+        let mut controller = app.world_mut().get_mut::<CharacterController>(entity).unwrap();
+        controller.movement.acceleration = SpeedRamp { seconds: 1.0, curve: Easing::Linear };
+        controller.movement.deceleration = SpeedRamp { seconds: 0.5, curve: Easing::EaseIn };
+    }
+    frame(&mut app, &[]);
+    // A second from standing to run speed: walking speed after half of it.
+    for _ in 0..15 {
+        frame(&mut app, &[KeyCode::KeyW]);
+    }
+    assert!((velocity(&app, entity).z + 1.5).abs() < 1e-3, "{}", velocity(&app, entity));
+    for _ in 0..30 {
+        frame(&mut app, &[KeyCode::KeyW]);
+    }
+    assert!((velocity(&app, entity).z + 3.0).abs() < 1e-3, "{}", velocity(&app, entity));
+
+    // Slowing down starts gently on an ease-in curve, then stops.
+    frame(&mut app, &[]);
+    let slowed = -velocity(&app, entity).z;
+    assert!(slowed > 2.7 && slowed < 3.0, "{}", slowed);
+    assert_eq!(clip(&app, entity), "walk");
+    for _ in 0..10 {
+        frame(&mut app, &[]);
+    }
+    assert_eq!(velocity(&app, entity), Vec3::ZERO);
+    assert_eq!(clip(&app, entity), "idle");
+}
+
+#[test]
+fn test_turning_around_brakes_before_speeding_up() {
+    let mut app = movement_app(Some(0.0));
+    let entity = spawn_player(&mut app, Vec3::ZERO);
+    {
+        let mut controller = app.world_mut().get_mut::<CharacterController>(entity).unwrap();
+        controller.movement.backward_multiplier = 1.0;
+        controller.movement.acceleration = SpeedRamp { seconds: 1.0, curve: Easing::Linear };
+        controller.movement.deceleration = SpeedRamp { seconds: 0.5, curve: Easing::Linear };
+    }
+    frame(&mut app, &[]);
+    for _ in 0..60 {
+        frame(&mut app, &[KeyCode::KeyW]);
+    }
+    assert!((velocity(&app, entity).z + 3.0).abs() < 1e-3, "{}", velocity(&app, entity));
+
+    // Still moving forward, a little slower, the tick after reversing.
+    frame(&mut app, &[KeyCode::KeyS]);
+    let reversed = velocity(&app, entity).z;
+    assert!(reversed < -2.5 && reversed > -3.0, "{}", reversed);
+    // Braking and the backward ramp together stop the character, then it
+    // speeds up backward.
+    for _ in 0..9 {
+        frame(&mut app, &[KeyCode::KeyS]);
+    }
+    assert!(velocity(&app, entity).z.abs() < 1e-3, "{}", velocity(&app, entity));
+    for _ in 0..30 {
+        frame(&mut app, &[KeyCode::KeyS]);
+    }
+    assert!((velocity(&app, entity).z - 3.0).abs() < 1e-3, "{}", velocity(&app, entity));
+}
+
+#[test]
+fn test_run_mode_and_speed_multipliers() {
+    let mut app = movement_app(Some(0.0));
+    let entity = spawn_player(&mut app, Vec3::ZERO);
+    {
+        let mut controller = app.world_mut().get_mut::<CharacterController>(entity).unwrap();
+        controller.movement.run_mode = ButtonMode::Hold;
+        controller.movement.strafe_multiplier = 0.5;
+        controller.movement.backward_multiplier = 0.25;
+    }
+    frame(&mut app, &[]);
+    frame(&mut app, &[KeyCode::KeyW, KeyCode::ShiftLeft]);
+    assert_eq!((velocity(&app, entity), clip(&app, entity)), (Vec3::new(0.0, 0.0, -6.0), "run"));
+    frame(&mut app, &[KeyCode::KeyW]);
+    assert_eq!((velocity(&app, entity), clip(&app, entity)), (Vec3::new(0.0, 0.0, -3.0), "walk"));
+    frame(&mut app, &[KeyCode::KeyE]);
+    assert!(velocity(&app, entity).distance(Vec3::new(1.5, 0.0, 0.0)) < 1e-5, "{}", velocity(&app, entity));
+    frame(&mut app, &[KeyCode::KeyS]);
+    assert!(velocity(&app, entity).distance(Vec3::new(0.0, 0.0, 0.75)) < 1e-5, "{}", velocity(&app, entity));
+}
+
+#[test]
+fn test_camera_relative_steering_follows_the_camera_and_faces_the_movement() {
+    let mut app = movement_app(Some(0.0));
+    let entity = spawn_player(&mut app, Vec3::ZERO);
+    app.world_mut().get_mut::<CharacterController>(entity).unwrap().movement.steering = Steering::CameraRelative;
+    let config = CameraConfig {
+        offset: (0.0, 5.0, 10.0),
+        look_at_offset: (0.0, 1.0, 0.0),
+        zoom_speed: 1.0,
+        orbit_speed: 1.0,
+        min_radius: 1.0,
+        max_radius: 20.0,
+    };
+    // The camera looks along -X, from the character's +X side.
+    app.world_mut().spawn(OrbitCamera { yaw: std::f32::consts::FRAC_PI_2, ..OrbitCamera::new(entity, &config) });
+    frame(&mut app, &[]);
+
+    // Forward is away from the camera; the character turns toward it at `rot_speed`.
+    frame(&mut app, &[KeyCode::KeyW]);
+    assert!(velocity(&app, entity).distance(Vec3::new(-3.0, 0.0, 0.0)) < 1e-5, "{}", velocity(&app, entity));
+    let turned = app.world().get::<Transform>(entity).unwrap().rotation.to_euler(EulerRot::YXZ).0;
+    assert!((turned - 3.0 / 60.0).abs() < 1e-4, "{}", turned);
+    for _ in 0..40 {
+        frame(&mut app, &[KeyCode::KeyW]);
+    }
+    let forward = app.world().get::<Transform>(entity).unwrap().forward();
+    assert!(forward.distance(Vec3::NEG_X) < 1e-4, "{:?}", forward);
+
+    // Turn keys strafe toward the camera's right, and the character turns around to it.
+    frame(&mut app, &[KeyCode::KeyD]);
+    assert!(velocity(&app, entity).distance(Vec3::new(0.0, 0.0, -3.0)) < 1e-5, "{}", velocity(&app, entity));
+    for _ in 0..40 {
+        frame(&mut app, &[KeyCode::KeyD]);
+    }
+    let forward = app.world().get::<Transform>(entity).unwrap().forward();
+    assert!(forward.distance(Vec3::NEG_Z) < 1e-4, "{:?}", forward);
+}
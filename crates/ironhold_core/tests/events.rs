@@ -0,0 +1,47 @@
+use ironhold_core::runtime::{Action, EventId, EventRegistry, EventSource};
+use ironhold_core::schema::{from_versioned_str, GlobalLogic, UiMenu};
+
+// Ids are part of recordings and saves, so they must never change.
+const START: EventId = EventId::of("ui.start");
+
+#[test]
+fn test_event_ids_are_stable() {
+    assert_eq!(EventId::of(""), EventId(0xcbf2_9ce4_8422_2325));
+    assert_eq!(EventId::of("a"), EventId(0xaf63_dc4c_8601_ec8c));
+    assert_eq!(EventId::of("ui.start"), START);
+    assert_ne!(EventId::of("ui.start"), EventId::of("ui.stop"));
+}
+
+#[test]
+fn test_registry_interns_asset_events() {
+    let logic: GlobalLogic = from_versioned_str(r#"(
+        schema_version: 1,
+        initial_state: "menu",
+        states: [(name: "menu", on_enter: [Sequence([WaitForEvent("intro.done"), EmitEvent("menu.ready")])])],
+        transitions: [(from: "menu", event: "ui.start")],
+    )"#).unwrap();
+    let menu: UiMenu = from_versioned_str(r#"(
+        schema_version: 1,
+        elements: [
+            Button(text: "Back", action: Emit("ui.back")),
+            Button(text: "Options", action: Run([EmitEvent("ui.options"), OpenUi("menus/options.ron")])),
+            Button(text: "Quit", action: LoadScene("scenes/menu.ron")),
+        ],
+    )"#).unwrap();
+
+    let mut registry = EventRegistry::default();
+    assert_eq!(registry.id("ui.start"), None);
+    logic.intern_events(&mut registry);
+    menu.intern_events(&mut registry);
+    assert_eq!(registry.id("ui.start"), Some(START));
+    assert_eq!(registry.name(START), Some("ui.start"));
+    for name in ["intro.done", "menu.ready", "ui.back", "ui.options"] {
+        assert_eq!(registry.name(EventId::of(name)), Some(name));
+    }
+    // `LoadScene` and `Run` buttons queue their actions and send no event of their own.
+    assert_eq!(registry.id("ui.load:scenes/menu.ron"), None);
+
+    registry.intern_action(&Action::Delay(1.0, Box::new(Action::EmitEvent("late".to_string()))));
+    assert!(registry.id("late").is_some());
+    assert_eq!(registry.name(EventId::of("unknown")), None);
+}
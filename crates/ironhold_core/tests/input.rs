@@ -0,0 +1,168 @@
+use bevy::input::gamepad::GamepadInput;
+use bevy::prelude::*;
+use ironhold_core::runtime::{InputActions, InputSources};
+use ironhold_core::schema::{
+    from_versioned_str, key_name, parse_key, InputAction, InputMap, InputProfile, CAMERA_ORBIT_ACTION, INTERACT_ACTION, JUMP_ACTION,
+    MOVE_ACTION, KEY_CODES, TURN_ACTION,
+};
+
+const PROFILE: &str = r#"(
+    schema_version: 1,
+    deadzone: 0.2,
+    actions: {
+        "jump": Button([Key("Space"), Key("KeyW"), Gamepad("South")]),
+        "move": Axis2d([Buttons(up: Key("KeyW"), down: Key("KeyS"), left: Key("KeyA"), right: Key("KeyD")), GamepadStick(Left)]),
+        "zoom": Axis([MouseWheel, GamepadAxis("RightStickY")]),
+        "look": Axis2d([MouseDrag("Right")]),
+    },
+)"#;
+
+fn keys(pressed: &[KeyCode]) -> ButtonInput<KeyCode> {
+    let mut keys = ButtonInput::default();
+    for key in pressed {
+        keys.press(*key);
+    }
+    keys
+}
+
+#[test]
+fn test_buttons_with_several_bindings() {
+    let profile: InputProfile = from_versioned_str(PROFILE).expect("Failed to deserialize InputProfile");
+    let mut actions = InputActions::new(profile);
+
+    let space = keys(&[KeyCode::Space]);
+    actions.update(&InputSources { keys: Some(&space), ..default() });
+    assert!(actions.pressed("jump") && actions.just_pressed("jump"));
+
+    // Holding a second binding keeps the action pressed without pressing it again.
+    let both = keys(&[KeyCode::Space, KeyCode::KeyW]);
+    actions.update(&InputSources { keys: Some(&both), ..default() });
+    assert!(actions.pressed("jump") && !actions.just_pressed("jump"));
+    assert_eq!(actions.axis_2d("move"), Vec2::new(0.0, 1.0));
+
+    actions.update(&InputSources::default());
+    assert!(!actions.pressed("jump") && actions.just_released("jump"));
+    assert!(!actions.pressed("unknown"));
+    assert_eq!(actions.axis("unknown"), 0.0);
+}
+
+#[test]
+fn test_presses_reach_one_gameplay_tick() {
+    let profile: InputProfile = from_versioned_str(PROFILE).expect("Failed to deserialize InputProfile");
+    let mut actions = InputActions::new(profile);
+
+    // A press and release within frames that ran no tick is still seen by the next one.
+    let space = keys(&[KeyCode::Space]);
+    actions.update(&InputSources { keys: Some(&space), ..default() });
+    actions.update(&InputSources::default());
+    assert!(!actions.just_pressed("jump"));
+    assert!(actions.tick_just_pressed("jump") && actions.tick_just_released("jump"));
+
+    // A second tick in the same frame does not see it again.
+    actions.end_tick();
+    assert!(!actions.tick_just_pressed("jump") && !actions.tick_just_released("jump"));
+}
+
+#[test]
+fn test_axes_from_keys_mouse_and_gamepads() {
+    let profile: InputProfile = from_versioned_str(PROFILE).unwrap();
+    let mut actions = InputActions::new(profile);
+
+    let mut gamepad = Gamepad::default();
+    gamepad.analog_mut().set(GamepadInput::Axis(GamepadAxis::LeftStickX), 0.5);
+    gamepad.analog_mut().set(GamepadInput::Axis(GamepadAxis::LeftStickY), 0.5);
+    gamepad.analog_mut().set(GamepadInput::Axis(GamepadAxis::RightStickY), -0.1);
+    gamepad.digital_mut().press(GamepadButton::South);
+    let left = keys(&[KeyCode::KeyA]);
+    let mut buttons = ButtonInput::default();
+    buttons.press(MouseButton::Right);
+
+    actions.update(&InputSources {
+        keys: Some(&left),
+        mouse_buttons: Some(&buttons),
+        mouse_motion: Vec2::new(4.0, -2.0),
+        gamepads: vec![&gamepad],
+        ..default()
+    });
+    // The binding with the largest value wins.
+    assert_eq!(actions.axis_2d("move"), Vec2::new(-1.0, 0.0));
+    assert!(actions.just_pressed("jump"));
+    // Inside the deadzone.
+    assert_eq!(actions.axis("zoom"), 0.0);
+    assert_eq!(actions.axis_2d("look"), Vec2::new(4.0, -2.0));
+
+    actions.update(&InputSources { mouse_wheel: 2.0, mouse_motion: Vec2::ONE, gamepads: vec![&gamepad], ..default() });
+    assert_eq!(actions.axis("zoom"), 2.0);
+    assert_eq!(actions.axis_2d("move"), Vec2::new(0.5, 0.5));
+    // Mouse motion only counts while the button is held.
+    assert_eq!(actions.axis_2d("look"), Vec2::ZERO);
+}
+
+#[test]
+fn test_default_profile_uses_input_map_keys() {
+    let inputs = InputMap { jump: "KeyE".to_string(), ..InputMap::default() };
+    let profile = InputProfile::from_input_map(&inputs);
+    for (action, kind) in [(MOVE_ACTION, "Axis2d"), (TURN_ACTION, "Axis"), (CAMERA_ORBIT_ACTION, "Axis2d"), (INTERACT_ACTION, "Button")] {
+        assert_eq!(profile.actions[action].kind(), kind);
+    }
+
+    let mut actions = InputActions::new(profile);
+    let pressed = keys(&[KeyCode::KeyE, KeyCode::KeyD]);
+    actions.update(&InputSources { keys: Some(&pressed), ..default() });
+    assert!(actions.pressed(JUMP_ACTION));
+    assert_eq!(actions.axis(TURN_ACTION), 1.0);
+    // `KeyE` also strafes right in the default map.
+    assert_eq!(actions.axis_2d(MOVE_ACTION), Vec2::new(1.0, 0.0));
+}
+
+#[test]
+fn test_sticks_are_scaled_to_mouse_pixels_in_mouse_actions() {
+    let profile = InputProfile::default();
+    let stick_pixels = profile.stick_pixels;
+    let mut actions = InputActions::new(profile);
+    let mut gamepad = Gamepad::default();
+    gamepad.analog_mut().set(GamepadInput::Axis(GamepadAxis::LeftStickY), 0.5);
+    gamepad.analog_mut().set(GamepadInput::Axis(GamepadAxis::RightStickX), 0.5);
+
+    actions.update(&InputSources { gamepads: vec![&gamepad], ..default() });
+    assert_eq!(actions.axis_2d(MOVE_ACTION), Vec2::new(0.0, 0.5));
+    assert_eq!(actions.axis_2d(CAMERA_ORBIT_ACTION), Vec2::new(0.5 * stick_pixels, 0.0));
+}
+
+#[test]
+fn test_sample_profile_parses() {
+    let profile: InputProfile = from_versioned_str(include_str!("../../../assets/input/default.ron"))
+        .expect("Failed to deserialize assets/input/default.ron");
+    assert!(matches!(&profile.actions[JUMP_ACTION], InputAction::Button(bindings) if bindings.len() == 2));
+    assert!(profile.actions.values().all(|action| action.named_inputs().iter().all(|(_, _, known)| *known)));
+}
+
+#[test]
+fn test_every_key_code_parses_by_name_and_alias() {
+    for (name, key) in KEY_CODES {
+        assert_eq!(parse_key(name), Ok(*key), "{}", name);
+        assert_eq!(key_name(*key), Some(*name));
+    }
+    assert_eq!(parse_key("W"), Ok(KeyCode::KeyW));
+    assert_eq!(parse_key("7"), Ok(KeyCode::Digit7));
+    assert_eq!(parse_key("Up"), Ok(KeyCode::ArrowUp));
+    assert_eq!(parse_key("Esc"), Ok(KeyCode::Escape));
+    assert_eq!(parse_key("ctrl"), Ok(KeyCode::ControlLeft));
+    assert_eq!(parse_key("/"), Ok(KeyCode::Slash));
+    assert_eq!(parse_key("f12"), Ok(KeyCode::F12));
+}
+
+#[test]
+fn test_unknown_keys_suggest_the_closest_name() {
+    let error = parse_key("ArowUp").unwrap_err();
+    assert_eq!(error.suggestion, Some("ArrowUp"));
+    assert_eq!(error.to_string(), r#"unknown key "ArowUp", did you mean "ArrowUp"?"#);
+    assert_eq!(parse_key("Pgdown").unwrap_err().suggestion, Some("PageDown"));
+    assert_eq!(parse_key("Banana").unwrap_err().suggestion, None);
+
+    let error = ron::from_str::<InputMap>(
+        r#"(forward: "KeyW", backward: "KeyS", left: "KeyA", right: "KeyD", strafe_left: "KeyQ", strafe_right: "KeyE", jump: "Spce")"#,
+    )
+    .unwrap_err();
+    assert!(error.to_string().contains(r#"did you mean "Space"?"#), "{}", error);
+}
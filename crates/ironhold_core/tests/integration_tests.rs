@@ -1,14 +1,23 @@
 use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use std::time::Duration;
 use ironhold_core::GamePlugin;
-use ironhold_core::runtime::{UiMessage, Action, ActionQueue};
-use ironhold_core::schema::AppState;
+use ironhold_core::runtime::{capture_snapshot, read_save_game, PendingLoad, SaveGame, restore_snapshot, save_input_overrides, InputRecorder, InputReplay, state_hash, MemoryStorage, StorageBackend, Action, EventId, GameEvent, InputActions, ActionQueue, Behavior, LoadFailure, LoadFailureKind, ErrorScreenButton, GameRng, GlobalLogicState, TransformInterpolation, HotReload, Variables};
+use ironhold_core::schema::{from_versioned_str, AppState, ButtonBinding, ButtonMode, Steering, InputOverrides, InputRecording, RecordedFrame, ColliderShape, EntityId, GameLevel, LevelHandle, ModelInfo, UiAction, Value};
+use ironhold_core::capabilities::{AnimationController, CharacterController, Collider, Locomotion, OrbitCamera};
+use std::collections::HashMap;
 use ironhold_core::ProjectConfigPath;
 
+/// Every frame advances time by one tick at the default tick rate, so fixed
+/// gameplay ticks run once per `app.update()`.
+const TICK: TimeUpdateStrategy = TimeUpdateStrategy::ManualDuration(Duration::from_nanos(16_666_667));
+
 #[test]
 fn test_ui_button_to_load_scene_action() {
     let mut app = App::new();
     
     app.add_plugins(MinimalPlugins)
+       .insert_resource(TICK)
        .add_plugins(bevy::state::app::StatesPlugin)
        .add_plugins(AssetPlugin::default())
        .add_message::<bevy::input::mouse::MouseMotion>()
@@ -25,8 +34,13 @@ fn test_ui_button_to_load_scene_action() {
     // 1. Run once to process Startup (setup)
     app.update();
     
-    // 2. Simulate Button Press Message
-    app.world_mut().resource_mut::<Messages<UiMessage>>().write(UiMessage::ButtonPressed("test_scene.ron".to_string()));
+    // 2. Press a LoadScene button
+    app.world_mut().spawn((
+        Button,
+        BackgroundColor::default(),
+        UiAction::LoadScene("test_scene.ron".to_string()),
+        Interaction::Pressed,
+    ));
     
     // 3. Run systems (Interpreter + Executor will run)
     app.update();
@@ -47,6 +61,7 @@ fn test_ui_button_to_load_scene_action() {
 fn test_action_to_state_transition() {
     let mut app = App::new();
     app.add_plugins(MinimalPlugins)
+       .insert_resource(TICK)
        .add_plugins(bevy::state::app::StatesPlugin)
        .add_plugins(AssetPlugin::default())
        .add_message::<bevy::input::mouse::MouseMotion>()
@@ -84,3 +99,1151 @@ fn test_action_to_state_transition() {
     let state = app.world().resource::<State<AppState>>();
     assert_eq!(*state.get(), AppState::LoadingScene);
 }
+
+fn build_test_app_with_assets(assets_dir: &std::path::Path, project: &str) -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+       .insert_resource(TICK)
+       .add_plugins(bevy::state::app::StatesPlugin)
+       .add_plugins(AssetPlugin {
+           file_path: assets_dir.to_string_lossy().to_string(),
+           ..default()
+       })
+       .add_message::<bevy::input::mouse::MouseMotion>()
+       .add_message::<bevy::input::mouse::MouseWheel>()
+       .init_resource::<ButtonInput<KeyCode>>()
+       .init_resource::<ButtonInput<MouseButton>>()
+       .init_resource::<Assets<Mesh>>()
+       .init_resource::<Assets<StandardMaterial>>()
+       .init_asset::<Gltf>()
+       .init_asset::<Scene>()
+       .init_resource::<Assets<AnimationGraph>>()
+       .insert_resource(ProjectConfigPath(project.to_string()))
+       .add_plugins(GamePlugin);
+    app
+}
+
+/// A project starting in `scenes/main.ron`.
+const PROJECT: &str = "(schema_version: 1, initial_scene: \"scenes/main.ron\")";
+
+/// Writes `files`, by path relative to the assets root, into a fresh
+/// temporary assets folder and returns the folder.
+fn write_assets(name: &str, files: &[(&str, &str)]) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("ironhold_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    for (path, contents) in files {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+    dir
+}
+
+/// Runs frames until the app reaches `target` (asset IO is asynchronous).
+fn update_until_state(app: &mut App, target: AppState) -> bool {
+    for _ in 0..200 {
+        app.update();
+        if *app.world().resource::<State<AppState>>().get() == target {
+            return true;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+    false
+}
+
+#[test]
+fn test_missing_project_enters_error_state() {
+    let dir = write_assets("error_project", &[]);
+
+    let mut app = build_test_app_with_assets(&dir, "missing_project.ron");
+    assert!(update_until_state(&mut app, AppState::Error), "Never reached AppState::Error");
+
+    let failure = app.world().resource::<LoadFailure>();
+    assert_eq!(failure.kind, LoadFailureKind::Project);
+    assert_eq!(failure.path, "missing_project.ron");
+}
+
+#[test]
+fn test_broken_scene_enters_error_state_with_position() {
+    let dir = write_assets("error_scene", &[
+        ("project.ron", "(schema_version: 1, initial_scene: \"scenes/broken.ron\")"),
+        ("scenes/broken.ron", "(\n    schema_version: 1,\n    models: [\n        (path: \"a.glb\" position: (0.0, 0.0, 0.0)),\n    ],\n)"),
+    ]);
+
+    let mut app = build_test_app_with_assets(&dir, "project.ron");
+    assert!(update_until_state(&mut app, AppState::Error), "Never reached AppState::Error");
+
+    let failure = app.world().resource::<LoadFailure>().clone();
+    assert_eq!(failure.kind, LoadFailureKind::Scene);
+    assert_eq!(failure.path, "scenes/broken.ron");
+    assert_eq!(failure.position.map(|(line, _)| line), Some(4));
+
+    // Fallback UI is spawned and offers both buttons.
+    let buttons: Vec<ErrorScreenButton> = app
+        .world_mut()
+        .query::<&ErrorScreenButton>()
+        .iter(app.world())
+        .copied()
+        .collect();
+    assert!(buttons.contains(&ErrorScreenButton::Retry));
+    assert!(buttons.contains(&ErrorScreenButton::BackToInitialScene));
+}
+
+#[test]
+fn test_retry_reloads_the_scene_with_the_broken_include() {
+    let dir = write_assets("error_include", &[
+        ("project.ron", PROJECT),
+        ("scenes/main.ron", "(schema_version: 1, includes: [\"scenes/part.ron\"])"),
+        ("scenes/part.ron", "(schema_version: 1, models: [(path: \"a.glb\" position: (0.0, 0.0, 0.0))])"),
+    ]);
+
+    let mut app = build_test_app_with_assets(&dir, "project.ron");
+    assert!(update_until_state(&mut app, AppState::Error), "Never reached AppState::Error");
+    let failure = app.world().resource::<LoadFailure>().clone();
+    assert_eq!(failure.path, "scenes/part.ron");
+    assert_eq!(failure.asset, "scenes/main.ron");
+
+    // Retry loads the scene again, not the include on its own.
+    let retry = app
+        .world_mut()
+        .query::<(Entity, &ErrorScreenButton)>()
+        .iter(app.world())
+        .find(|(_, button)| **button == ErrorScreenButton::Retry)
+        .map(|(entity, _)| entity)
+        .unwrap();
+    app.world_mut().entity_mut(retry).insert(Interaction::Pressed);
+    assert!(update_until_state(&mut app, AppState::LoadingScene), "Retry never loaded a scene");
+    let level = app.world().resource::<LevelHandle>().0.clone();
+    let path = app.world().resource::<AssetServer>().get_path(&level).unwrap();
+    assert_eq!(path.to_string(), "scenes/main.ron");
+}
+
+const HOT_RELOAD_SCENE: &str = r#"(
+    schema_version: 1,
+    models: [
+        (id: Some("chest"), path: "models/chest.glb#Scene0", position: (4.0, 0.0, 0.0), rotation: Euler(0.0, 45.0, 0.0)),
+    ],
+    player: Some((
+        model_path: "models/hero.glb#Scene0",
+        initial_position: (0.0, 0.0, 0.0),
+        camera: (
+            offset: (0.0, 5.0, 10.0),
+            look_at_offset: (0.0, 2.0, 0.0),
+            zoom_speed: 10.0,
+            orbit_speed: 0.5,
+            min_radius: 2.0,
+            max_radius: 20.0,
+        ),
+        inputs: (
+            forward: "KeyW",
+            backward: "KeyS",
+            left: "KeyA",
+            right: "KeyD",
+            strafe_left: "KeyQ",
+            strafe_right: "KeyE",
+            jump: "Space",
+        ),
+        animations: (
+            idle: "Idle",
+            walk: "Walk",
+            run: "Run",
+            jump_enter: "JumpEnter",
+            jump_loop: "JumpLoop",
+            jump_exit: "JumpExit",
+            death: "Death",
+            dance: "Dance",
+            crouch_idle: "CrouchIdle",
+            crouch_forward: "CrouchForward",
+            roll: "Roll",
+        ),
+    )),
+)"#;
+
+#[test]
+fn test_hot_reload_respawns_level_and_keeps_player() {
+    let dir = write_assets("hot_reload", &[
+        ("project.ron", "(schema_version: 1, initial_scene: \"scenes/main.ron\", hot_reload: true)"),
+        ("scenes/main.ron", HOT_RELOAD_SCENE),
+    ]);
+
+    let mut app = build_test_app_with_assets(&dir, "project.ron");
+    assert!(update_until_state(&mut app, AppState::InGame), "Never reached AppState::InGame");
+    app.update();
+    assert!(app.world().resource::<HotReload>().enabled);
+
+    // Move the player and the camera away from their spawn values.
+    let moved = Transform::from_xyz(1.0, 0.0, -3.0);
+    {
+        let world = app.world_mut();
+        let mut player = world.query_filtered::<&mut Transform, With<CharacterController>>();
+        *player.single_mut(world).unwrap() = moved;
+        let mut camera = world.query::<&mut OrbitCamera>();
+        camera.single_mut(world).unwrap().yaw = 1.25;
+    }
+
+    // Edit the level asset in place, as a file change would.
+    let handle = app.world().resource::<LevelHandle>().0.clone();
+    app.world_mut()
+        .resource_mut::<Assets<GameLevel>>()
+        .get_mut(&handle)
+        .unwrap()
+        .models
+        .push(ModelInfo {
+            path: "models/chest.glb#Scene0".to_string(),
+            position: (-4.0, 0.0, 0.0),
+            ..Default::default()
+        });
+    app.update();
+    app.update();
+
+    let world = app.world_mut();
+    let model_count = world
+        .query_filtered::<(), (With<SceneRoot>, Without<CharacterController>)>()
+        .iter(world)
+        .count();
+    assert_eq!(model_count, 2);
+
+    let mut ids: Vec<String> = world
+        .query::<(&Name, &EntityId)>()
+        .iter(world)
+        .map(|(name, id)| {
+            assert_eq!(name.as_str(), id.0);
+            id.0.clone()
+        })
+        .collect();
+    ids.sort();
+    assert_eq!(ids, ["chest", "player", "player_camera"]);
+
+    let mut player = world.query_filtered::<&Transform, With<CharacterController>>();
+    assert_eq!(player.single(world).unwrap().translation, moved.translation);
+    let mut camera = world.query::<&OrbitCamera>();
+    assert_eq!(camera.single(world).unwrap().yaw, 1.25);
+    assert_eq!(*world.resource::<State<AppState>>().get(), AppState::InGame);
+}
+
+#[test]
+fn test_movement_blocks_configure_spawned_characters() {
+    let scene = HOT_RELOAD_SCENE
+        .replacen("        ),\n    )),", "        ),\n        movement: (walk_speed: 2.0, run_mode: Hold),\n    )),", 1)
+        .replacen(
+            "    player: Some((",
+            "    entities: [(id: \"npc\", capabilities: [CharacterController(movement: (crouch_speed: 1.0, steering: CameraRelative))])],\n    player: Some((",
+            1,
+        );
+    let dir = write_assets("movement", &[
+        ("project.ron", PROJECT),
+        ("scenes/main.ron", &scene),
+    ]);
+
+    let mut app = build_test_app_with_assets(&dir, "project.ron");
+    assert!(update_until_state(&mut app, AppState::InGame), "Never reached AppState::InGame");
+    app.update();
+
+    let world = app.world_mut();
+    let controllers: HashMap<String, (f32, f32, ButtonMode, Steering)> = world
+        .query::<(&EntityId, &CharacterController)>()
+        .iter(world)
+        .map(|(id, c)| (id.0.clone(), (c.movement.walk_speed, c.movement.crouch_speed, c.movement.run_mode, c.movement.steering)))
+        .collect();
+    assert_eq!(controllers["player"], (2.0, 1.5, ButtonMode::Hold, Steering::Tank));
+    assert_eq!(controllers["npc"], (3.0, 1.0, ButtonMode::Toggle, Steering::CameraRelative));
+}
+
+const ENTITY_SCENE: &str = r#"(
+    schema_version: 1,
+    entities: [
+        (
+            id: "npc",
+            model: Some("models/npc.glb#Scene0"),
+            position: (2.0, 0.0, 1.0),
+            capabilities: [
+                CharacterController(walk_speed: 1.5),
+                Collider(shape: Capsule(0.4, 0.6)),
+            ],
+        ),
+        (
+            id: "npc_camera",
+            capabilities: [
+                OrbitCamera(
+                    target: "npc",
+                    camera: (
+                        offset: (0.0, 3.0, 4.0),
+                        look_at_offset: (0.0, 1.0, 0.0),
+                        zoom_speed: 10.0,
+                        orbit_speed: 0.5,
+                        min_radius: 2.0,
+                        max_radius: 20.0,
+                    ),
+                ),
+            ],
+        ),
+        (id: "lamp", position: (0.0, 4.0, 0.0), capabilities: [PointLight(intensity: 5000.0, shadows: true)]),
+    ],
+)"#;
+
+#[test]
+fn test_entities_spawn_with_capabilities() {
+    let dir = write_assets("entities", &[
+        ("project.ron", PROJECT),
+        ("scenes/main.ron", ENTITY_SCENE),
+    ]);
+
+    let mut app = build_test_app_with_assets(&dir, "project.ron");
+    assert!(update_until_state(&mut app, AppState::InGame), "Never reached AppState::InGame");
+    app.update();
+
+    let world = app.world_mut();
+    let mut ids = HashMap::new();
+    for (entity, id) in world.query::<(Entity, &EntityId)>().iter(world) {
+        ids.insert(id.0.clone(), entity);
+    }
+    assert_eq!(ids.len(), 3);
+
+    let npc = world.entity(ids["npc"]);
+    let controller = npc.get::<CharacterController>().unwrap();
+    assert_eq!(controller.movement.walk_speed, 1.5);
+    assert!(controller.inputs.is_none());
+    assert_eq!(npc.get::<Transform>().unwrap().translation, Vec3::new(2.0, 0.0, 1.0));
+    assert_eq!(npc.get::<Collider>().unwrap().shape, ColliderShape::Capsule(0.4, 0.6));
+    assert!(npc.contains::<SceneRoot>());
+
+    let camera = world.entity(ids["npc_camera"]);
+    assert_eq!(camera.get::<OrbitCamera>().unwrap().target, ids["npc"]);
+    assert!(camera.contains::<Camera3d>());
+
+    let lamp = world.entity(ids["lamp"]);
+    assert!(lamp.get::<PointLight>().unwrap().shadows_enabled);
+    assert!(!lamp.contains::<SceneRoot>());
+
+    // The entity camera replaces the default one.
+    assert_eq!(world.query::<&Camera3d>().iter(world).count(), 1);
+}
+
+#[test]
+fn test_prefabs_and_includes_are_spawned() {
+    let dir = write_assets("prefabs", &[
+        ("project.ron", PROJECT),
+        ("prefabs/lamp_post.ron", r#"(
+            schema_version: 1,
+            params: { "range": 10.0 },
+            children: [(id: "bulb", position: (0.0, 3.0, 0.0), capabilities: [PointLight(range: Param("range"))])],
+        )"#),
+        ("prefabs/camera.ron", r#"(
+            schema_version: 1,
+            params: { "target": "nobody" },
+            capabilities: [OrbitCamera(target: Param("target"), camera: (offset: (0.0, 3.0, 4.0), look_at_offset: (0.0, 1.0, 0.0), zoom_speed: 10.0, orbit_speed: 0.5, min_radius: 2.0, max_radius: 20.0))],
+        )"#),
+        ("scenes/shared/street.ron", r#"(
+            schema_version: 1,
+            prefabs: [(prefab: "prefabs/lamp_post.ron", id: Some("lamp"), position: (5.0, 0.0, 0.0), params: { "range": 25.0 })],
+        )"#),
+        ("scenes/main.ron", r#"(
+            schema_version: 1,
+            includes: ["scenes/shared/street.ron"],
+            prefabs: [(prefab: "prefabs/camera.ron", id: Some("cam"), params: { "target": "lamp" })],
+        )"#),
+    ]);
+
+    let mut app = build_test_app_with_assets(&dir, "project.ron");
+    let reached = update_until_state(&mut app, AppState::InGame);
+    assert!(reached, "Never reached AppState::InGame: {:?} {:?}", app.world().resource::<State<AppState>>().get(), app.world().get_resource::<LoadFailure>());
+    app.update();
+
+    let world = app.world_mut();
+    let mut ids = HashMap::new();
+    for (entity, id) in world.query::<(Entity, &EntityId)>().iter(world) {
+        ids.insert(id.0.clone(), entity);
+    }
+    assert_eq!(ids.len(), 3, "{:?}", ids.keys());
+
+    let bulb = world.entity(ids["lamp.bulb"]);
+    assert_eq!(bulb.get::<ChildOf>().unwrap().parent(), ids["lamp"]);
+    assert_eq!(bulb.get::<PointLight>().unwrap().range, 25.0);
+    assert_eq!(world.entity(ids["lamp"]).get::<Transform>().unwrap().translation.x, 5.0);
+    assert_eq!(world.entity(ids["cam"]).get::<OrbitCamera>().unwrap().target, ids["lamp"]);
+}
+
+#[test]
+fn test_reused_ids_spawn_only_the_first_entity() {
+    let dir = write_assets("duplicate_ids", &[
+        ("project.ron", PROJECT),
+        ("prefabs/lamp_post.ron", r#"(
+            schema_version: 2,
+            children: [(id: "bulb", position: (0.0, 3.0, 0.0))],
+        )"#),
+        ("scenes/main.ron", r#"(
+            schema_version: 2,
+            models: [
+                (id: Some("crate"), path: "models/crate.glb#Scene0", position: (1.0, 0.0, 0.0)),
+                (id: Some("crate"), path: "models/crate.glb#Scene0", position: (2.0, 0.0, 0.0)),
+            ],
+            entities: [(id: "crate"), (id: "npc", position: (3.0, 0.0, 0.0)), (id: "npc", position: (4.0, 0.0, 0.0))],
+            prefabs: [
+                (prefab: "prefabs/lamp_post.ron", id: Some("npc")),
+                (prefab: "prefabs/lamp_post.ron", id: Some("lamp"), position: (5.0, 0.0, 0.0)),
+                (prefab: "prefabs/lamp_post.ron", id: Some("lamp")),
+            ],
+        )"#),
+    ]);
+
+    let mut app = build_test_app_with_assets(&dir, "project.ron");
+    assert!(update_until_state(&mut app, AppState::InGame), "Never reached AppState::InGame");
+    app.update();
+
+    let world = app.world_mut();
+    let mut ids: HashMap<String, Vec<Entity>> = HashMap::new();
+    for (entity, id) in world.query::<(Entity, &EntityId)>().iter(world) {
+        ids.entry(id.0.clone()).or_default().push(entity);
+    }
+    let mut names: Vec<&str> = ids.keys().map(String::as_str).collect();
+    names.sort();
+    assert_eq!(names, ["crate", "lamp", "lamp.bulb", "npc"]);
+    assert!(ids.values().all(|entities| entities.len() == 1), "{:?}", ids);
+
+    // The first entity with each id is the one spawned.
+    let x = |id: &str| world.entity(ids[id][0]).get::<Transform>().unwrap().translation.x;
+    assert_eq!((x("crate"), x("npc"), x("lamp")), (1.0, 3.0, 5.0));
+    assert_eq!(world.entity(ids["lamp.bulb"][0]).get::<ChildOf>().unwrap().parent(), ids["lamp"][0]);
+}
+
+#[test]
+fn test_hot_reload_follows_only_the_active_level() {
+    let dir = write_assets("hot_reload_deps", &[
+        ("project.ron", "(schema_version: 1, initial_scene: \"scenes/main.ron\", hot_reload: true)"),
+        ("prefabs/lamp_post.ron", "(schema_version: 1)"),
+        ("scenes/shared/street.ron", r#"(
+            schema_version: 1,
+            prefabs: [(prefab: "prefabs/lamp_post.ron", id: Some("lamp"))],
+        )"#),
+        ("scenes/main.ron", "(schema_version: 1, includes: [\"scenes/shared/street.ron\"])"),
+    ]);
+
+    let mut app = build_test_app_with_assets(&dir, "project.ron");
+    assert!(update_until_state(&mut app, AppState::InGame), "Never reached AppState::InGame");
+    app.update();
+    let lamp = |app: &mut App| {
+        let world = app.world_mut();
+        world.query::<(Entity, &EntityId)>().iter(world).find(|(_, id)| id.0 == "lamp").unwrap().0
+    };
+    let spawned = lamp(&mut app);
+
+    // Edits to a level that is not playing leave the scene alone.
+    let other = app.world_mut().resource_mut::<Assets<GameLevel>>().add(GameLevel::default());
+    app.world_mut().resource_mut::<Assets<GameLevel>>().get_mut(&other).unwrap().ground_plane = None;
+    app.update();
+    app.update();
+    assert_eq!(lamp(&mut app), spawned);
+
+    // Edits to an included scene respawn the level.
+    let street = app.world().resource::<AssetServer>().get_handle::<GameLevel>("scenes/shared/street.ron").unwrap();
+    app.world_mut().resource_mut::<Assets<GameLevel>>().get_mut(&street).unwrap().ground_plane = None;
+    app.update();
+    app.update();
+    assert_ne!(lamp(&mut app), spawned);
+}
+
+#[test]
+fn test_global_logic_drives_scene_flow() {
+    let dir = write_assets("logic", &[
+        ("project.ron", r#"(schema_version: 1, initial_scene: "scenes/menu.ron", logic: Some("logic/flow.ron"))"#),
+        ("scenes/menu.ron", r#"(schema_version: 1, ui: [Button(text: "Play", action: Emit("ui.start"))])"#),
+        ("scenes/main.ron", "(schema_version: 1)"),
+        ("logic/flow.ron", r#"(
+            schema_version: 1,
+            initial_state: "menu",
+            states: [(name: "menu"), (name: "game"), (name: "pause", on_enter: [SetPaused(true)], on_exit: [SetPaused(false)])],
+            transitions: [
+                (from: "menu", event: "ui.start", to: Some("game"), actions: [LoadScene("scenes/main.ron")]),
+                (from: "game", event: "key.Escape", to: Some("pause")),
+                (from: "pause", event: "key.Escape", to: Some("game")),
+            ],
+        )"#),
+    ]);
+
+    let mut app = build_test_app_with_assets(&dir, "project.ron");
+    assert!(update_until_state(&mut app, AppState::InGame), "Never reached AppState::InGame");
+    for _ in 0..100 {
+        if app.world().resource::<GlobalLogicState>().current().is_some() {
+            break;
+        }
+        app.update();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+    assert_eq!(app.world().resource::<GlobalLogicState>().current(), Some("menu"));
+
+    app.world_mut().write_message(GameEvent::new(EventId::of("ui.start")));
+    app.update();
+    assert_eq!(app.world().resource::<GlobalLogicState>().current(), Some("game"));
+    let level = app.world().resource::<LevelHandle>().0.clone();
+    let path = app.world().resource::<AssetServer>().get_path(&level).unwrap();
+    assert_eq!(path.to_string(), "scenes/main.ron");
+
+    app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::Escape);
+    app.update();
+    assert_eq!(app.world().resource::<GlobalLogicState>().current(), Some("pause"));
+    assert!(app.world().resource::<Time<Virtual>>().is_paused());
+
+    {
+        let mut keys = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keys.release(KeyCode::Escape);
+        keys.clear();
+        keys.press(KeyCode::Escape);
+    }
+    app.update();
+    assert_eq!(app.world().resource::<GlobalLogicState>().current(), Some("game"));
+    assert!(!app.world().resource::<Time<Virtual>>().is_paused());
+}
+
+const BEHAVIOR_SCENE: &str = r#"(
+    schema_version: 1,
+    entities: [
+        (
+            id: "hero",
+            position: (10.0, 0.0, 0.0),
+            capabilities: [
+                CharacterController(inputs: Some((
+                    forward: "KeyW", backward: "KeyS", left: "KeyA", right: "KeyD",
+                    strafe_left: "KeyQ", strafe_right: "KeyE", jump: "Space",
+                ))),
+            ],
+        ),
+        (
+            id: "coin",
+            capabilities: [
+                Collider(shape: Sphere(1.0), trigger: true),
+                Behavior(machine: "behaviors/pickup.ron"),
+            ],
+        ),
+        (id: "lever", position: (10.0, 0.0, 1.0), capabilities: [Behavior(machine: "behaviors/lever.ron")]),
+        (id: "gate", position: (20.0, 0.0, 0.0), capabilities: [Behavior(machine: "behaviors/lever.ron")]),
+    ],
+)"#;
+
+#[test]
+fn test_behaviors_react_to_triggers_and_interaction() {
+    let dir = write_assets("behaviors", &[
+        ("project.ron", PROJECT),
+        ("scenes/main.ron", BEHAVIOR_SCENE),
+        ("behaviors/pickup.ron", &include_str!("../../../assets/behaviors/pickup.ron").replace("player", "hero")),
+        ("behaviors/lever.ron", r#"(
+            schema_version: 1,
+            initial_state: "off",
+            states: [(name: "off"), (name: "on")],
+            transitions: [
+                (from: "off", event: "interact", to: Some("on"), actions: [SendEvent("gate", "pulled")]),
+                (from: "off", event: "pulled", to: Some("on")),
+            ],
+        )"#),
+    ]);
+
+    let mut app = build_test_app_with_assets(&dir, "project.ron");
+    app.add_plugins(TransformPlugin);
+    assert!(update_until_state(&mut app, AppState::InGame), "Never reached AppState::InGame");
+
+    let mut ids = HashMap::new();
+    {
+        let world = app.world_mut();
+        for (entity, id) in world.query::<(Entity, &EntityId)>().iter(world) {
+            ids.insert(id.0.clone(), entity);
+        }
+    }
+    let state = |app: &App, id: &str| {
+        app.world().get::<Behavior>(ids[id]).and_then(|behavior| behavior.current().map(str::to_string))
+    };
+    for _ in 0..200 {
+        if state(&app, "coin").is_some() && state(&app, "lever").is_some() {
+            break;
+        }
+        app.update();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+    assert_eq!(state(&app, "coin").as_deref(), Some("waiting"));
+
+    // Pressing interact next to the lever pulls it, and the lever pulls the gate.
+    app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::KeyF);
+    app.update();
+    app.update();
+    assert_eq!(state(&app, "lever").as_deref(), Some("on"));
+    assert_eq!(state(&app, "gate").as_deref(), Some("on"));
+
+    // Walking onto the coin picks it up.
+    app.world_mut().get_mut::<Transform>(ids["hero"]).unwrap().translation = Vec3::new(0.5, 0.0, 0.0);
+    for _ in 0..3 {
+        app.update();
+    }
+    assert!(app.world().get_entity(ids["coin"]).is_err());
+}
+
+#[test]
+fn test_variables_drive_ui_visibility() {
+    let dir = write_assets("variables", &[
+        ("project.ron", r#"(schema_version: 1, initial_scene: "scenes/main.ron", variables: { "has_key": false, "coins": 5 })"#),
+        ("scenes/main.ron", r#"(
+            schema_version: 1,
+            variables: { "coins": 0, "doors_open": 0 },
+            ui: [Button(text: "Open vault", action: Emit("ui.vault"), visible: Some(Expr("has_key && coins >= 5")))],
+        )"#),
+    ]);
+
+    let mut app = build_test_app_with_assets(&dir, "project.ron");
+    assert!(update_until_state(&mut app, AppState::InGame), "Never reached AppState::InGame");
+    app.update();
+
+    let display = |app: &mut App| {
+        let world = app.world_mut();
+        world.query_filtered::<&Node, With<Button>>().single(world).unwrap().display
+    };
+    {
+        let variables = app.world().resource::<Variables>();
+        // Scene defaults do not overwrite project values.
+        assert_eq!(variables.get("coins"), Some(&Value::Int(5)));
+        assert_eq!(variables.get("doors_open"), Some(&Value::Int(0)));
+    }
+    assert_eq!(display(&mut app), Display::None);
+
+    {
+        let mut queue = app.world_mut().resource_mut::<ActionQueue>();
+        queue.push(Action::ToggleVar("has_key".to_string()));
+        queue.push(Action::AddVar("doors_open".to_string(), Value::Int(1)));
+    }
+    app.update();
+    app.update();
+    assert_eq!(app.world().resource::<Variables>().get("doors_open"), Some(&Value::Int(1)));
+    assert_eq!(display(&mut app), Display::Flex);
+}
+
+#[test]
+fn test_actions_spawn_move_and_open_menus() {
+    let dir = write_assets("actions", &[
+        ("project.ron", PROJECT),
+        ("prefabs/coin.ron", "(schema_version: 1, capabilities: [PointLight(intensity: 10.0)])"),
+        ("menus/pause.ron", r#"(
+            schema_version: 1,
+            elements: [Button(text: "Resume", action: Run([CloseUi("menus/pause.ron")]))],
+        )"#),
+        ("scenes/main.ron", r#"(
+            schema_version: 1,
+            entities: [
+                (id: "chest", position: (4.0, 0.0, 0.0)),
+                (
+                    id: "guard",
+                    model: Some("models/guard.glb#Scene0"),
+                    capabilities: [
+                        CharacterController(),
+                        AnimationController(animations: (
+                            idle: "Idle", walk: "Walk", run: "Run",
+                            jump_enter: "JumpEnter", jump_loop: "JumpLoop", jump_exit: "JumpExit",
+                            death: "Death", dance: "Dance",
+                            crouch_idle: "CrouchIdle", crouch_forward: "CrouchForward", roll: "Roll",
+                        )),
+                    ],
+                ),
+            ],
+        )"#),
+    ]);
+
+    let mut app = build_test_app_with_assets(&dir, "project.ron");
+    app.add_plugins(TransformPlugin);
+    assert!(update_until_state(&mut app, AppState::InGame), "Never reached AppState::InGame");
+    app.update();
+
+    let ids = |app: &mut App| {
+        let world = app.world_mut();
+        world.query::<(Entity, &EntityId)>().iter(world).map(|(e, id)| (id.0.clone(), e)).collect::<HashMap<_, _>>()
+    };
+    let run = |app: &mut App, actions: Vec<Action>| {
+        app.world_mut().resource_mut::<ActionQueue>().extend(actions);
+        for _ in 0..50 {
+            app.update();
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+    };
+
+    run(&mut app, vec![
+        Action::SpawnPrefab {
+            prefab: "prefabs/coin.ron".to_string(),
+            id: None,
+            at: Some("chest".to_string()),
+            position: (0.0, 1.0, 0.0),
+            rotation: None,
+            scale: None,
+            params: HashMap::new(),
+        },
+        Action::SetTransform { target: "chest".to_string(), position: Some((0.0, 0.0, -2.0)), rotation: None, scale: None },
+        Action::OpenUi("menus/pause.ron".to_string()),
+        Action::OpenUi("menus/pause.ron".to_string()),
+    ]);
+    let spawned = ids(&mut app);
+    let coin = app.world().entity(spawned["coin_0"]);
+    assert_eq!(coin.get::<Transform>().unwrap().translation, Vec3::new(4.0, 1.0, 0.0));
+    assert!(coin.contains::<PointLight>());
+    assert_eq!(app.world().entity(spawned["chest"]).get::<Transform>().unwrap().translation, Vec3::new(0.0, 0.0, -2.0));
+    let buttons = |app: &mut App| {
+        let world = app.world_mut();
+        world.query::<&UiAction>().iter(world).count()
+    };
+    assert_eq!(buttons(&mut app), 1);
+
+    // A second coin gets the next free default id.
+    run(&mut app, vec![
+        Action::SpawnPrefab {
+            prefab: "prefabs/coin.ron".to_string(),
+            id: None,
+            at: None,
+            position: (0.0, 0.0, 0.0),
+            rotation: None,
+            scale: None,
+            params: HashMap::new(),
+        },
+        Action::CloseUi("menus/pause.ron".to_string()),
+        Action::SetVelocity("chest".to_string(), (0.0, 2.0, 0.0)),
+    ]);
+    assert!(ids(&mut app).contains_key("coin_1"));
+
+    // An explicit id that is already in use is not spawned.
+    run(&mut app, vec![Action::SpawnPrefab {
+        prefab: "prefabs/coin.ron".to_string(),
+        id: Some("chest".to_string()),
+        at: None,
+        position: (0.0, 0.0, 0.0),
+        rotation: None,
+        scale: None,
+        params: HashMap::new(),
+    }]);
+    assert_eq!(ids(&mut app)["chest"], spawned["chest"]);
+    assert_eq!(app.world_mut().query::<&EntityId>().iter(app.world()).filter(|id| id.0 == "chest").count(), 1);
+    assert_eq!(buttons(&mut app), 0);
+    assert!(app.world().entity(spawned["chest"]).get::<Transform>().unwrap().translation.y > 0.0);
+
+    let locomotion = |app: &App| app.world().entity(spawned["guard"]).get::<CharacterController>().unwrap().locomotion;
+    let clip = |app: &App| app.world().entity(spawned["guard"]).get::<AnimationController>().unwrap().current.clone();
+    run(&mut app, vec![Action::PlayAnimation("guard".to_string(), "Wave".to_string())]);
+    assert_eq!(clip(&app), "Wave");
+    run(&mut app, vec![Action::Kill("guard".to_string())]);
+    assert_eq!((locomotion(&app), clip(&app)), (Locomotion::Dead, "Death".to_string()));
+    run(&mut app, vec![Action::Revive("guard".to_string())]);
+    assert_eq!((locomotion(&app), clip(&app)), (Locomotion::Standing, "Idle".to_string()));
+
+    // Actions after a "self" target despawns itself are skipped.
+    app.world_mut().resource_mut::<ActionQueue>().extend_scoped(spawned["chest"], [
+        Action::Despawn("self".to_string()),
+        Action::SetVelocity("self".to_string(), (1.0, 0.0, 0.0)),
+    ]);
+    app.update();
+    run(&mut app, vec![Action::SetVelocity("chest".to_string(), (1.0, 0.0, 0.0))]);
+    assert!(app.world().get_entity(spawned["chest"]).is_err());
+
+    app.world_mut().resource_mut::<ActionQueue>().push(Action::Quit);
+    app.update();
+    assert!(app.should_exit().is_some());
+}
+
+#[test]
+fn test_scheduled_actions_survive_scene_loads() {
+    let dir = write_assets("scheduler", &[
+        ("project.ron", PROJECT),
+        ("scenes/main.ron", "(schema_version: 1)"),
+        ("scenes/next.ron", "(schema_version: 1, variables: { \"visits\": 0 })"),
+    ]);
+
+    let mut app = build_test_app_with_assets(&dir, "project.ron");
+    assert!(update_until_state(&mut app, AppState::InGame), "Never reached AppState::InGame");
+
+    let script: Action = ron::from_str(r#"Sequence([
+        AddVar("visits", 1),
+        LoadScene("scenes/next.ron"),
+        WaitForEvent("ui.continue"),
+        Delay(0.05, SetVar("done", true)),
+    ])"#).unwrap();
+    app.world_mut().resource_mut::<ActionQueue>().push(script);
+    assert!(update_until_state(&mut app, AppState::LoadingScene));
+    assert!(update_until_state(&mut app, AppState::InGame), "Never reached the next scene");
+    // The variable was set before the scene load, which kept it.
+    assert_eq!(app.world().resource::<Variables>().get("visits"), Some(&Value::Int(1)));
+    assert_eq!(app.world().resource::<Variables>().get("done"), None);
+
+    app.world_mut().write_message(GameEvent::new(EventId::of("ui.continue")));
+    for _ in 0..30 {
+        app.update();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+    assert_eq!(app.world().resource::<Variables>().get("done"), Some(&Value::Bool(true)));
+}
+
+#[test]
+fn test_project_seed_drives_random_choices() {
+    let dir = write_assets("rng", &[
+        ("project.ron", "(schema_version: 1, initial_scene: \"scenes/main.ron\", seed: Some(7))"),
+        ("scenes/main.ron", "(schema_version: 1)"),
+    ]);
+
+    let mut app = build_test_app_with_assets(&dir, "project.ron");
+    assert!(update_until_state(&mut app, AppState::InGame), "Never reached AppState::InGame");
+    assert_eq!(app.world().resource::<GameRng>().seed(), 7);
+
+    let choices: Vec<Action> = (0..4).map(|i| Action::SetVar("loot".to_string(), Value::Int(i))).collect();
+    let mut expected = GameRng::new(7);
+    for _ in 0..3 {
+        app.world_mut().resource_mut::<ActionQueue>().push(Action::RandomChoice(choices.clone()));
+        app.update();
+        let Some(Action::SetVar(_, value)) = expected.choose(&choices) else { unreachable!() };
+        assert_eq!(app.world().resource::<Variables>().get("loot"), Some(value));
+    }
+}
+
+#[test]
+fn test_snapshot_restores_the_running_game() {
+    let dir = write_assets("snapshot", &[
+        ("project.ron", "(schema_version: 1, initial_scene: \"scenes/main.ron\", variables: { \"score\": 0 })"),
+        ("scenes/main.ron", HOT_RELOAD_SCENE),
+    ]);
+
+    let mut app = build_test_app_with_assets(&dir, "project.ron");
+    assert!(update_until_state(&mut app, AppState::InGame), "Never reached AppState::InGame");
+    app.update();
+    let snapshot = capture_snapshot(app.world_mut()).unwrap();
+    assert_eq!(snapshot.level.as_deref(), Some("scenes/main.ron"));
+    let player = &snapshot.entities["player"];
+    assert!(player.contains_key("Transform") && player.contains_key("CharacterController") && player.contains_key("AnimationController"));
+    let start = player_translation(&mut app);
+
+    app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::KeyW);
+    app.world_mut().resource_mut::<Variables>().set("score", Value::Int(5));
+    for _ in 0..5 {
+        app.update();
+    }
+    assert_ne!(player_translation(&mut app), start);
+    assert_ne!(capture_snapshot(app.world_mut()).unwrap().checksum(), snapshot.checksum());
+
+    restore_snapshot(app.world_mut(), &snapshot).unwrap();
+    assert_eq!(player_translation(&mut app), start);
+    assert_eq!(capture_snapshot(app.world_mut()).unwrap().checksum(), snapshot.checksum());
+}
+
+#[test]
+fn test_saved_games_load_their_level_and_state() {
+    let dir = write_assets("save", &[
+        ("project.ron", "(schema_version: 1, initial_scene: \"scenes/main.ron\", variables: { \"score\": 0 })"),
+        ("scenes/main.ron", HOT_RELOAD_SCENE),
+        ("scenes/menu.ron", "(schema_version: 1)"),
+    ]);
+
+    let mut app = build_test_app_with_assets(&dir, "project.ron");
+    assert!(update_until_state(&mut app, AppState::InGame), "Never reached AppState::InGame");
+    app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::KeyW);
+    for _ in 0..5 {
+        app.update();
+    }
+    app.world_mut().resource_mut::<ButtonInput<KeyCode>>().release(KeyCode::KeyW);
+    app.world_mut().resource_mut::<Variables>().set("score", Value::Int(5));
+    app.world_mut().resource_mut::<ActionQueue>().push(Action::SaveGame("slot1".to_string()));
+    for _ in 0..3 {
+        app.update();
+    }
+    let saved = player_translation(&mut app);
+    assert_ne!(saved, Vec3::ZERO);
+
+    let save = read_save_game(app.world().resource::<StorageBackend>().0.as_ref(), "slot1").unwrap();
+    assert_eq!(save.scene, "scenes/main.ron");
+    assert_eq!(save.schema_version, 1);
+    assert!(save.saved_at > 0);
+    assert_eq!(save.snapshot.variables["score"], Value::Int(5));
+
+    app.world_mut().resource_mut::<Variables>().set("score", Value::Int(9));
+    app.world_mut().resource_mut::<ActionQueue>().push(Action::LoadScene("scenes/menu.ron".to_string()));
+    assert!(update_until_state(&mut app, AppState::LoadingScene));
+    assert!(update_until_state(&mut app, AppState::InGame), "Never reached the menu");
+
+    // Loading an empty slot leaves the game as it is.
+    app.world_mut().resource_mut::<ActionQueue>().push(Action::LoadGame("slot2".to_string()));
+    app.update();
+    assert_eq!(*app.world().resource::<State<AppState>>().get(), AppState::InGame);
+
+    app.world_mut().resource_mut::<ActionQueue>().push(Action::LoadGame("slot1".to_string()));
+    assert!(update_until_state(&mut app, AppState::LoadingScene));
+    assert!(update_until_state(&mut app, AppState::InGame), "Never loaded the saved level");
+    app.update();
+    assert_eq!(player_translation(&mut app), saved);
+    assert_eq!(app.world().resource::<Variables>().get("score"), Some(&Value::Int(5)));
+
+    // A save whose level fails to load is dropped, not restored over the next level.
+    let mut broken = save.clone();
+    broken.scene = "scenes/missing.ron".to_string();
+    let document = broken.to_ron().unwrap();
+    app.world_mut().resource_mut::<StorageBackend>().0.write(&SaveGame::storage_key("slot3"), &document).unwrap();
+    app.world_mut().resource_mut::<ActionQueue>().push(Action::LoadGame("slot3".to_string()));
+    assert!(update_until_state(&mut app, AppState::Error), "The missing level never failed");
+    assert!(!app.world().contains_resource::<PendingLoad>());
+}
+
+#[test]
+fn test_project_input_profile_drives_actions() {
+    let dir = write_assets("input", &[
+        ("project.ron", r#"(schema_version: 1, initial_scene: "scenes/main.ron", input: Some("input/profile.ron"))"#),
+        ("input/profile.ron", r#"(
+            schema_version: 1,
+            actions: { "wave": Button([Key("KeyQ"), Mouse("Middle")]) },
+        )"#),
+        ("scenes/main.ron", "(schema_version: 1)"),
+    ]);
+
+    let mut app = build_test_app_with_assets(&dir, "project.ron");
+    assert!(update_until_state(&mut app, AppState::InGame), "Never reached AppState::InGame");
+    for _ in 0..20 {
+        app.update();
+        if app.world().resource::<InputActions>().profile().actions.contains_key("wave") {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+    assert_eq!(app.world().resource::<InputActions>().profile().actions.len(), 1);
+
+    app.world_mut().resource_mut::<ButtonInput<MouseButton>>().press(MouseButton::Middle);
+    app.update();
+    assert!(app.world().resource::<InputActions>().just_pressed("wave"));
+    app.update();
+    let actions = app.world().resource::<InputActions>();
+    assert!(actions.pressed("wave") && !actions.just_pressed("wave"));
+}
+
+#[test]
+fn test_saved_input_overrides_apply_to_spawned_characters() {
+    let dir = write_assets("overrides", &[
+        ("project.ron", PROJECT),
+        ("scenes/main.ron", r#"(
+            schema_version: 1,
+            entities: [
+                (id: "hero", capabilities: [CharacterController(inputs: Some((
+                    forward: "KeyW", backward: "KeyS", left: "KeyA", right: "KeyD",
+                    strafe_left: "KeyQ", strafe_right: "KeyE", jump: "Space",
+                )))]),
+            ],
+        )"#),
+    ]);
+
+    let mut overrides = InputOverrides::default();
+    overrides.bindings.insert("jump".to_string(), ButtonBinding::Key("KeyJ".to_string()));
+    overrides.bindings.insert("interact".to_string(), ButtonBinding::Gamepad("North".to_string()));
+    let mut storage = MemoryStorage::default();
+    save_input_overrides(&overrides, &mut storage);
+
+    let mut app = build_test_app_with_assets(&dir, "project.ron");
+    app.insert_resource(StorageBackend(Box::new(storage)));
+    assert!(update_until_state(&mut app, AppState::InGame), "Never reached AppState::InGame");
+    app.update();
+
+    let world = app.world_mut();
+    let controller = world.query::<&CharacterController>().single(world).unwrap();
+    let inputs = controller.inputs.as_ref().unwrap();
+    assert_eq!(inputs.jump, "KeyJ");
+    assert_eq!(inputs.forward, "KeyW");
+
+    let profile = app.world().resource::<InputActions>().profile();
+    assert_eq!(profile.input_bindings("jump")[0], &ButtonBinding::Key("KeyJ".to_string()));
+    assert!(profile.input_bindings("interact").contains(&&ButtonBinding::Gamepad("North".to_string())));
+
+    app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::KeyJ);
+    app.update();
+    assert!(app.world().resource::<InputActions>().just_pressed("jump"));
+}
+
+/// Walks the player forward for one second at `frame_rate` frames per second,
+/// with 32 ticks per second, and returns how far the simulation moved them
+/// and their walk speed.
+fn walk_for_a_second(dir: &std::path::Path, frame_rate: u32) -> (f32, f32) {
+    let mut app = build_test_app_with_assets(dir, "project.ron");
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs(1) / frame_rate));
+    assert!(update_until_state(&mut app, AppState::InGame), "Never reached AppState::InGame");
+    app.update();
+
+    let simulated = |app: &mut App| {
+        let world = app.world_mut();
+        let mut player = world.query::<(&TransformInterpolation, &CharacterController)>();
+        let (interpolation, controller) = player.single(world).unwrap();
+        (interpolation.current.translation, controller.movement.walk_speed)
+    };
+    let (start, walk_speed) = simulated(&mut app);
+    app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::KeyW);
+    for _ in 0..frame_rate {
+        app.update();
+    }
+
+    // What is shown lies between the last two ticks.
+    let world = app.world_mut();
+    let mut player = world.query_filtered::<(&Transform, &TransformInterpolation), With<CharacterController>>();
+    let (shown, interpolation) = player.single(world).unwrap();
+    let t = world.resource::<Time<Fixed>>().overstep_fraction();
+    assert_eq!(*shown, interpolation.blend(t));
+
+    let (end, _) = simulated(&mut app);
+    (start.distance(end), walk_speed)
+}
+
+#[test]
+fn test_movement_speed_does_not_depend_on_frame_rate() {
+    let dir = write_assets("tick", &[
+        ("project.ron", "(schema_version: 1, initial_scene: \"scenes/main.ron\", tick_rate: 32.0)"),
+        ("scenes/main.ron", HOT_RELOAD_SCENE),
+    ]);
+
+    for frame_rate in [16, 32, 64, 128] {
+        let (distance, walk_speed) = walk_for_a_second(&dir, frame_rate);
+        assert!((distance - walk_speed).abs() < 1e-4, "Walked {} at {} fps, expected {}", distance, frame_rate, walk_speed);
+    }
+}
+
+fn player_translation(app: &mut App) -> Vec3 {
+    let world = app.world_mut();
+    let mut player = world.query_filtered::<&Transform, With<CharacterController>>();
+    player.single(world).unwrap().translation
+}
+
+/// Replays `recording` in a fresh app and returns the final state hash and
+/// where the player ended up.
+fn replay(dir: &std::path::Path, recording: InputRecording) -> (u64, Vec3) {
+    let mut app = build_test_app_with_assets(dir, "project.ron");
+    app.insert_resource(InputReplay::new(recording));
+    assert!(update_until_state(&mut app, AppState::InGame), "Never reached AppState::InGame");
+    for _ in 0..200 {
+        app.update();
+        if let Some(hash) = app.world().resource::<InputReplay>().final_hash {
+            return (hash, player_translation(&mut app));
+        }
+    }
+    panic!("Replay never finished");
+}
+
+#[test]
+fn test_replays_hand_time_back_and_stop_on_load_failures() {
+    let dir = write_assets("replay_end", &[
+        ("project.ron", PROJECT),
+        ("broken.ron", "(schema_version: 1, initial_scene: \"scenes/broken.ron\")"),
+        ("scenes/main.ron", "(schema_version: 1)"),
+        ("scenes/broken.ron", "(schema_version: 1, models: [(path: \"a.glb\" position: (0.0, 0.0, 0.0))])"),
+    ]);
+    let recording = |initial_scene: &str| {
+        let mut recording = InputRecording::new("project.ron".to_string(), initial_scene.to_string(), 7);
+        recording.frames.push(RecordedFrame { delta: 1.0 / 60.0, actions: Default::default() });
+        recording
+    };
+    let replayed = |project: &str, recording: InputRecording| {
+        let mut app = build_test_app_with_assets(&dir, project);
+        app.insert_resource(InputReplay::new(recording));
+        for _ in 0..200 {
+            app.update();
+            if app.should_exit().is_some() || app.world().resource::<InputReplay>().final_hash.is_some() {
+                break;
+            }
+        }
+        app
+    };
+
+    // After the last frame the player has control, on the clock's time.
+    let mut app = replayed("project.ron", recording("scenes/main.ron"));
+    assert!(app.world().resource::<InputReplay>().final_hash.is_some());
+    app.update();
+    assert!(matches!(app.world().resource::<TimeUpdateStrategy>(), TimeUpdateStrategy::Automatic));
+
+    // Replays recorded elsewhere, or whose game fails to load, exit with an error.
+    let app = replayed("project.ron", recording("scenes/other.ron"));
+    assert_eq!(app.should_exit(), Some(AppExit::error()));
+    let app = replayed("broken.ron", recording("scenes/broken.ron"));
+    assert_eq!(app.should_exit(), Some(AppExit::error()));
+}
+
+#[test]
+fn test_recorded_movement_replays_deterministically() {
+    let dir = write_assets("replay", &[
+        ("project.ron", PROJECT),
+        ("scenes/main.ron", HOT_RELOAD_SCENE),
+    ]);
+    let file = dir.join("walk.ron");
+
+    // Record walking forward for a few frames, then standing still.
+    let mut app = build_test_app_with_assets(&dir, "project.ron");
+    app.insert_resource(InputRecorder::new(&file));
+    assert!(update_until_state(&mut app, AppState::InGame), "Never reached AppState::InGame");
+    app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::KeyW);
+    for _ in 0..5 {
+        app.update();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+    }
+    app.world_mut().resource_mut::<ButtonInput<KeyCode>>().release(KeyCode::KeyW);
+    app.update();
+    let recorded_position = player_translation(&mut app);
+    app.world_mut().write_message(AppExit::Success);
+    app.update();
+
+    let recording: InputRecording = from_versioned_str(&std::fs::read_to_string(&file).unwrap()).unwrap();
+    assert_eq!(recording.project, "project.ron");
+    assert_eq!(recording.initial_scene, "scenes/main.ron");
+    // Recording starts with the frame that enters the game.
+    assert_eq!(recording.frames.len(), 8);
+    assert!(recording.frames[0].actions.is_empty());
+    assert_eq!(recording.frames[1].actions.get("move"), Some(&(0.0, 1.0)));
+    assert!(recording.frames[6].actions.is_empty());
+    assert!(recorded_position.z < 0.0, "The player did not walk forward: {:?}", recorded_position);
+
+    // Replays move the player exactly as the recorded run did, every time.
+    let (hash, position) = replay(&dir, recording.clone());
+    assert_eq!(position, recorded_position);
+    assert_eq!(replay(&dir, recording).0, hash);
+}
+
+#[test]
+fn test_replayed_delays_span_scene_loads() {
+    let dir = write_assets("replay_load", &[
+        ("project.ron", r#"(schema_version: 1, initial_scene: "scenes/main.ron", logic: Some("logic/flow.ron"), variables: { "delayed": false })"#),
+        ("scenes/main.ron", "(schema_version: 1)"),
+        ("scenes/next.ron", "(schema_version: 1)"),
+        ("logic/flow.ron", r#"(
+            schema_version: 1,
+            initial_state: "start",
+            states: [(name: "start", timer: Some(0.05)), (name: "next")],
+            transitions: [
+                (from: "start", event: "timer", to: Some("next"), actions: [
+                    Delay(0.1, SetVar("delayed", true)),
+                    LoadScene("scenes/next.ron"),
+                ]),
+            ],
+        )"#),
+    ]);
+    let file = dir.join("load.ron");
+    let delayed = |app: &App| app.world().resource::<Variables>().get("delayed") == Some(&Value::Bool(true));
+
+    // Record until the delay fires; it starts before the load and ends after it.
+    let mut app = build_test_app_with_assets(&dir, "project.ron");
+    app.insert_resource(InputRecorder::new(&file));
+    assert!(update_until_state(&mut app, AppState::InGame), "Never reached AppState::InGame");
+    let mut loaded = false;
+    for _ in 0..200 {
+        app.update();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        if *app.world().resource::<State<AppState>>().get() == AppState::LoadingScene {
+            assert!(!delayed(&app), "The delay ran on while the scene loaded");
+            loaded = true;
+        }
+        if delayed(&app) {
+            break;
+        }
+    }
+    assert!(loaded && delayed(&app), "The delay never spanned a scene load");
+    app.world_mut().write_message(AppExit::Success);
+    app.update();
+    let recorded_hash = state_hash(&AppState::InGame, std::iter::empty(), app.world().resource::<Variables>());
+
+    // Without the loading frames, the replay still fires the delay on the same frame.
+    let recording: InputRecording = from_versioned_str(&std::fs::read_to_string(&file).unwrap()).unwrap();
+    let mut app = build_test_app_with_assets(&dir, "project.ron");
+    app.insert_resource(InputReplay::new(recording));
+    for _ in 0..200 {
+        app.update();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        if app.world().resource::<InputReplay>().final_hash.is_some() {
+            break;
+        }
+    }
+    assert!(delayed(&app));
+    assert_eq!(app.world().resource::<InputReplay>().final_hash, Some(recorded_hash));
+}
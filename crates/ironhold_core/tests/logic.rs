@@ -0,0 +1,79 @@
+use ironhold_core::runtime::{step, Action, ActionQueue, ConditionContext, EventId, GlobalLogicState};
+use ironhold_core::schema::{from_versioned_str, Condition, GlobalLogic};
+
+const LOGIC: &str = r#"
+    (
+        schema_version: 1,
+        initial_state: "menu",
+        states: [
+            (name: "menu", on_enter: [LoadScene("scenes/menu.ron")]),
+            (name: "game", on_exit: [SetPaused(false)]),
+            (name: "pause", on_enter: [SetPaused(true)]),
+        ],
+        transitions: [
+            (from: "menu", event: "ui.start", guard: Some(SceneIs("scenes/menu.ron")), to: Some("game"), actions: [LoadScene("scenes/main.ron")]),
+            (from: "game", event: "key.Escape", to: Some("pause")),
+            (from: "*", event: "ui.reload", actions: [LoadScene("scenes/main.ron")]),
+        ],
+    )
+"#;
+
+#[test]
+fn test_transition_collects_exit_transition_and_enter_actions() {
+    let logic: GlobalLogic = from_versioned_str(LOGIC).expect("Failed to deserialize GlobalLogic");
+    let mut context = ConditionContext { scene: Some("scenes/menu.ron"), ..Default::default() };
+
+    let start = step(&logic.states, &logic.transitions, "menu", EventId::of("ui.start"), &mut context).unwrap();
+    assert_eq!(start.to.as_deref(), Some("game"));
+    assert_eq!(start.actions, vec![Action::LoadScene("scenes/main.ron".to_string())]);
+
+    let pause = step(&logic.states, &logic.transitions, "game", EventId::of("key.Escape"), &mut context).unwrap();
+    assert_eq!(pause.actions, vec![Action::SetPaused(false), Action::SetPaused(true)]);
+
+    // Transitions without a target keep the state and skip exit/enter.
+    let reload = step(&logic.states, &logic.transitions, "pause", EventId::of("ui.reload"), &mut context).unwrap();
+    assert_eq!(reload.to, None);
+    assert_eq!(reload.actions.len(), 1);
+
+    assert!(step(&logic.states, &logic.transitions, "pause", EventId::of("ui.start"), &mut context).is_none());
+}
+
+#[test]
+fn test_guards() {
+    let logic: GlobalLogic = from_versioned_str(LOGIC).unwrap();
+    let mut elsewhere = ConditionContext { scene: Some("scenes/other.ron"), ..Default::default() };
+    assert!(step(&logic.states, &logic.transitions, "menu", EventId::of("ui.start"), &mut elsewhere).is_none());
+
+    let condition = Condition::All(vec![
+        Condition::SceneIs("a.ron".to_string()),
+        Condition::Not(Box::new(Condition::Any(vec![Condition::SceneIs("b.ron".to_string())]))),
+    ]);
+    assert!(condition.evaluate(&mut ConditionContext { scene: Some("a.ron"), ..Default::default() }));
+    assert!(!condition.evaluate(&mut ConditionContext { scene: None, ..Default::default() }));
+}
+
+#[test]
+fn test_logic_state_starts_once() {
+    let logic: GlobalLogic = from_versioned_str(LOGIC).unwrap();
+    let mut state = GlobalLogicState::default();
+    let mut queue = ActionQueue::default();
+    assert_eq!(state.current(), None);
+
+    state.start(&logic, &mut queue);
+    state.start(&logic, &mut queue);
+    assert_eq!(state.current(), Some("menu"));
+    assert_eq!(queue.actions().collect::<Vec<_>>(), [&Action::LoadScene("scenes/menu.ron".to_string())]);
+
+    let mut context = ConditionContext { scene: Some("scenes/menu.ron"), ..Default::default() };
+    assert!(state.handle_event(&logic, EventId::of("ui.start"), &mut context, &mut queue));
+    assert!(!state.handle_event(&logic, EventId::of("ui.unknown"), &mut context, &mut queue));
+    assert_eq!(state.current(), Some("game"));
+}
+
+#[test]
+fn test_sample_flow_parses() {
+    let logic: GlobalLogic = from_versioned_str(include_str!("../../../assets/logic/flow.ron"))
+        .expect("Failed to deserialize assets/logic/flow.ron");
+    let names: Vec<&str> = logic.states.iter().map(|state| state.name.as_str()).collect();
+    assert_eq!(names, ["menu", "game", "pause", "game_over"]);
+}
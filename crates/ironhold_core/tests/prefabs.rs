@@ -0,0 +1,120 @@
+use ironhold_core::schema::{
+    from_versioned_str, CapabilityBlock, GameLevel, ParamValue, Prefab, PrefabInstance, Scale,
+};
+use std::collections::HashMap;
+
+const NPC_PREFAB: &str = r#"
+    (
+        schema_version: 1,
+        params: {
+            "speed": 2.5,
+            "lamp_color": (1.0, 0.5, 0.0),
+        },
+        // Param("speed") in a comment is left alone.
+        model: Some("models/npc.glb#Scene0"),
+        scale: Uniform(2.0),
+        capabilities: [CharacterController(walk_speed: Param("speed"))],
+        children: [
+            (id: "lamp", position: (0.0, 2.0, 0.0), capabilities: [PointLight(color: Param("lamp_color"))]),
+        ],
+    )
+"#;
+
+fn walk_speed(blocks: &[CapabilityBlock]) -> f32 {
+    match blocks {
+        [CapabilityBlock::CharacterController { movement, .. }] => movement.walk_speed,
+        other => panic!("Unexpected capabilities: {:?}", other),
+    }
+}
+
+#[test]
+fn test_prefab_defaults() {
+    let prefab: Prefab = from_versioned_str(NPC_PREFAB).expect("Failed to load prefab");
+    assert_eq!(prefab.params["speed"], ParamValue::Number(2.5));
+    assert_eq!(walk_speed(&prefab.template.capabilities), 2.5);
+
+    let instance = PrefabInstance {
+        prefab: "prefabs/npc.ron".to_string(),
+        position: (1.0, 0.0, 2.0),
+        ..Default::default()
+    };
+    let (root, children) = prefab.instantiate(&instance, "guard").unwrap();
+    assert_eq!(root.id, "guard");
+    assert_eq!(root.model.as_deref(), Some("models/npc.glb#Scene0"));
+    assert_eq!(root.position, (1.0, 0.0, 2.0));
+    assert_eq!(root.scale, Scale::Uniform(2.0));
+    assert_eq!(children.len(), 1);
+    assert_eq!(children[0].id, "guard.lamp");
+}
+
+#[test]
+fn test_prefab_instance_overrides() {
+    let prefab: Prefab = from_versioned_str(NPC_PREFAB).unwrap();
+    let instance = PrefabInstance {
+        prefab: "prefabs/npc.ron".to_string(),
+        scale: Some(Scale::Uniform(1.0)),
+        params: HashMap::from([("speed".to_string(), ParamValue::Number(4.0))]),
+        ..Default::default()
+    };
+    let (root, children) = prefab.instantiate(&instance, "runner").unwrap();
+    assert_eq!(walk_speed(&root.capabilities), 4.0);
+    assert_eq!(root.scale, Scale::Uniform(1.0));
+    assert!(matches!(
+        children[0].capabilities[..],
+        [CapabilityBlock::PointLight { color: (1.0, 0.5, 0.0), .. }]
+    ));
+
+    let unknown = PrefabInstance {
+        params: HashMap::from([("sped".to_string(), ParamValue::Number(4.0))]),
+        ..instance
+    };
+    let err = prefab.instantiate(&unknown, "runner").unwrap_err();
+    assert!(err.to_string().contains("unknown parameter \"sped\""), "{}", err);
+}
+
+#[test]
+fn test_undeclared_placeholder_is_an_error() {
+    let source = r#"(schema_version: 1, capabilities: [CharacterController(walk_speed: Param("speed"))])"#;
+    let err = from_versioned_str::<Prefab>(source).unwrap_err();
+    assert!(err.to_string().contains("\"speed\" is not declared"), "{}", err);
+}
+
+#[test]
+fn test_scene_prefab_instances_and_includes() {
+    let ron_str = r#"
+        (
+            schema_version: 1,
+            includes: ["scenes/shared/player.ron"],
+            prefabs: [
+                (prefab: "prefabs/npc.ron", position: (3.0, 0.0, 0.0), params: { "speed": 1.0 }),
+                (prefab: "prefabs/npc.ron", id: Some("boss"), rotation: Some(Euler(0.0, 90.0, 0.0))),
+            ]
+        )
+    "#;
+    let level: GameLevel = from_versioned_str(ron_str).expect("Failed to deserialize prefab instances");
+    assert_eq!(level.includes, ["scenes/shared/player.ron"]);
+    assert_eq!(level.prefabs[0].params["speed"], ParamValue::Number(1.0));
+    assert_eq!(level.prefabs[0].id_or_default(0), "npc_0");
+    assert_eq!(level.prefabs[1].id_or_default(1), "boss");
+
+    let shared: GameLevel = from_versioned_str(r#"(
+        schema_version: 1,
+        includes: ["scenes/main.ron"],
+        entities: [(id: "sun", capabilities: [DirectionalLight()])],
+        prefabs: [(prefab: "prefabs/player.ron", id: Some("player"))],
+    )"#).unwrap();
+    // The cycle back to the including scene is skipped.
+    let flat = level.flattened("scenes/main.ron", &|path| match path {
+        "scenes/shared/player.ron" => Some(shared.clone()),
+        "scenes/main.ron" => Some(level.clone()),
+        _ => None,
+    });
+    assert_eq!(flat.entities.len(), 1);
+    let ids: Vec<String> = flat
+        .prefabs
+        .iter()
+        .enumerate()
+        .map(|(index, instance)| instance.id_or_default(index))
+        .collect();
+    assert_eq!(ids, ["player", "npc_1", "boss"]);
+}
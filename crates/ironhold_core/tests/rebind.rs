@@ -0,0 +1,148 @@
+use bevy::input::mouse::{MouseMotion, MouseWheel};
+use bevy::prelude::*;
+use ironhold_core::capabilities::{rebind_label_system, rebind_system, CharacterController, RebindButton};
+use ironhold_core::runtime::{input_actions_system, load_input_overrides, save_input_overrides, InputActions, MemoryStorage, PersistentStorage, StorageBackend};
+use ironhold_core::schema::{ButtonBinding, InputAction, InputMap, InputOverrides, InputProfile, MovementConfig, JUMP_ACTION, MOVE_ACTION};
+
+fn key(name: &str) -> ButtonBinding {
+    ButtonBinding::Key(name.to_string())
+}
+
+fn gamepad(name: &str) -> ButtonBinding {
+    ButtonBinding::Gamepad(name.to_string())
+}
+
+#[test]
+fn test_rebind_replaces_bindings_of_the_same_kind() {
+    let mut profile = InputProfile::default();
+
+    assert!(profile.rebind("jump", key("KeyJ")));
+    assert_eq!(profile.actions[JUMP_ACTION], InputAction::Button(vec![key("KeyJ"), gamepad("South")]));
+    assert!(profile.rebind("interact", ButtonBinding::Mouse("Middle".to_string())));
+    assert_eq!(profile.input_bindings("interact").len(), 3);
+
+    // `InputMap` inputs name one direction of `move` or `turn`.
+    assert!(profile.rebind("forward", key("ArrowUp")));
+    assert_eq!(profile.input_bindings("forward"), vec![&key("ArrowUp")]);
+    assert!(profile.rebind("right", gamepad("East")));
+    assert_eq!(profile.input_bindings("right"), vec![&key("KeyD"), &gamepad("East")]);
+    // Conflicts are listed by input name.
+    assert_eq!(profile.inputs_bound_to(&gamepad("East")), vec!["roll".to_string(), "right".to_string()]);
+
+    // Axes only rebind buttons they already have.
+    assert!(!profile.rebind("forward", gamepad("North")));
+    assert!(!profile.rebind("fly", key("KeyZ")));
+    assert!(matches!(&profile.actions[MOVE_ACTION], InputAction::Axis2d(bindings) if bindings.len() == 2));
+}
+
+#[test]
+fn test_overrides_round_trip_through_storage() {
+    let mut overrides = InputOverrides::default();
+    overrides.bindings.insert("jump".to_string(), key("KeyJ"));
+    overrides.bindings.insert("run".to_string(), gamepad("RightThumb"));
+
+    let mut storage = MemoryStorage::default();
+    assert_eq!(load_input_overrides(&storage), InputOverrides::default());
+    save_input_overrides(&overrides, &mut storage);
+    assert_eq!(load_input_overrides(&storage), overrides);
+
+    // Only keys apply to an `InputMap`.
+    let mut inputs = InputMap::default();
+    overrides.apply_to_map(&mut inputs);
+    assert_eq!(inputs.jump, "KeyJ");
+    assert_eq!(inputs.run, "ShiftLeft");
+
+    storage.write(InputOverrides::STORAGE_KEY, r#"(bindings: {"jump": Key("Spacebar")})"#).unwrap();
+    assert_eq!(load_input_overrides(&storage), InputOverrides::default());
+}
+
+fn rebind_app() -> (App, Entity, Entity) {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .init_resource::<ButtonInput<KeyCode>>()
+        .init_resource::<ButtonInput<MouseButton>>()
+        .init_resource::<InputActions>()
+        .init_resource::<InputOverrides>()
+        .init_resource::<StorageBackend>()
+        .add_message::<MouseMotion>()
+        .add_message::<MouseWheel>()
+        .add_systems(Update, (rebind_system, input_actions_system, rebind_label_system).chain());
+    let character = app
+        .world_mut()
+        .spawn(CharacterController {
+            movement: MovementConfig { walk_speed: 1.0, run_speed: 2.0, rot_speed: 1.0, ..default() },
+            inputs: Some(InputMap::default()),
+            ..default()
+        })
+        .id();
+    let button = app
+        .world_mut()
+        .spawn((Interaction::None, RebindButton::new("jump".to_string(), "Jump".to_string())))
+        .with_child(Text::new(""))
+        .id();
+    app.update();
+    (app, button, character)
+}
+
+fn label(app: &mut App, button: Entity) -> String {
+    let child = app.world().get::<Children>(button).unwrap()[0];
+    app.world().get::<Text>(child).unwrap().0.clone()
+}
+
+fn click(app: &mut App, button: Entity) {
+    *app.world_mut().get_mut::<Interaction>(button).unwrap() = Interaction::Pressed;
+    app.update();
+    *app.world_mut().get_mut::<Interaction>(button).unwrap() = Interaction::None;
+    app.update();
+}
+
+fn press(app: &mut App, key: KeyCode) {
+    let mut keys = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+    keys.reset_all();
+    keys.press(key);
+    app.update();
+}
+
+#[test]
+fn test_rebind_button_binds_the_next_press_and_saves_it() {
+    let (mut app, button, character) = rebind_app();
+    assert_eq!(label(&mut app, button), "Jump: Space");
+
+    click(&mut app, button);
+    assert!(app.world().get::<RebindButton>(button).unwrap().is_listening());
+    assert_eq!(label(&mut app, button), "Jump: ...");
+
+    press(&mut app, KeyCode::KeyJ);
+    assert!(!app.world().get::<RebindButton>(button).unwrap().is_listening());
+    assert_eq!(label(&mut app, button), "Jump: KeyJ");
+    let inputs = app.world().get::<CharacterController>(character).unwrap().inputs.clone().unwrap();
+    assert_eq!(inputs.jump, "KeyJ");
+    assert_eq!(app.world().resource::<InputOverrides>().bindings["jump"], key("KeyJ"));
+
+    let storage = &app.world().resource::<StorageBackend>().0;
+    assert_eq!(load_input_overrides(storage.as_ref()).bindings["jump"], key("KeyJ"));
+
+    // Escape cancels without rebinding.
+    click(&mut app, button);
+    press(&mut app, KeyCode::Escape);
+    assert!(!app.world().get::<RebindButton>(button).unwrap().is_listening());
+    assert_eq!(label(&mut app, button), "Jump: KeyJ");
+}
+
+#[test]
+fn test_the_captured_press_does_not_reach_the_game() {
+    let (mut app, button, _) = rebind_app();
+    click(&mut app, button);
+    press(&mut app, KeyCode::KeyJ);
+    assert_eq!(label(&mut app, button), "Jump: KeyJ");
+    assert!(!app.world().resource::<ButtonInput<KeyCode>>().just_pressed(KeyCode::KeyJ));
+    assert!(!app.world().resource::<InputActions>().pressed(JUMP_ACTION));
+
+    // Still ignored while held, then bound once pressed again.
+    app.update();
+    assert!(!app.world().resource::<InputActions>().pressed(JUMP_ACTION));
+    app.world_mut().resource_mut::<ButtonInput<KeyCode>>().release_all();
+    app.update();
+    press(&mut app, KeyCode::KeyJ);
+    assert!(app.world().resource::<InputActions>().just_pressed(JUMP_ACTION));
+}
@@ -0,0 +1,76 @@
+use bevy::prelude::*;
+use std::collections::BTreeMap;
+use ironhold_core::runtime::{state_hash, InputActions, Variables};
+use ironhold_core::capabilities::OrbitCamera;
+use ironhold_core::schema::{from_versioned_str, AppState, EntityId, InputRecording, RecordedFrame, Value};
+
+#[test]
+fn test_recordings_round_trip_as_ron() {
+    let mut recording = InputRecording::new("project.ron".to_string(), "scenes/main.ron".to_string(), 42);
+    recording.frames.push(RecordedFrame { delta: 1.0 / 60.0, actions: BTreeMap::new() });
+    recording.frames.push(RecordedFrame {
+        delta: 0.016_667,
+        actions: BTreeMap::from([("move".to_string(), (0.0, 1.0)), ("jump".to_string(), (1.0, 0.0))]),
+    });
+
+    let document = recording.to_ron().unwrap();
+    // One frame per line.
+    assert_eq!(document.lines().filter(|line| line.contains("delta")).count(), 2, "{}", document);
+    let parsed: InputRecording = from_versioned_str(&document).unwrap();
+    assert_eq!(parsed, recording);
+}
+
+#[test]
+fn test_set_values_presses_and_releases_actions() {
+    let mut actions = InputActions::default();
+    actions.set_values(&BTreeMap::from([("jump".to_string(), Vec2::X), ("move".to_string(), Vec2::new(0.0, 0.5))]));
+    assert!(actions.just_pressed("jump"));
+    assert_eq!(actions.axis_2d("move"), Vec2::new(0.0, 0.5));
+    assert_eq!(actions.values().collect::<Vec<_>>(), vec![("jump", Vec2::X), ("move", Vec2::new(0.0, 0.5))]);
+
+    actions.set_values(&BTreeMap::from([("move".to_string(), Vec2::new(0.0, 0.5))]));
+    assert!(actions.just_released("jump"));
+    assert!(actions.pressed("move") && !actions.just_pressed("move"));
+}
+
+#[test]
+fn test_state_hash_ignores_entity_order_but_not_state() {
+    let a = (EntityId("a".to_string()), Transform::from_xyz(1.0, 0.0, 0.0));
+    let b = (EntityId("b".to_string()), Transform::from_xyz(0.0, 2.0, 0.0));
+    let mut variables = Variables::default();
+    variables.set("score", Value::Int(3));
+
+    let hash = state_hash(&AppState::InGame, [(&a.0, &a.1, None), (&b.0, &b.1, None)].into_iter(), &variables);
+    assert_eq!(hash, state_hash(&AppState::InGame, [(&b.0, &b.1, None), (&a.0, &a.1, None)].into_iter(), &variables));
+
+    let moved = Transform::from_xyz(1.0, 0.0, 0.001);
+    assert_ne!(hash, state_hash(&AppState::InGame, [(&a.0, &moved, None), (&b.0, &b.1, None)].into_iter(), &variables));
+    variables.set("score", Value::Int(4));
+    assert_ne!(hash, state_hash(&AppState::InGame, [(&a.0, &a.1, None), (&b.0, &b.1, None)].into_iter(), &variables));
+}
+
+#[test]
+fn test_state_hash_covers_the_orbit_camera() {
+    let camera = (EntityId("camera".to_string()), Transform::from_xyz(0.0, 2.0, 5.0));
+    let mut orbit = OrbitCamera {
+        target: Entity::PLACEHOLDER,
+        radius: 5.0,
+        offset: Vec3::new(0.0, 2.0, 5.0),
+        zoom_speed: 1.0,
+        orbit_speed: 1.0,
+        min_radius: 1.0,
+        max_radius: 10.0,
+        pitch: 0.5,
+        yaw: 0.0,
+        look_at_offset: Vec3::ZERO,
+        steer: 0.0,
+    };
+    let variables = Variables::default();
+
+    let hash = state_hash(&AppState::InGame, [(&camera.0, &camera.1, Some(&orbit))].into_iter(), &variables);
+    orbit.yaw = 0.25;
+    let turned = state_hash(&AppState::InGame, [(&camera.0, &camera.1, Some(&orbit))].into_iter(), &variables);
+    assert_ne!(hash, turned);
+    orbit.pitch = 0.75;
+    assert_ne!(turned, state_hash(&AppState::InGame, [(&camera.0, &camera.1, Some(&orbit))].into_iter(), &variables));
+}
@@ -0,0 +1,76 @@
+use ironhold_core::runtime::{Action, ConditionContext, GameRng};
+use ironhold_core::schema::{Condition, Expression, Value};
+
+#[test]
+fn test_same_seed_gives_same_sequence() {
+    // SplitMix64's reference output, so every platform draws the same numbers.
+    let mut rng = GameRng::new(0);
+    assert_eq!(rng.next_u64(), 0xe220_a839_7b1d_cdaf);
+    assert_eq!(rng.next_u64(), 0x6e78_9e6a_a1b9_65f4);
+
+    let mut a = GameRng::new(42);
+    let mut b = GameRng::new(42);
+    let first: Vec<u64> = (0..8).map(|_| a.next_u64()).collect();
+    assert_eq!(first, (0..8).map(|_| b.next_u64()).collect::<Vec<_>>());
+    assert_ne!(first[0], GameRng::new(43).next_u64());
+
+    // Restoring the state resumes the sequence.
+    let state = a.state();
+    let next = a.next_u64();
+    let mut restored = GameRng::new(42);
+    restored.set_state(state);
+    assert_eq!(restored.next_u64(), next);
+    assert_eq!(restored.seed(), 42);
+}
+
+#[test]
+fn test_ranges_and_choices() {
+    let mut rng = GameRng::new(7);
+    let mut seen = [false; 3];
+    for _ in 0..200 {
+        let x = rng.next_f32();
+        assert!((0.0..1.0).contains(&x));
+        assert!((2.0..5.0).contains(&rng.range(2.0, 5.0)));
+        seen[rng.below(3)] = true;
+    }
+    assert_eq!(seen, [true; 3]);
+    assert_eq!(rng.below(0), 0);
+    assert!(!rng.chance(0.0) && rng.chance(1.0));
+    assert_eq!(rng.choose::<u8>(&[]), None);
+    assert_eq!(rng.choose(&["only"]), Some(&"only"));
+}
+
+#[test]
+fn test_random_conditions_use_the_context_rng() {
+    let mut rng = GameRng::new(3);
+    let mut context = ConditionContext { rng: Some(&mut rng), ..Default::default() };
+    assert!(Condition::Random(1.0).evaluate(&mut context));
+    assert!(!Condition::Random(0.0).evaluate(&mut context));
+
+    // `random()` draws the same numbers as the RNG itself.
+    let mut expected = GameRng::new(3);
+    expected.next_f32();
+    expected.next_f32();
+    let below = expected.next_f64() < 0.5;
+    let expression = Expression::parse("random() < 0.5").unwrap();
+    assert_eq!(Condition::Expr(expression.clone()).evaluate(&mut context), below);
+    assert_eq!(rng.state(), expected.state());
+
+    // Without a random source they never hold.
+    let mut context = ConditionContext::default();
+    assert!(!Condition::Random(1.0).evaluate(&mut context));
+    assert!(!Condition::Expr(expression).evaluate(&mut context));
+    assert!(Expression::parse("random(1)").is_err());
+
+    let condition: Condition = ron::from_str("All([Random(0.25), Expr(\"random() >= 0.0\")])").unwrap();
+    assert!(matches!(condition, Condition::All(ref c) if c[0] == Condition::Random(0.25)));
+
+    let action: Action = ron::from_str("RandomChoice([SetVar(\"loot\", \"gem\"), AddVar(\"gold\", 5)])").unwrap();
+    assert_eq!(
+        action,
+        Action::RandomChoice(vec![
+            Action::SetVar("loot".to_string(), Value::String("gem".to_string())),
+            Action::AddVar("gold".to_string(), Value::Int(5)),
+        ])
+    );
+}
@@ -1,10 +1,15 @@
-use ironhold_core::schema::{ProjectConfig, GameLevel};
+use ironhold_core::schema::{
+    ButtonMode, CapabilityBlock, ColliderShape, Easing, GameLevel, MovementConfig, ProjectConfig, Rotation, Scale,
+    SpeedRamp, Steering,
+};
+use bevy::math::Vec3;
 use ron::de::from_str;
 
 #[test]
 fn test_project_config_deserialization() {
     let ron_str = r#"
         (
+            schema_version: 1,
             initial_scene: "scenes/main.ron"
         )
     "#;
@@ -27,6 +32,7 @@ fn test_invalid_project_config() {
 fn test_game_level_minimal() {
     let ron_str = r#"
         (
+            schema_version: 1,
             models: [],
             ui: [],
             player: None
@@ -41,6 +47,7 @@ fn test_game_level_minimal() {
 fn test_game_level_full() {
     let ron_str = r#"
         (
+            schema_version: 1,
             models: [
                 (
                     path: "models/cube.glb",
@@ -93,3 +100,118 @@ fn test_game_level_full() {
     assert_eq!(level.models.len(), 1);
     assert!(level.player.is_some());
 }
+
+#[test]
+fn test_model_transform_and_id() {
+    let ron_str = r#"
+        (
+            schema_version: 1,
+            models: [
+                (
+                    id: Some("chest_left"),
+                    path: "models/chest.glb#Scene0",
+                    position: (1.0, 0.0, 0.0),
+                    rotation: Euler(0.0, 90.0, 0.0),
+                    scale: Uniform(2.0)
+                ),
+                (
+                    path: "models/chest.glb#Scene0",
+                    position: (0.0, 0.0, 0.0),
+                    rotation: Quat(0.0, 0.0, 0.0, 1.0),
+                    scale: PerAxis(1.0, 2.0, 3.0)
+                ),
+                (
+                    path: "models/anvil.glb#Scene0",
+                    position: (0.0, 0.0, 0.0)
+                )
+            ]
+        )
+    "#;
+    let level: GameLevel = from_str(ron_str).expect("Failed to deserialize model transforms");
+    let [rotated, stretched, plain] = &level.models[..] else { panic!("Expected 3 models") };
+
+    assert_eq!(rotated.id.as_deref(), Some("chest_left"));
+    assert_eq!(rotated.rotation, Rotation::Euler(0.0, 90.0, 0.0));
+    let transform = rotated.transform();
+    assert!((transform.forward().as_vec3() - Vec3::NEG_X).length() < 1e-5);
+    assert_eq!(transform.scale, Vec3::splat(2.0));
+
+    assert_eq!(stretched.scale, Scale::PerAxis(1.0, 2.0, 3.0));
+    assert_eq!(stretched.transform().scale, Vec3::new(1.0, 2.0, 3.0));
+
+    assert!(plain.id.is_none());
+    assert_eq!(plain.transform(), bevy::prelude::Transform::IDENTITY);
+}
+
+#[test]
+fn test_entities_with_capabilities() {
+    let ron_str = r#"
+        (
+            schema_version: 1,
+            entities: [
+                (
+                    id: "torch",
+                    position: (1.0, 2.0, 3.0),
+                    capabilities: [
+                        PointLight(color: (1.0, 0.6, 0.2), range: 8.0),
+                        Collider(shape: Sphere(0.5), trigger: true)
+                    ]
+                ),
+                (
+                    id: "sun",
+                    rotation: Euler(-45.0, 30.0, 0.0),
+                    capabilities: [DirectionalLight(shadows: true)]
+                )
+            ]
+        )
+    "#;
+    let level: GameLevel = from_str(ron_str).expect("Failed to deserialize entities");
+    let [torch, sun] = &level.entities[..] else { panic!("Expected 2 entities") };
+
+    assert_eq!(torch.id, "torch");
+    assert!(torch.model.is_none());
+    assert_eq!(torch.transform().translation, Vec3::new(1.0, 2.0, 3.0));
+    match &torch.capabilities[..] {
+        [
+            CapabilityBlock::PointLight { color, intensity, range, shadows },
+            CapabilityBlock::Collider { shape, trigger },
+        ] => {
+            assert_eq!(*color, (1.0, 0.6, 0.2));
+            assert_eq!(*intensity, 1_000_000.0);
+            assert_eq!(*range, 8.0);
+            assert!(!shadows);
+            assert_eq!(*shape, ColliderShape::Sphere(0.5));
+            assert!(trigger);
+        }
+        other => panic!("Unexpected capabilities: {:?}", other),
+    }
+
+    assert!(matches!(
+        sun.capabilities[..],
+        [CapabilityBlock::DirectionalLight { shadows: true, .. }]
+    ));
+}
+
+#[test]
+fn test_movement_block() {
+    let movement: MovementConfig = from_str(r#"
+        (
+            run_speed: 8.0,
+            acceleration: (seconds: 0.4, curve: EaseInOut),
+            deceleration: (seconds: 0.2),
+            strafe_multiplier: 0.7
+        )
+    "#).expect("Failed to deserialize MovementConfig");
+    assert_eq!((movement.walk_speed, movement.run_speed, movement.strafe_multiplier), (3.0, 8.0, 0.7));
+    assert_eq!(movement.acceleration, SpeedRamp { seconds: 0.4, curve: Easing::EaseInOut });
+    assert_eq!(movement.deceleration, SpeedRamp { seconds: 0.2, curve: Easing::Linear });
+    assert_eq!((movement.run_mode, movement.steering), (ButtonMode::Toggle, Steering::Tank));
+    assert_eq!((movement.jump_speed, movement.air_control), (5.0, 0.3));
+
+    let block: CapabilityBlock = from_str("CharacterController(movement: (run_mode: Hold))").unwrap();
+    let CapabilityBlock::CharacterController { movement, inputs } = block else {
+        panic!("expected a CharacterController");
+    };
+    assert_eq!(movement, MovementConfig { run_mode: ButtonMode::Hold, ..Default::default() });
+    assert!(inputs.is_none());
+}
@@ -0,0 +1,122 @@
+use bevy::prelude::*;
+use ironhold_core::runtime::{Action, ActionQueue, ActionScheduler, EventId, GameRng, QueuedAction};
+use ironhold_core::schema::Value;
+
+fn set(name: &str, value: i64) -> Action {
+    Action::SetVar(name.to_string(), Value::Int(value))
+}
+
+fn actions(queued: Vec<QueuedAction>) -> Vec<Action> {
+    queued.into_iter().map(|queued| queued.action).collect()
+}
+
+#[test]
+fn test_queue_is_fifo() {
+    let mut queue = ActionQueue::default();
+    queue.push(set("a", 1));
+    queue.extend([set("b", 2), Action::LoadScene("scenes/next.ron".to_string())]);
+    queue.prepend(vec![QueuedAction { action: set("first", 0), source: None }]);
+
+    let order: Vec<Action> = std::iter::from_fn(|| queue.pop()).map(|queued| queued.action).collect();
+    assert_eq!(order, [set("first", 0), set("a", 1), set("b", 2), Action::LoadScene("scenes/next.ron".to_string())]);
+}
+
+#[test]
+fn test_sequence_and_delay() {
+    let mut scheduler = ActionScheduler::default();
+    let mut rng = GameRng::new(0);
+    let script: Action = ron::from_str(r#"Sequence([
+        SetVar("a", 1),
+        Delay(1.0, SetVar("b", 2)),
+        Delay(0.5, Sequence([SetVar("c", 3), SetVar("d", 4)])),
+        SetVar("e", 5),
+    ])"#).unwrap();
+
+    let source = Some(Entity::PLACEHOLDER);
+    let started = scheduler.start(script, source, &mut rng);
+    assert_eq!(started, [QueuedAction { action: set("a", 1), source }]);
+    assert_eq!(scheduler.running(), 1);
+
+    assert!(scheduler.step(0.6, &mut rng).is_empty());
+    assert_eq!(actions(scheduler.step(0.6, &mut rng)), [set("b", 2)]);
+    // 0.2s were left over from the first delay.
+    assert_eq!(actions(scheduler.step(0.3, &mut rng)), [set("c", 3), set("d", 4), set("e", 5)]);
+    assert_eq!(scheduler.running(), 0);
+}
+
+#[test]
+fn test_parallel_finishes_with_its_last_action() {
+    let mut scheduler = ActionScheduler::default();
+    let mut rng = GameRng::new(0);
+    let script = Action::Sequence(vec![
+        Action::Parallel(vec![
+            Action::Delay(2.0, Box::new(set("slow", 1))),
+            set("now", 1),
+            Action::Delay(1.0, Box::new(set("fast", 1))),
+        ]),
+        set("after", 1),
+    ]);
+
+    assert_eq!(actions(scheduler.start(script, None, &mut rng)), [set("now", 1)]);
+    assert_eq!(actions(scheduler.step(1.0, &mut rng)), [set("fast", 1)]);
+    assert_eq!(actions(scheduler.step(1.0, &mut rng)), [set("slow", 1), set("after", 1)]);
+    assert_eq!(scheduler.running(), 0);
+}
+
+#[test]
+fn test_wait_for_event() {
+    let mut scheduler = ActionScheduler::default();
+    let mut rng = GameRng::new(0);
+    let script = Action::Sequence(vec![
+        Action::WaitForEvent("ui.next".to_string()),
+        set("page", 2),
+        Action::WaitForEvent("ui.next".to_string()),
+        set("page", 3),
+    ]);
+    scheduler.start(script, None, &mut rng);
+
+    scheduler.notify(EventId::of("ui.back"));
+    assert!(scheduler.step(0.1, &mut rng).is_empty());
+    scheduler.notify(EventId::of("ui.next"));
+    // The second wait only counts events raised after it started.
+    assert_eq!(actions(scheduler.step(0.1, &mut rng)), [set("page", 2)]);
+    assert!(scheduler.step(0.1, &mut rng).is_empty());
+    scheduler.notify(EventId::of("ui.next"));
+    assert_eq!(actions(scheduler.step(0.1, &mut rng)), [set("page", 3)]);
+    assert_eq!(scheduler.running(), 0);
+}
+
+#[test]
+fn test_tasks_stop_with_their_source() {
+    let mut world = World::new();
+    let source = world.spawn_empty().id();
+    let mut scheduler = ActionScheduler::default();
+    let mut rng = GameRng::new(0);
+    scheduler.start(Action::Delay(1.0, Box::new(set("scoped", 1))), Some(source), &mut rng);
+    scheduler.start(Action::Delay(1.0, Box::new(set("global", 1))), None, &mut rng);
+
+    world.despawn(source);
+    scheduler.cancel_orphaned(|entity| world.get_entity(entity).is_ok());
+    assert_eq!(scheduler.running(), 1);
+    assert_eq!(actions(scheduler.step(1.0, &mut rng)), [set("global", 1)]);
+}
+
+#[test]
+fn test_random_choice_runs_in_its_sequence() {
+    let mut scheduler = ActionScheduler::default();
+    let mut rng = GameRng::new(7);
+    let script: Action = ron::from_str(r#"Sequence([
+        RandomChoice([Delay(1.0, SetVar("picked", 1))]),
+        SetVar("after", 1),
+    ])"#).unwrap();
+    assert!(scheduler.start(script, None, &mut rng).is_empty());
+    assert!(scheduler.step(0.5, &mut rng).is_empty());
+    assert_eq!(actions(scheduler.step(0.5, &mut rng)), [set("picked", 1), set("after", 1)]);
+
+    // The pick draws from the game's RNG when the choice is reached.
+    let choices = vec![set("loot", 0), set("loot", 1), set("loot", 2)];
+    let mut expected = rng.clone();
+    let picked = expected.choose(&choices).cloned().unwrap();
+    assert_eq!(actions(scheduler.start(Action::RandomChoice(choices), None, &mut rng)), [picked]);
+    assert_eq!(scheduler.running(), 0);
+}
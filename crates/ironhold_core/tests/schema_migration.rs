@@ -0,0 +1,139 @@
+use ironhold_core::schema::{
+    from_versioned_str, migrate_document, read_schema_version, set_schema_version, ButtonMode,
+    CapabilityBlock, GameLevel, ProjectConfig, SchemaError, VersionedAsset,
+};
+
+#[test]
+fn test_current_version_loads_unchanged() {
+    let ron_str = r#"
+        (
+            schema_version: 1,
+            initial_scene: "scenes/main.ron"
+        )
+    "#;
+    let config: ProjectConfig = from_versioned_str(ron_str).expect("Failed to load v1 ProjectConfig");
+    assert_eq!(config.schema_version, ProjectConfig::CURRENT_VERSION);
+    assert_eq!(config.initial_scene, "scenes/main.ron");
+}
+
+#[test]
+fn test_legacy_project_config_is_migrated() {
+    let ron_str = r#"
+        (
+            initial_scene: "scenes/main.ron"
+        )
+    "#;
+    assert_eq!(read_schema_version(ron_str).unwrap(), 0);
+    let config: ProjectConfig = from_versioned_str(ron_str).expect("Failed to migrate legacy ProjectConfig");
+    assert_eq!(config.schema_version, 1);
+    assert_eq!(config.initial_scene, "scenes/main.ron");
+}
+
+#[test]
+fn test_legacy_game_level_is_migrated() {
+    // Legacy scenes have no version field and may contain comments and enum payloads.
+    let ron_str = r#"
+        // start menu
+        (
+            models: [],
+            ui: [
+                Button(
+                    text: "Play (schema_version: 9)",
+                    action: LoadScene("scenes/game.ron")
+                )
+            ]
+        )
+    "#;
+    let level: GameLevel = from_versioned_str(ron_str).expect("Failed to migrate legacy GameLevel");
+    assert_eq!(level.schema_version, GameLevel::CURRENT_VERSION);
+    assert_eq!(level.ui.len(), 1);
+}
+
+#[test]
+fn test_future_version_is_rejected() {
+    let ron_str = r#"
+        (
+            schema_version: 99,
+            models: []
+        )
+    "#;
+    let result = from_versioned_str::<GameLevel>(ron_str);
+    match result {
+        Err(SchemaError::UnsupportedVersion { found, current, .. }) => {
+            assert_eq!(found, 99);
+            assert_eq!(current, GameLevel::CURRENT_VERSION);
+        }
+        other => panic!("Expected UnsupportedVersion, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn test_invalid_version_is_rejected() {
+    let ron_str = r#"(schema_version: "one", initial_scene: "a.ron")"#;
+    assert!(matches!(
+        migrate_document::<ProjectConfig>(ron_str),
+        Err(SchemaError::InvalidVersion(_))
+    ));
+}
+
+#[test]
+fn test_set_schema_version_replaces_existing_field() {
+    let ron_str = r#"(initial_scene: "schema_version: 4", schema_version: 4)"#;
+    let updated = set_schema_version(ron_str, 5).unwrap();
+    assert_eq!(updated, r#"(initial_scene: "schema_version: 4", schema_version: 5)"#);
+}
+
+#[test]
+fn test_v1_character_controllers_get_a_movement_block() {
+    let ron_str = r#"
+        (
+            schema_version: 1,
+            entities: [
+                (id: "npc", capabilities: [CharacterController(
+                    walk_speed: 2.0, // "slow", (walker)
+                    inputs: Some((
+                        forward: "KeyW", backward: "KeyS", left: "KeyA", right: "KeyD",
+                        strafe_left: "KeyQ", strafe_right: "KeyE", jump: "Space",
+                    )),
+                    crouch_mode: Hold,
+                )]),
+                (id: "idle", capabilities: [CharacterController()]),
+            ],
+            ui: [Button(text: "CharacterController(walk_speed: 9.0)", action: Emit("ui.start"))],
+        )
+    "#;
+    let migrated = migrate_document::<GameLevel>(ron_str).unwrap();
+    assert_eq!(migrated.lines().count(), ron_str.lines().count());
+    let level: GameLevel = from_versioned_str(ron_str).expect("Failed to migrate v1 GameLevel");
+    assert_eq!(level.schema_version, 2);
+    let CapabilityBlock::CharacterController { movement, inputs } = &level.entities[0].capabilities[0] else {
+        panic!("expected a CharacterController");
+    };
+    assert_eq!((movement.walk_speed, movement.run_speed, movement.crouch_mode), (2.0, 6.0, ButtonMode::Hold));
+    assert!(inputs.is_some());
+    assert!(matches!(&level.entities[1].capabilities[0], CapabilityBlock::CharacterController { inputs: None, .. }));
+}
+
+#[test]
+fn test_character_controller_migration_skips_raw_strings_and_chars() {
+    let ron_str = r##"(schema_version: 1, entities: [(id: "npc", capabilities: [CharacterController(
+        inputs: Some((
+            forward: r#"KeyW"#, backward: "KeyS", left: "KeyA", right: "KeyD",
+            strafe_left: "KeyQ", strafe_right: "KeyE", jump: "Space",
+        )),
+        walk_speed: 2.0,
+    )])])"##;
+    let level: GameLevel = from_versioned_str(ron_str).expect("Failed to migrate a raw string");
+    let CapabilityBlock::CharacterController { movement, inputs } = &level.entities[0].capabilities[0] else {
+        panic!("expected a CharacterController");
+    };
+    assert_eq!(movement.walk_speed, 2.0);
+    assert_eq!(inputs.as_ref().unwrap().forward, "KeyW");
+
+    // Brackets, quotes and commas inside literals do not end the block or a field.
+    let migrated = migrate_document::<GameLevel>(
+        r##"(schema_version: 1, a: CharacterController(inputs: Some((forward: r#"Key"),W"#)), key: '(', walk_speed: 2.0))"##,
+    ).unwrap();
+    let expected = r##"CharacterController(movement: (key: '(', walk_speed: 2.0), inputs: Some((forward: r#"Key"),W"#))))"##;
+    assert!(migrated.contains(expected), "{}", migrated);
+}
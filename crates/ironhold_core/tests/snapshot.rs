@@ -0,0 +1,107 @@
+use bevy::prelude::*;
+use ironhold_core::runtime::{capture_snapshot, restore_snapshot, GameRng, Snapshot, SnapshotError, SnapshotRegistry, Variables};
+use ironhold_core::schema::{from_versioned_str, EntityId, Value};
+use ironhold_core::capabilities::{OrbitCamera, Velocity};
+
+fn world() -> World {
+    let mut registry = SnapshotRegistry::default();
+    registry.register::<Transform>();
+    registry.register::<Velocity>();
+    let mut world = World::new();
+    world.insert_resource(registry);
+    world.insert_resource(GameRng::new(9));
+    world.insert_resource(Variables::default());
+    world.spawn((EntityId("crate".to_string()), Transform::from_xyz(1.0, 2.0, 3.0), Velocity(Vec3::X)));
+    world.spawn((EntityId("door".to_string()), Transform::from_rotation(Quat::from_rotation_y(0.5))));
+    world
+}
+
+#[test]
+fn test_snapshots_restore_exactly() {
+    let mut world = world();
+    world.resource_mut::<Variables>().set("score", Value::Int(3));
+    world.resource_mut::<GameRng>().next_u64();
+    let snapshot = capture_snapshot(&mut world).unwrap();
+    assert_eq!(snapshot.entities.len(), 2);
+    assert_eq!(snapshot.entities["door"].len(), 1);
+
+    // Change everything the snapshot covers.
+    let next = world.resource_mut::<GameRng>().next_u64();
+    world.resource_mut::<Variables>().set("score", Value::Int(4));
+    world.resource_mut::<Variables>().set("extra", Value::Bool(true));
+    let mut query = world.query::<(Entity, &EntityId)>();
+    let ids: Vec<(Entity, String)> = query.iter(&world).map(|(entity, id)| (entity, id.0.clone())).collect();
+    for (entity, id) in &ids {
+        world.entity_mut(*entity).get_mut::<Transform>().unwrap().translation.x += 5.0;
+        if id == "crate" {
+            world.entity_mut(*entity).remove::<Velocity>();
+        } else {
+            world.entity_mut(*entity).insert(Velocity(Vec3::Y));
+        }
+    }
+    world.spawn((EntityId("loot".to_string()), Transform::default()));
+    assert_ne!(capture_snapshot(&mut world).unwrap().checksum(), snapshot.checksum());
+
+    restore_snapshot(&mut world, &snapshot).unwrap();
+    let restored = capture_snapshot(&mut world).unwrap();
+    assert_eq!(restored, snapshot);
+    assert_eq!(restored.checksum(), snapshot.checksum());
+    assert_eq!(world.resource_mut::<GameRng>().next_u64(), next);
+    assert_eq!(world.resource::<Variables>().get("extra"), None);
+}
+
+#[test]
+fn test_snapshots_round_trip_through_ron() {
+    let mut world = world();
+    let snapshot = capture_snapshot(&mut world).unwrap();
+    let document = snapshot.to_ron().unwrap();
+    let parsed: Snapshot = from_versioned_str(&document).unwrap();
+    assert_eq!(parsed, snapshot);
+    assert_eq!(parsed.checksum(), snapshot.checksum());
+    // The checksum only depends on the state.
+    assert_eq!(capture_snapshot(&mut world).unwrap().checksum(), snapshot.checksum());
+}
+
+#[test]
+fn test_restore_reports_what_it_cannot_restore() {
+    let mut world = world();
+    let mut snapshot = capture_snapshot(&mut world).unwrap();
+    snapshot.entities.insert("ghost".to_string(), Default::default());
+    assert_eq!(restore_snapshot(&mut world, &snapshot), Err(SnapshotError::MissingEntity("ghost".to_string())));
+
+    let mut snapshot = capture_snapshot(&mut world).unwrap();
+    snapshot.level = Some("scenes/other.ron".to_string());
+    assert!(matches!(restore_snapshot(&mut world, &snapshot), Err(SnapshotError::LevelMismatch { .. })));
+
+    let mut snapshot = capture_snapshot(&mut world).unwrap();
+    let state = snapshot.entities["door"]["Transform"].clone();
+    snapshot.entities.get_mut("door").unwrap().insert("Health".to_string(), state);
+    assert_eq!(restore_snapshot(&mut world, &snapshot), Err(SnapshotError::UnknownComponent("Health".to_string())));
+}
+
+#[test]
+fn test_snapshots_restore_the_orbit_camera() {
+    let mut world = world();
+    world.resource_mut::<SnapshotRegistry>().register::<OrbitCamera>();
+    let camera = world.spawn((EntityId("camera".to_string()), Transform::default(), OrbitCamera {
+        target: Entity::PLACEHOLDER,
+        radius: 5.0,
+        offset: Vec3::new(0.0, 2.0, 5.0),
+        zoom_speed: 1.0,
+        orbit_speed: 1.0,
+        min_radius: 1.0,
+        max_radius: 10.0,
+        pitch: 0.5,
+        yaw: 1.0,
+        look_at_offset: Vec3::ZERO,
+        steer: 0.0,
+    })).id();
+    let snapshot = capture_snapshot(&mut world).unwrap();
+
+    let mut orbit = world.get_mut::<OrbitCamera>(camera).unwrap();
+    orbit.yaw = -2.0;
+    orbit.pitch = 1.2;
+    restore_snapshot(&mut world, &snapshot).unwrap();
+    let orbit = world.get::<OrbitCamera>(camera).unwrap();
+    assert_eq!((orbit.yaw, orbit.pitch), (1.0, 0.5));
+}
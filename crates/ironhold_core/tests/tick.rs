@@ -0,0 +1,14 @@
+use bevy::prelude::*;
+use ironhold_core::runtime::TransformInterpolation;
+
+#[test]
+fn test_interpolation_blends_the_last_two_ticks() {
+    let mut interpolation = TransformInterpolation::default();
+    interpolation.current = Transform::from_xyz(2.0, 0.0, -4.0).with_rotation(Quat::from_rotation_y(1.0));
+
+    assert_eq!(interpolation.blend(0.0), interpolation.previous);
+    assert_eq!(interpolation.blend(1.0).translation, interpolation.current.translation);
+    let halfway = interpolation.blend(0.5);
+    assert_eq!(halfway.translation, Vec3::new(1.0, 0.0, -2.0));
+    assert!(halfway.rotation.angle_between(Quat::from_rotation_y(0.5)) < 1e-5);
+}
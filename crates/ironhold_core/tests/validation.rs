@@ -0,0 +1,342 @@
+use std::fs;
+use std::path::PathBuf;
+use ironhold_core::validation::validate_project;
+
+/// Creates a scratch assets folder with the given files.
+fn write_assets(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("ironhold_validate_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    for (path, contents) in files {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+    root
+}
+
+const PLAYER_SCENE: &str = r#"(
+    schema_version: 1,
+    models: [
+        (path: "models/chest.glb#Scene0", position: (0.0, 0.0, 0.0)),
+    ],
+    player: Some((
+        model_path: "models/hero.glb#Scene0",
+        initial_position: (0.0, 0.0, 0.0),
+        camera: (
+            offset: (0.0, 5.0, 10.0),
+            look_at_offset: (0.0, 2.0, 0.0),
+            zoom_speed: 10.0,
+            orbit_speed: 0.5,
+            min_radius: 2.0,
+            max_radius: 20.0,
+        ),
+        inputs: (
+            forward: "KeyW",
+            backward: "KeyS",
+            left: "KeyA",
+            right: "KeyD",
+            strafe_left: "KeyQ",
+            strafe_right: "KeyE",
+            jump: "Space",
+        ),
+        animations: (
+            idle: "Idle",
+            walk: "Walk",
+            run: "Run",
+            jump_enter: "JumpEnter",
+            jump_loop: "JumpLoop",
+            jump_exit: "JumpExit",
+            death: "Death",
+            dance: "Dance",
+            crouch_idle: "CrouchIdle",
+            crouch_forward: "CrouchForward",
+            roll: "Roll",
+        ),
+    )),
+)"#;
+
+#[test]
+fn test_valid_project_follows_scene_graph() {
+    let root = write_assets("valid", &[
+        ("project.ron", r#"(schema_version: 1, initial_scene: "scenes/menu.ron")"#),
+        ("scenes/menu.ron", r#"(
+            schema_version: 1,
+            ui: [
+                Button(text: "Play", action: LoadScene("scenes/game.ron")),
+                Button(text: "Back", action: LoadScene("scenes/menu.ron")),
+            ]
+        )"#),
+        ("scenes/game.ron", r#"(schema_version: 1, models: [(path: "models/chest.glb#Scene0", position: (1.0, 0.0, 0.0))])"#),
+        ("models/chest.glb", ""),
+    ]);
+
+    let report = validate_project(&root.join("project.ron"), &root);
+    assert!(!report.has_errors(), "Unexpected diagnostics: {:?}", report.diagnostics);
+    assert_eq!(report.scenes, vec!["scenes/menu.ron", "scenes/game.ron"]);
+}
+
+#[test]
+fn test_missing_assets_are_reported() {
+    let root = write_assets("missing", &[
+        ("project.ron", r#"(schema_version: 1, initial_scene: "scenes/main.ron")"#),
+        ("scenes/main.ron", PLAYER_SCENE),
+        ("models/chest.glb", ""),
+    ]);
+
+    let report = validate_project(&root.join("project.ron"), &root);
+    let messages: Vec<String> = report.diagnostics.iter().map(|d| d.to_string()).collect();
+    assert_eq!(report.diagnostics.len(), 1, "{:?}", messages);
+
+    let model = &report.diagnostics[0];
+    assert!(model.message.contains("models/hero.glb"));
+    assert_eq!(model.line, Some(7));
+}
+
+#[test]
+fn test_unknown_keys_are_rejected_with_suggestion() {
+    let root = write_assets("keys", &[
+        ("project.ron", r#"(schema_version: 1, initial_scene: "scenes/main.ron")"#),
+        ("scenes/main.ron", &PLAYER_SCENE.replace(r#"jump: "Space""#, r#"jump: "Spacebar""#)),
+        ("models/chest.glb", ""),
+        ("models/hero.glb", ""),
+    ]);
+
+    let report = validate_project(&root.join("project.ron"), &root);
+    let messages: Vec<String> = report.diagnostics.iter().map(|d| d.to_string()).collect();
+    assert_eq!(report.diagnostics.len(), 1, "{:?}", messages);
+
+    let key = &report.diagnostics[0];
+    assert!(key.message.contains(r#"unknown key "Spacebar", did you mean "Space"?"#), "{}", key.message);
+    assert_eq!(key.line, Some(24));
+}
+
+#[test]
+fn test_parse_errors_report_position() {
+    let root = write_assets("parse", &[
+        ("project.ron", r#"(schema_version: 1, initial_scene: "scenes/broken.ron")"#),
+        ("scenes/broken.ron", "(\n    schema_version: 1,\n    models: [\n        (path: \"a.glb\" position: (0.0, 0.0, 0.0)),\n    ],\n)"),
+    ]);
+
+    let report = validate_project(&root.join("project.ron"), &root);
+    assert_eq!(report.diagnostics.len(), 1);
+    let diagnostic = &report.diagnostics[0];
+    assert!(diagnostic.file.ends_with("scenes/broken.ron"));
+    assert_eq!(diagnostic.line, Some(4));
+}
+
+#[test]
+fn test_missing_scene_is_reported() {
+    let root = write_assets("scene", &[
+        ("project.ron", "(\n    schema_version: 1,\n    initial_scene: \"scenes/nope.ron\",\n)"),
+    ]);
+
+    let report = validate_project(&root.join("project.ron"), &root);
+    assert_eq!(report.diagnostics.len(), 1);
+    assert_eq!(report.diagnostics[0].line, Some(3));
+    assert!(report.scenes.is_empty());
+}
+
+#[test]
+fn test_entity_references_are_checked() {
+    let root = write_assets("entities", &[
+        ("project.ron", r#"(schema_version: 1, initial_scene: "scenes/main.ron")"#),
+        ("scenes/main.ron", r#"(
+    schema_version: 1,
+    models: [
+        (id: Some("guard"), path: "models/chest.glb#Scene0", position: (0.0, 0.0, 0.0)),
+    ],
+    entities: [
+        (id: "guard", model: Some("models/guard.glb#Scene0")),
+        (
+            id: "lamp",
+            capabilities: [
+                PointLight(),
+                AnimationController(animations: (idle: "Idle", walk: "Walk", run: "Run", jump_enter: "", jump_loop: "", jump_exit: "", death: "", dance: "", crouch_idle: "", crouch_forward: "", roll: "")),
+            ],
+        ),
+        (
+            id: "cam",
+            capabilities: [
+                OrbitCamera(
+                    target: "hero",
+                    camera: (offset: (0.0, 5.0, 10.0), look_at_offset: (0.0, 2.0, 0.0), zoom_speed: 10.0, orbit_speed: 0.5, min_radius: 2.0, max_radius: 20.0),
+                ),
+            ],
+        ),
+    ],
+)"#),
+        ("models/chest.glb", ""),
+    ]);
+
+    let report = validate_project(&root.join("project.ron"), &root);
+    let messages: Vec<String> = report.diagnostics.iter().map(|d| d.message.clone()).collect();
+    assert_eq!(messages.len(), 4, "{:?}", messages);
+    assert!(messages[0].contains("duplicate entity id \"guard\""));
+    assert_eq!(report.diagnostics[0].line, Some(7));
+    assert!(messages[1].contains("models/guard.glb"));
+    assert!(messages[2].contains("AnimationController requires a model"));
+    assert!(messages[3].contains("target \"hero\" does not exist"));
+}
+
+#[test]
+fn test_prefab_and_include_references_are_checked() {
+    let root = write_assets("prefabs", &[
+        ("project.ron", r#"(schema_version: 1, initial_scene: "scenes/main.ron")"#),
+        ("prefabs/npc.ron", "(\n    schema_version: 1,\n    params: { \"target\": \"player\" },\n    model: Some(\"models/npc.glb#Scene0\"),\n)"),
+        ("prefabs/camera.ron", r#"(
+            schema_version: 1,
+            params: { "target": "npc_0" },
+            capabilities: [OrbitCamera(target: Param("target"), camera: (offset: (0.0, 3.0, 4.0), look_at_offset: (0.0, 1.0, 0.0), zoom_speed: 10.0, orbit_speed: 0.5, min_radius: 2.0, max_radius: 20.0))],
+        )"#),
+        ("scenes/shared.ron", r#"(schema_version: 1, prefabs: [(prefab: "prefabs/npc.ron")])"#),
+        ("scenes/main.ron", r#"(
+    schema_version: 1,
+    includes: ["scenes/shared.ron", "scenes/missing.ron"],
+    prefabs: [
+        (prefab: "prefabs/camera.ron"),
+        (prefab: "prefabs/camera.ron", id: Some("bad_cam"), params: { "target": "ghost" }),
+        (prefab: "prefabs/npc.ron", id: Some("typo"), params: { "targt": "x" }),
+        (prefab: "prefabs/nope.ron"),
+    ],
+)"#),
+    ]);
+
+    let report = validate_project(&root.join("project.ron"), &root);
+    let messages: Vec<String> = report.diagnostics.iter().map(|d| d.to_string()).collect();
+    assert_eq!(messages.len(), 5, "{:#?}", messages);
+    assert!(messages[0].contains("main.ron:3:") && messages[0].contains("scenes/missing.ron"));
+    assert!(messages[1].contains("npc.ron:4:") && messages[1].contains("models/npc.glb"));
+    assert!(messages[2].contains("unknown parameter \"targt\""));
+    assert!(messages[3].contains("prefab \"prefabs/nope.ron\" not found"));
+    assert!(messages[4].contains("\"bad_cam\": OrbitCamera target \"ghost\" does not exist"));
+    assert_eq!(report.scenes, ["scenes/main.ron", "scenes/shared.ron"]);
+}
+
+#[test]
+fn test_global_logic_is_checked_and_followed() {
+    let root = write_assets("logic", &[
+        ("project.ron", r#"(schema_version: 1, initial_scene: "scenes/menu.ron", logic: Some("logic/flow.ron"))"#),
+        ("scenes/menu.ron", r#"(schema_version: 1, ui: [Button(text: "Play", action: Emit("ui.start"))])"#),
+        ("scenes/main.ron", "(schema_version: 1)"),
+        ("logic/flow.ron", r#"(
+    schema_version: 1,
+    initial_state: "menu",
+    states: [(name: "menu"), (name: "game")],
+    transitions: [
+        (from: "menu", event: "ui.start", to: Some("game"), actions: [LoadScene("scenes/main.ron")]),
+        (from: "game", event: "key.Escape", to: Some("pause")),
+    ],
+)"#),
+    ]);
+
+    let report = validate_project(&root.join("project.ron"), &root);
+    let messages: Vec<String> = report.diagnostics.iter().map(|d| d.to_string()).collect();
+    assert_eq!(messages.len(), 1, "{:#?}", messages);
+    assert!(messages[0].contains("flow.ron:7:") && messages[0].contains("unknown state \"pause\""));
+    assert_eq!(report.scenes, ["scenes/menu.ron", "scenes/main.ron"]);
+}
+
+#[test]
+fn test_behaviors_are_checked_once() {
+    let root = write_assets("behaviors", &[
+        ("project.ron", r#"(schema_version: 1, initial_scene: "scenes/main.ron")"#),
+        ("scenes/main.ron", r#"(
+    schema_version: 1,
+    entities: [
+        (id: "door_a", capabilities: [Behavior(machine: "behaviors/door.ron")]),
+        (id: "door_b", capabilities: [Behavior(machine: "behaviors/door.ron")]),
+        (id: "ghost", capabilities: [Behavior(machine: "behaviors/ghost.ron")]),
+    ],
+)"#),
+        ("scenes/vault.ron", "(schema_version: 1)"),
+        ("behaviors/door.ron", r#"(
+    schema_version: 1,
+    initial_state: "closed",
+    states: [(name: "closed"), (name: "open", on_enter: [LoadScene("scenes/vault.ron")])],
+    transitions: [(from: "closed", event: "interact", to: Some("opened"))],
+)"#),
+    ]);
+
+    let report = validate_project(&root.join("project.ron"), &root);
+    let messages: Vec<String> = report.diagnostics.iter().map(|d| d.to_string()).collect();
+    assert_eq!(messages.len(), 2, "{:#?}", messages);
+    assert!(messages[0].contains("asset \"behaviors/ghost.ron\" not found"));
+    assert!(messages[1].contains("door.ron:5:") && messages[1].contains("unknown state \"opened\""));
+    assert_eq!(report.scenes, ["scenes/main.ron", "scenes/vault.ron"]);
+}
+
+#[test]
+fn test_action_assets_are_checked() {
+    let root = write_assets("actions", &[
+        ("project.ron", r#"(schema_version: 1, initial_scene: "scenes/main.ron")"#),
+        ("scenes/main.ron", r#"(
+    schema_version: 1,
+    ui: [
+        Button(text: "Options", action: Run([OpenUi("menus/options.ron"), PlaySound("sounds/click.ogg")])),
+        Button(text: "Coin", action: Run([SpawnPrefab(prefab: "prefabs/coin.ron"), LoadScene("scenes/end.ron")])),
+        Button(text: "Save", action: Run([SaveGame("quick"), LoadGame("../quick")])),
+    ],
+)"#),
+        ("scenes/end.ron", "(schema_version: 1)"),
+        ("menus/options.ron", r#"(schema_version: 1, elements: [])"#),
+    ]);
+
+    let report = validate_project(&root.join("project.ron"), &root);
+    let messages: Vec<String> = report.diagnostics.iter().map(|d| d.to_string()).collect();
+    assert_eq!(messages.len(), 3, "{:#?}", messages);
+    assert!(messages[0].contains("main.ron:4:") && messages[0].contains("asset \"sounds/click.ogg\" not found"));
+    assert!(messages[1].contains("main.ron:5:") && messages[1].contains("asset \"prefabs/coin.ron\" not found"));
+    assert!(messages[2].contains("main.ron:6:") && messages[2].contains("invalid save slot \"../quick\""));
+    assert_eq!(report.scenes, ["scenes/main.ron", "scenes/end.ron"]);
+}
+
+#[test]
+fn test_unknown_events_are_reported() {
+    let root = write_assets("events", &[
+        ("project.ron", r#"(schema_version: 1, initial_scene: "scenes/main.ron", logic: Some("logic/flow.ron"))"#),
+        ("logic/flow.ron", r#"(
+    schema_version: 1,
+    initial_state: "menu",
+    states: [(name: "menu", on_enter: [EmitEvent("menu.shown")])],
+    transitions: [(from: "menu", event: "ui.start")],
+)"#),
+        ("scenes/main.ron", r#"(
+    schema_version: 1,
+    ui: [
+        Button(text: "Start", action: Emit("ui.start")),
+        Button(text: "Quit", action: Emit("ui.qiut")),
+        Button(text: "Next", action: Run([Sequence([WaitForEvent("menu.shown")])])),
+    ],
+)"#),
+    ]);
+
+    let report = validate_project(&root.join("project.ron"), &root);
+    let messages: Vec<String> = report.diagnostics.iter().map(|d| d.to_string()).collect();
+    assert_eq!(messages.len(), 1, "{:#?}", messages);
+    assert!(messages[0].contains("main.ron:5:") && messages[0].contains("unknown event \"ui.qiut\""), "{}", messages[0]);
+}
+
+#[test]
+fn test_input_profile_is_checked() {
+    let root = write_assets("input", &[
+        ("project.ron", r#"(schema_version: 1, initial_scene: "scenes/main.ron", input: Some("input/profile.ron"))"#),
+        ("scenes/main.ron", "(schema_version: 1)"),
+        ("input/profile.ron", r#"(
+    schema_version: 1,
+    actions: {
+        "jump": Button([Key("Space"), Gamepad("Triangle")]),
+        "move": Axis([GamepadAxis("LeftStickW")]),
+        "look": Axis2d([MouseDrag("Center")]),
+    },
+)"#),
+    ]);
+
+    let report = validate_project(&root.join("project.ron"), &root);
+    let messages: Vec<String> = report.diagnostics.iter().map(|d| d.to_string()).collect();
+    assert_eq!(messages.len(), 4, "{:#?}", messages);
+    assert!(messages[0].contains("profile.ron:4:") && messages[0].contains("unknown gamepad button \"Triangle\" bound to action \"jump\""));
+    // Actions are checked in name order.
+    assert!(messages[1].contains("profile.ron:6:") && messages[1].contains("unknown mouse button \"Center\""));
+    assert!(messages[2].contains("profile.ron:5:") && messages[2].contains("action \"move\" must be Axis2d, found Axis"));
+    assert!(messages[3].contains("unknown gamepad axis \"LeftStickW\""));
+}
@@ -0,0 +1,118 @@
+use ironhold_core::runtime::{ConditionContext, Variables};
+use ironhold_core::schema::{from_versioned_str, Condition, Expression, GameLevel, ProjectConfig, Value};
+use std::collections::HashMap;
+
+fn variables() -> Variables {
+    let mut variables = Variables::default();
+    variables.set("score", Value::Int(12));
+    variables.set("speed", Value::Float(2.5));
+    variables.set("has_key", Value::Bool(false));
+    variables.set("difficulty", Value::String("hard".to_string()));
+    variables.set("spawn", Value::Vec3(0.0, 1.0, 0.0));
+    variables
+}
+
+fn check(source: &str) -> bool {
+    let variables = variables();
+    let mut context = ConditionContext {
+        variables: Some(&variables),
+        ..Default::default()
+    };
+    let expression = Expression::parse(source).unwrap_or_else(|e| panic!("{}: {}", source, e));
+    Condition::Expr(expression).evaluate(&mut context)
+}
+
+#[test]
+fn test_expressions() {
+    assert!(check("score >= 10 && !has_key"));
+    assert!(check("score > 12.0 || speed == 2.5"));
+    assert!(check("difficulty == \"hard\" and not (score < 5 or has_key)"));
+    assert!(check("spawn == vec3(0, 1, 0)"));
+    assert!(check("score != \"12\""));
+    assert!(check("score"));
+    assert!(!check("has_key"));
+    assert!(!check("difficulty < \"easy\""));
+
+    // Undefined variables are false, and so is any comparison with them.
+    assert!(!check("lives"));
+    assert!(!check("lives == 0"));
+    assert!(check("!lives"));
+}
+
+#[test]
+fn test_expression_errors_have_columns() {
+    let error = Expression::parse("score >= && has_key").unwrap_err();
+    assert_eq!(error.column, 10);
+    assert_eq!(error.to_string(), "expected a value, found `&&` at column 10");
+
+    assert_eq!(Expression::parse("(score > 1").unwrap_err().message, "expected `)`, found end of expression");
+    assert_eq!(Expression::parse("score > 1 2").unwrap_err().message, "unexpected number 2");
+    assert_eq!(Expression::parse("name == \"bob").unwrap_err().column, 9);
+    assert_eq!(Expression::parse("a = 1").unwrap_err().message, "unexpected character `=`");
+
+    let expression = Expression::parse("self.uses < max_uses").unwrap();
+    assert_eq!(expression.variables(), ["self.uses", "max_uses"]);
+}
+
+#[test]
+fn test_guards_read_locals_and_variables() {
+    let variables = variables();
+    let locals = HashMap::from([("uses".to_string(), Value::Int(3))]);
+    let mut context = ConditionContext {
+        variables: Some(&variables),
+        locals: Some(&locals),
+        ..Default::default()
+    };
+    let guard: Condition = ron::from_str(r#"Expr("self.uses < score && self.score == 0")"#).unwrap();
+    assert!(!guard.evaluate(&mut context));
+    let guard: Condition = ron::from_str(r#"All([Expr("self.uses == 3"), Not(Expr("has_key"))])"#).unwrap();
+    assert!(guard.evaluate(&mut context));
+
+    let error = ron::from_str::<Condition>(r#"Expr("score >")"#).unwrap_err();
+    assert!(error.to_string().contains("invalid expression \"score >\""), "{}", error);
+}
+
+#[test]
+fn test_set_add_and_toggle() {
+    let mut variables = variables();
+    variables.add("score", &Value::Int(3)).unwrap();
+    assert_eq!(variables.get("score"), Some(&Value::Int(15)));
+    variables.add("score", &Value::Float(0.5)).unwrap();
+    assert_eq!(variables.get("score"), Some(&Value::Float(15.5)));
+    variables.add("spawn", &Value::Vec3(1.0, 0.0, 0.0)).unwrap();
+    assert_eq!(variables.get("spawn"), Some(&Value::Vec3(1.0, 1.0, 0.0)));
+    variables.add("coins", &Value::Int(1)).unwrap();
+    assert_eq!(variables.get("coins"), Some(&Value::Int(1)));
+    assert!(variables.add("difficulty", &Value::Int(1)).is_err());
+
+    variables.toggle("has_key").unwrap();
+    assert_eq!(variables.get("has_key"), Some(&Value::Bool(true)));
+    variables.toggle("music").unwrap();
+    assert_eq!(variables.get("music"), Some(&Value::Bool(true)));
+    assert!(variables.toggle("score").is_err());
+
+    // Declaring keeps existing values.
+    variables.declare(&HashMap::from([
+        ("score".to_string(), Value::Int(0)),
+        ("lives".to_string(), Value::Int(3)),
+    ]));
+    assert_eq!(variables.get("score"), Some(&Value::Float(15.5)));
+    assert_eq!(variables.get("lives"), Some(&Value::Int(3)));
+}
+
+#[test]
+fn test_variable_defaults_parse() {
+    let project: ProjectConfig = from_versioned_str(r#"(
+        schema_version: 1,
+        initial_scene: "scenes/main.ron",
+        variables: { "score": 0, "volume": 0.8, "name": "hero", "muted": false, "checkpoint": (1.0, 0.0, 2.0) },
+    )"#).unwrap();
+    assert_eq!(project.variables["score"], Value::Int(0));
+    assert_eq!(project.variables["volume"], Value::Float(0.8));
+    assert_eq!(project.variables["name"], Value::String("hero".to_string()));
+    assert_eq!(project.variables["muted"], Value::Bool(false));
+    assert_eq!(project.variables["checkpoint"], Value::Vec3(1.0, 0.0, 2.0));
+
+    let level: GameLevel = from_versioned_str(r#"(schema_version: 1, variables: { "doors_open": 0 })"#).unwrap();
+    assert_eq!(level.variables["doors_open"], Value::Int(0));
+}
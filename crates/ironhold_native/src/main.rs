@@ -1,7 +1,117 @@
-use ironhold_core::start_app;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use bevy::app::AppExit;
+use ironhold_core::{start_app_with_options, StartOptions};
+use ironhold_core::schema::{from_versioned_str, InputRecording};
+use ironhold_core::utils::find_assets_folder;
+use ironhold_core::validation::validate_project;
 
-fn main() {
+fn main() -> ExitCode {
     let args: Vec<String> = std::env::args().collect();
-    let project_path = args.get(1).cloned();
-    start_app(project_path);
+
+    if args.get(1).map(String::as_str) == Some("validate") {
+        return validate(&args[2..]);
+    }
+
+    let usage = "usage: ironhold_native [project.ron] [--hot-reload] [--record <file>] [--replay <file> [--headless]]";
+    let mut options = StartOptions::default();
+    let mut iter = args[1..].iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--hot-reload" => options.hot_reload = true,
+            "--headless" => options.headless = true,
+            "--record" | "--replay" => {
+                let Some(file) = iter.next() else {
+                    eprintln!("error: {} requires a file", arg);
+                    eprintln!("{}", usage);
+                    return ExitCode::from(2);
+                };
+                if arg == "--record" {
+                    options.record = Some(file.clone());
+                } else {
+                    match read_recording(file) {
+                        Ok(recording) => options.replay = Some(recording),
+                        Err(e) => {
+                            eprintln!("error: {}: {}", file, e);
+                            return ExitCode::FAILURE;
+                        }
+                    }
+                }
+            }
+            _ if options.project_path.is_none() => options.project_path = Some(arg.clone()),
+            _ => {
+                eprintln!("error: unexpected argument \"{}\"", arg);
+                eprintln!("{}", usage);
+                return ExitCode::from(2);
+            }
+        }
+    }
+    if options.headless && options.replay.is_none() {
+        eprintln!("error: --headless requires --replay");
+        eprintln!("{}", usage);
+        return ExitCode::from(2);
+    }
+
+    match start_app_with_options(options) {
+        AppExit::Success => ExitCode::SUCCESS,
+        AppExit::Error(code) => ExitCode::from(code.get()),
+    }
+}
+
+fn read_recording(file: &str) -> Result<InputRecording, String> {
+    let source = std::fs::read_to_string(file).map_err(|e| e.to_string())?;
+    from_versioned_str::<InputRecording>(&source).map_err(|e| e.to_string())
+}
+
+/// `ironhold_native validate [project.ron] [--assets <dir>]`
+///
+/// Checks a project without opening a window. The assets folder defaults to
+/// the directory containing the project file.
+fn validate(args: &[String]) -> ExitCode {
+    let mut project_file = None;
+    let mut assets_root = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--assets" => match iter.next() {
+                Some(dir) => assets_root = Some(PathBuf::from(dir)),
+                None => {
+                    eprintln!("error: --assets requires a directory");
+                    return ExitCode::from(2);
+                }
+            },
+            _ if project_file.is_none() => project_file = Some(PathBuf::from(arg)),
+            _ => {
+                eprintln!("error: unexpected argument \"{}\"", arg);
+                eprintln!("usage: ironhold_native validate [project.ron] [--assets <dir>]");
+                return ExitCode::from(2);
+            }
+        }
+    }
+
+    let project_file = project_file.unwrap_or_else(|| find_assets_folder().join("project.ron"));
+    let assets_root = assets_root.unwrap_or_else(|| {
+        project_file
+            .parent()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("."))
+    });
+
+    let report = validate_project(&project_file, &assets_root);
+    for diagnostic in &report.diagnostics {
+        eprintln!("{}", diagnostic);
+    }
+
+    if report.has_errors() {
+        eprintln!(
+            "{} error(s) in {} scene(s)",
+            report.diagnostics.len(),
+            report.scenes.len()
+        );
+        ExitCode::FAILURE
+    } else {
+        println!("{}: OK ({} scene(s) checked)", project_file.display(), report.scenes.len());
+        ExitCode::SUCCESS
+    }
 }
@@ -1,7 +1,13 @@
 use wasm_bindgen::prelude::*;
-use ironhold_core::start_app;
+use ironhold_core::{start_app_with_options, StartOptions};
 
-#[wasm_bindgen(start)]
-pub fn start() {
-    start_app(None);
+/// Starts the engine; called by the page once the module is loaded, e.g.
+/// `start()` or `start("project.ron", true)`.
+#[wasm_bindgen]
+pub fn start(project_path: Option<String>, hot_reload: Option<bool>) {
+    start_app_with_options(StartOptions {
+        project_path,
+        hot_reload: hot_reload.unwrap_or(false),
+        ..Default::default()
+    });
 }